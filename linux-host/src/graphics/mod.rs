@@ -1,6 +1,6 @@
 use skia_safe::gpu::gl::{FramebufferInfo, Interface};
 use skia_safe::gpu::{BackendRenderTarget, DirectContext, SurfaceOrigin};
-use skia_safe::{ColorType, Surface};
+use skia_safe::{ColorType, IPoint, IRect, Surface};
 use snowland_universal::control::ControlMessage;
 use snowland_universal::host::SnowlandRenderer;
 use snowland_universal::rendering::display::Display;
@@ -149,6 +149,67 @@ impl SnowlandRenderer for SnowlandX11Renderer {
         let geometry = self.inner.window.get_geometry();
         Ok((geometry.width as _, geometry.height as _))
     }
+
+    fn get_windows(&self) -> Result<Vec<IRect>, Self::Error> {
+        let display = self.inner.display.as_ref().get_ref();
+        let root = display.default_screen().root_window();
+
+        // Without an EWMH compliant window manager there is no reliable window list
+        let client_list = match display.get_atom("_NET_CLIENT_LIST_STACKING") {
+            None => return Ok(Vec::new()),
+            Some(v) => v,
+        };
+
+        let window_type = display.get_or_create_atom("_NET_WM_WINDOW_TYPE");
+        let desktop_type = display.get_or_create_atom("_NET_WM_WINDOW_TYPE_DESKTOP");
+        let frame_extents = display.get_or_create_atom("_NET_FRAME_EXTENTS");
+
+        // Every window costs a few round trips, which is why the renderer only asks while a
+        // module needs the windows. Windows may be destroyed at any time, so failing requests
+        // are expected and detected by their results.
+        let windows = display.ignore_errors(|| {
+            root.get_window_list_property(client_list)
+                .into_iter()
+                .filter(|window| window.handle() != self.inner.window.handle())
+                .filter_map(|window| {
+                    let attributes = window.try_get_attributes()?;
+                    if !attributes.is_viewable()
+                        || window
+                            .get_atom_list_property(window_type)
+                            .contains(&desktop_type)
+                    {
+                        return None;
+                    }
+
+                    let (x, y) = window.translate_coordinates(0, 0, &self.inner.window)?;
+
+                    // The window manager decorations are part of the visible window
+                    let (left, right, top, bottom) =
+                        match window.get_cardinal_list_property(frame_extents)[..] {
+                            [left, right, top, bottom] => (left, right, top, bottom),
+                            _ => (0, 0, 0, 0),
+                        };
+
+                    Some(IRect::from_ltrb(
+                        x - left as i32,
+                        y - top as i32,
+                        x + (attributes.width() + right) as i32,
+                        y + (attributes.height() + bottom) as i32,
+                    ))
+                })
+                .collect()
+        });
+
+        Ok(windows)
+    }
+
+    fn get_pointer_position(&self) -> Result<Option<IPoint>, Self::Error> {
+        Ok(self
+            .inner
+            .window
+            .query_pointer()
+            .map(|(x, y)| IPoint::new(x, y)))
+    }
 }
 
 #[derive(Debug, Error)]
@@ -1,4 +1,4 @@
-use skia_safe::Surface;
+use skia_safe::{IPoint, IRect, Surface};
 
 /// Abstraction for the underlying platform host.
 pub trait SnowlandHost: Sized + 'static {
@@ -26,6 +26,22 @@ pub trait SnowlandRenderer {
 
     /// Retrieves the size of the area to be rendered.
     fn get_size(&self) -> Result<(u64, u64), Self::Error>;
+
+    /// Retrieves the areas covered by visible top level windows, ordered from the bottom most
+    /// to the top most window.
+    ///
+    /// The areas are relative to the rendered area. Hosts which can't determine the windows
+    /// return an empty list.
+    fn get_windows(&self) -> Result<Vec<IRect>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    /// Retrieves the position of the pointer relative to the rendered area.
+    ///
+    /// Hosts which can't determine the pointer position return `None`.
+    fn get_pointer_position(&self) -> Result<Option<IPoint>, Self::Error> {
+        Ok(None)
+    }
 }
 
 /// Helper type alias for the renderer error type of a specific host.
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 use std::sync::mpsc::Receiver;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use skia_safe::Surface;
+use skia_safe::{IRect, Point, Surface};
 
 use crate::rendering::display::Display;
 use crate::rendering::state::RendererStateMessage;
@@ -14,6 +14,10 @@ pub mod display;
 pub mod fonts;
 pub mod state;
 
+/// Querying windows requires round trips to the window system, so they are not refreshed
+/// every frame.
+const WINDOW_QUERY_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Contains the renderer and control over it.
 pub struct RendererContainer<H>
 where
@@ -28,6 +32,10 @@ where
     modules: Vec<Box<dyn BoundModuleRenderer>>,
     primary_display: Display,
     displays: HashMap<String, Display>,
+    windows: Vec<IRect>,
+    windows_requested: bool,
+    last_window_query: Option<Instant>,
+    pointer: Option<Point>,
 }
 
 impl<H> RendererContainer<H>
@@ -53,6 +61,10 @@ where
             modules: Vec::new(),
             primary_display: Display::uninitialized(),
             displays: HashMap::new(),
+            windows: Vec::new(),
+            windows_requested: false,
+            last_window_query: None,
+            pointer: None,
         })
     }
 
@@ -61,6 +73,11 @@ where
         loop {
             let (width, height) = self.renderer.get_size()?;
             self.resize(width, height)?;
+            self.update_windows()?;
+            self.pointer = self
+                .renderer
+                .get_pointer_position()?
+                .map(|position| Point::new(position.x as _, position.y as _));
 
             self.render_frame()?;
 
@@ -110,6 +127,27 @@ where
         Ok(())
     }
 
+    /// Queries the window list again if it is outdated and a module requested it during
+    /// the last frame.
+    fn update_windows(&mut self) -> Result<(), RendererError<H>> {
+        if !self.windows_requested {
+            self.windows.clear();
+            self.last_window_query = None;
+            return Ok(());
+        }
+
+        let outdated = self
+            .last_window_query
+            .map_or(true, |last| last.elapsed() >= WINDOW_QUERY_INTERVAL);
+
+        if outdated {
+            self.windows = self.renderer.get_windows()?;
+            self.last_window_query = Some(Instant::now());
+        }
+
+        Ok(())
+    }
+
     /// Renders a single frame and ticks the scene.
     fn render_frame(&mut self) -> Result<(), RendererError<H>> {
         let width = self.surface.width();
@@ -118,19 +156,25 @@ where
         let canvas = self.surface.canvas();
 
         let last_frame_time = std::mem::replace(&mut self.last_frame_time, Instant::now());
+        let mut windows_requested = false;
 
         for module in &mut self.modules {
             let mut data = SceneData::new(
                 canvas,
                 &self.primary_display,
                 &self.displays,
+                &self.windows,
+                self.pointer,
                 width,
                 height,
                 last_frame_time.elapsed(),
             );
             module.render(&mut data);
+            windows_requested |= data.windows_requested();
         }
 
+        self.windows_requested = windows_requested;
+
         self.surface.flush_and_submit();
         self.renderer.present()?;
 
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use skia_safe::Canvas;
+use skia_safe::{Canvas, IRect, Point};
 
 use crate::rendering::display::Display;
 
@@ -13,6 +13,9 @@ pub struct SceneData<'a> {
     canvas: &'a mut Canvas,
     primary_display: &'a Display,
     displays: &'a HashMap<String, Display>,
+    windows: &'a [IRect],
+    windows_requested: bool,
+    pointer: Option<Point>,
     width: i32,
     height: i32,
     delta: Duration,
@@ -23,6 +26,8 @@ impl<'a> SceneData<'a> {
         canvas: &'a mut Canvas,
         primary_display: &'a Display,
         displays: &'a HashMap<String, Display>,
+        windows: &'a [IRect],
+        pointer: Option<Point>,
         width: i32,
         height: i32,
         delta: Duration,
@@ -31,6 +36,9 @@ impl<'a> SceneData<'a> {
             canvas,
             primary_display,
             displays,
+            windows,
+            windows_requested: false,
+            pointer,
             width,
             height,
             delta,
@@ -66,4 +74,37 @@ impl<'a> SceneData<'a> {
     pub fn lookup_display(&self, id: &str) -> Option<&'a Display> {
         self.displays.get(id)
     }
+
+    /// Iterates all known displays.
+    pub fn displays(&self) -> impl Iterator<Item = &'a Display> {
+        self.displays.values()
+    }
+
+    /// The areas covered by visible top level windows, ordered from bottom to top.
+    ///
+    /// This is empty if the host can't determine the windows or no module requested them,
+    /// see [`SceneData::request_windows`].
+    pub fn windows(&self) -> &'a [IRect] {
+        self.windows
+    }
+
+    /// Asks the renderer to keep the windows up to date.
+    ///
+    /// Windows are only queried while a module requests them every frame, the list is
+    /// available from the frame after the first request.
+    pub fn request_windows(&mut self) {
+        self.windows_requested = true;
+    }
+
+    /// Determines whether a module rendering this scene requested the windows.
+    pub fn windows_requested(&self) -> bool {
+        self.windows_requested
+    }
+
+    /// The position of the pointer in canvas units.
+    ///
+    /// This is `None` if the pointer is outside the scene or the host can't determine it.
+    pub fn pointer_position(&self) -> Option<Point> {
+        self.pointer
+    }
 }
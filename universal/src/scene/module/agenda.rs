@@ -0,0 +1,254 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Utc};
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use serde::{Deserialize, Serialize};
+use skia_safe::scalar;
+
+use crate::scene::module::part::{
+    FontSetting, HorizontalPositionAnchor, ModulePosition, PaintSetting,
+};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::util::{expand_placeholders, CalendarFile, Occurrence};
+
+pub(super) struct AgendaModule;
+
+impl Module for AgendaModule {
+    type Config = AgendaModuleConfig;
+    type Renderer = AgendaModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        AgendaModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Agenda".into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaModuleConfig {
+    position: ModulePosition,
+    alignment: HorizontalPositionAnchor,
+
+    /// Path to the iCalendar file.
+    path: String,
+
+    /// Maximum amount of events to list.
+    count: i32,
+
+    /// Template for upcoming events, see [`AgendaModuleConfig::TEMPLATE_HELP`].
+    template: String,
+
+    /// Template for events which are currently taking place.
+    ongoing_template: String,
+
+    /// Line displayed when there are no upcoming events.
+    empty_message: String,
+
+    paint: PaintSetting,
+    font: FontSetting,
+}
+
+impl AgendaModuleConfig {
+    const TEMPLATE_HELP: &'static str = "\
+Placeholders: {summary} {start} {end}
+Custom time format: {start:%d.%m. %H:%M}";
+
+    /// Renders a template for a single occurrence.
+    fn render(template: &str, occurrence: &Occurrence, out: &mut String) {
+        expand_placeholders(template, out, |key, format, out| {
+            Self::render_placeholder(key, format, occurrence, out)
+        });
+    }
+
+    /// Renders a single placeholder, returns `false` if the placeholder is unknown.
+    fn render_placeholder(
+        key: &str,
+        format: Option<&str>,
+        occurrence: &Occurrence,
+        out: &mut String,
+    ) -> bool {
+        let format = format.unwrap_or("%a %H:%M");
+
+        let time = match key {
+            "summary" => {
+                out.push_str(&occurrence.summary);
+                return true;
+            }
+            "start" => occurrence.start,
+            "end" => occurrence.end,
+            _ => return false,
+        };
+
+        let len = out.len();
+        if write!(out, "{}", time.with_timezone(&Local).format(format)).is_err() {
+            out.truncate(len);
+            out.push_str("<invalid format>");
+        }
+
+        true
+    }
+}
+
+impl Default for AgendaModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            alignment: Default::default(),
+            path: String::new(),
+            count: 5,
+            template: String::from("{start} {summary}"),
+            ongoing_template: String::from("Now: {summary} (until {end:%H:%M})"),
+            empty_message: String::from("No upcoming events"),
+            paint: PaintSetting::default(),
+            font: FontSetting::default(),
+        }
+    }
+}
+
+impl ModuleConfig for AgendaModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            if let Some(_tok) = ui.begin_table("Module Options", 2) {
+                ui.table_next_row();
+                ui.table_next_column();
+
+                InputText::new(ui, "Calendar", &mut self.path)
+                    .hint("Path to an .ics file")
+                    .build();
+                ui.table_next_column();
+                if ui.small_button("...") {
+                    match FileDialog::new()
+                        .add_filter("iCalendar", &["ics"])
+                        .show_open_single_file()
+                    {
+                        Ok(None) => {}
+                        Ok(Some(p)) => self.path = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+
+            Drag::new("Events")
+                .range(1, 100)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.count);
+        }
+
+        if ui.collapsing_header("Format", TreeNodeFlags::FRAMED) {
+            ui.text("Line alignment");
+            self.alignment.represent(ui, ctx);
+            ui.separator();
+
+            InputText::new(ui, "Template", &mut self.template).build();
+            if ui.is_item_hovered() {
+                ui.tooltip_text(Self::TEMPLATE_HELP);
+            }
+
+            InputText::new(ui, "Ongoing template", &mut self.ongoing_template).build();
+            if ui.is_item_hovered() {
+                ui.tooltip_text(Self::TEMPLATE_HELP);
+            }
+
+            InputText::new(ui, "No events message", &mut self.empty_message).build();
+        }
+    }
+}
+
+pub struct AgendaModuleRenderer {
+    calendar: CalendarFile,
+    lines: Vec<String>,
+}
+
+impl AgendaModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            calendar: CalendarFile::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Formats the upcoming events into the line buffer.
+    fn update_lines(&mut self, config: &AgendaModuleConfig, now: &DateTime<Utc>) {
+        self.calendar.update(&config.path);
+        let upcoming = self.calendar.upcoming(now, config.count.max(1) as usize);
+
+        let count = upcoming.len().max(1);
+        self.lines.resize_with(count, String::new);
+        self.lines.truncate(count);
+
+        if upcoming.is_empty() {
+            self.lines[0].clear();
+            self.lines[0].push_str(&config.empty_message);
+            return;
+        }
+
+        for (occurrence, line) in upcoming.iter().zip(self.lines.iter_mut()) {
+            let template = if occurrence.is_ongoing(now) {
+                &config.ongoing_template
+            } else {
+                &config.template
+            };
+
+            line.clear();
+            AgendaModuleConfig::render(template, occurrence, line);
+        }
+    }
+}
+
+impl ModuleRenderer for AgendaModuleRenderer {
+    type Config = AgendaModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        self.update_lines(config, &Utc::now());
+
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+
+        let widths = self
+            .lines
+            .iter()
+            .map(|line| font.measure_str(line, None).0)
+            .collect::<Vec<_>>();
+
+        let width = widths.iter().fold(0.0, |width: scalar, w| width.max(*w));
+        let height = line_height * self.lines.len() as scalar;
+
+        if let Some((x, y)) = config
+            .position
+            .compute_position(data, width as i32, height as i32)
+        {
+            let canvas = data.canvas();
+
+            for (i, (line, line_width)) in self.lines.iter().zip(widths).enumerate() {
+                let line_x = x + config.alignment.compute(width as i32, line_width as i32);
+                let line_y = y as scalar - metrics.ascent + (line_height * i as scalar);
+
+                font.draw_str(
+                    canvas,
+                    line,
+                    (line_x as scalar, line_y),
+                    config.paint.get_paint(),
+                );
+            }
+        }
+    }
+}
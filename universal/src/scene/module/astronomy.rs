@@ -0,0 +1,338 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Utc};
+use imgui::{Drag, InputText, InputTextMultiline, SliderFlags, TreeNodeFlags, Ui};
+use serde::{Deserialize, Serialize};
+use skia_safe::{scalar, Path, Point};
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::sky::build_moon_path;
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::util::{
+    expand_placeholders, moon_illumination, moon_phase, moon_phase_name, sun_times, Observer,
+    SunTimes,
+};
+
+pub(super) struct AstronomyModule;
+
+impl Module for AstronomyModule {
+    type Config = AstronomyModuleConfig;
+    type Renderer = AstronomyModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        AstronomyModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Astronomy".into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AstronomyModuleConfig {
+    position: ModulePosition,
+
+    /// Location of the observer in degrees.
+    latitude: f64,
+    longitude: f64,
+
+    /// The text template, see [`AstronomyModuleConfig::TEMPLATE_HELP`].
+    template: String,
+
+    /// The chrono (strftime like) format string used for times of day.
+    time_format: String,
+
+    /// Whether the moon phase is drawn in front of the text.
+    icon: bool,
+
+    paint: PaintSetting,
+
+    /// Paint of the unlit part of the moon.
+    shadow_paint: PaintSetting,
+
+    font: FontSetting,
+}
+
+impl AstronomyModuleConfig {
+    const TEMPLATE_HELP: &'static str = "\
+Placeholders: {sunrise} {sunset} {dawn} {dusk} {noon}
+{day_length} {daylight_left} {phase} {illumination}
+{dawn} and {dusk} are the start and end of the civil twilight.
+Events which don't happen today, such as during the polar night, are shown as --";
+
+    fn observer(&self) -> Observer {
+        Observer {
+            latitude: self.latitude,
+            longitude: self.longitude,
+        }
+    }
+
+    /// Renders the text template, invalid time formats yield [`None`].
+    fn render(
+        &self,
+        now: DateTime<Utc>,
+        times: &SunTimes,
+        phase: f64,
+        out: &mut String,
+    ) -> Option<()> {
+        let mut valid = true;
+
+        expand_placeholders(&self.template, out, |name, argument, out| {
+            if argument.is_some() {
+                return false;
+            }
+
+            match name {
+                "sunrise" => valid &= self.write_time(times.sunrise, out).is_some(),
+                "sunset" => valid &= self.write_time(times.sunset, out).is_some(),
+                "dawn" => valid &= self.write_time(times.dawn, out).is_some(),
+                "dusk" => valid &= self.write_time(times.dusk, out).is_some(),
+                "noon" => valid &= self.write_time(Some(times.solar_noon), out).is_some(),
+                "day_length" => write_duration(times.day_length(), out),
+                "daylight_left" => write_duration(times.daylight_left(now), out),
+                "phase" => out.push_str(moon_phase_name(phase)),
+                "illumination" => {
+                    let _ = write!(out, "{:.0}", moon_illumination(phase) * 100.0);
+                }
+                _ => return false,
+            }
+
+            true
+        });
+
+        if valid {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn write_time(&self, time: Option<DateTime<Utc>>, out: &mut String) -> Option<()> {
+        match time {
+            None => out.push_str("--"),
+            Some(time) => write!(
+                out,
+                "{}",
+                time.with_timezone(&Local).format(&self.time_format)
+            )
+            .ok()?,
+        }
+
+        Some(())
+    }
+}
+
+/// Writes a duration as hours and minutes.
+fn write_duration(duration: chrono::Duration, out: &mut String) {
+    let minutes = duration.num_minutes().max(0);
+    let _ = write!(out, "{}:{:02}", minutes / 60, minutes % 60);
+}
+
+impl Default for AstronomyModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            latitude: 52.52,
+            longitude: 13.405,
+            template: String::from(
+                "Sunrise {sunrise}, sunset {sunset}\n\
+                {daylight_left} of {day_length} daylight left\n\
+                {phase} ({illumination}%)",
+            ),
+            time_format: String::from("%H:%M"),
+            icon: true,
+            paint: PaintSetting::default(),
+            shadow_paint: PaintSetting::from_rgba(1.0, 1.0, 1.0, 0.2),
+            font: FontSetting::default(),
+        }
+    }
+}
+
+impl ModuleConfig for AstronomyModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Location", TreeNodeFlags::FRAMED) {
+            Drag::new("Latitude")
+                .range(-90.0, 90.0)
+                .speed(0.01)
+                .display_format("%.4f")
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.latitude);
+            Drag::new("Longitude")
+                .range(-180.0, 180.0)
+                .speed(0.01)
+                .display_format("%.4f")
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.longitude);
+
+            ui.text_disabled("North and east are positive");
+        }
+
+        if ui.collapsing_header("Format", TreeNodeFlags::FRAMED) {
+            InputTextMultiline::new(ui, "Template", &mut self.template, [0.0, 80.0]).build();
+
+            if ui.is_item_hovered() {
+                ui.tooltip_text(Self::TEMPLATE_HELP);
+            }
+
+            InputText::new(ui, "Time format", &mut self.time_format).build();
+
+            ui.checkbox("Moon phase icon", &mut self.icon);
+            if self.icon {
+                let _id = ui.push_id("Shadow");
+                ui.text("Unlit part of the moon");
+                self.shadow_paint.represent(ui, ctx);
+            }
+        }
+    }
+}
+
+/// The inputs the text has last been rendered from.
+struct RenderedFor {
+    minute: i64,
+    observer: Observer,
+    template: String,
+    time_format: String,
+}
+
+impl RenderedFor {
+    fn matches(&self, minute: i64, config: &AstronomyModuleConfig) -> bool {
+        self.minute == minute
+            && self.observer == config.observer()
+            && self.template == config.template
+            && self.time_format == config.time_format
+    }
+}
+
+pub struct AstronomyModuleRenderer {
+    rendered: Option<RenderedFor>,
+
+    text: String,
+    phase: f64,
+    moon_path: Path,
+}
+
+impl AstronomyModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            rendered: None,
+            text: String::new(),
+            phase: 0.0,
+            moon_path: Path::new(),
+        }
+    }
+
+    /// Renders the text again once a minute or when the configuration changed.
+    fn update(&mut self, config: &AstronomyModuleConfig) {
+        let now = Utc::now();
+        let minute = now.timestamp() / 60;
+
+        if let Some(rendered) = &self.rendered {
+            if rendered.matches(minute, config) {
+                return;
+            }
+        }
+
+        // Local noon selects today's events regardless of the time zone
+        let noon = Local::today().and_hms(12, 0, 0).with_timezone(&Utc);
+        let times = sun_times(config.observer(), noon);
+        self.phase = moon_phase(now);
+
+        self.text.clear();
+        if config
+            .render(now, &times, self.phase, &mut self.text)
+            .is_none()
+        {
+            self.text.clear();
+            self.text.push_str("<invalid format>");
+        }
+
+        self.rendered = Some(RenderedFor {
+            minute,
+            observer: config.observer(),
+            template: config.template.clone(),
+            time_format: config.time_format.clone(),
+        });
+    }
+}
+
+impl ModuleRenderer for AstronomyModuleRenderer {
+    type Config = AstronomyModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        self.update(config);
+
+        let font = &config.font;
+        let paint = config.paint.get_paint();
+        let (line_height, metrics) = font.get_font().metrics();
+
+        let lines = self.text.lines().collect::<Vec<_>>();
+        let text_width = lines
+            .iter()
+            .map(|line| font.measure_str(line, None).0)
+            .fold(0.0, scalar::max);
+        let text_height = line_height * lines.len() as scalar;
+
+        let icon_size = if config.icon {
+            text_height.max(line_height)
+        } else {
+            0.0
+        };
+        let text_x = if config.icon {
+            icon_size + line_height / 2.0
+        } else {
+            0.0
+        };
+
+        let width = text_x + text_width;
+        let height = text_height.max(icon_size);
+
+        let (x, y) = match config
+            .position
+            .compute_position(data, width as i32, height as i32)
+        {
+            None => return,
+            Some((x, y)) => (x as scalar, y as scalar),
+        };
+
+        if config.icon {
+            let center = Point::new(x + icon_size / 2.0, y + height / 2.0);
+            let radius = icon_size / 2.0;
+
+            let mut shadow = config.shadow_paint.get_paint().clone();
+            shadow.set_anti_alias(true);
+            data.canvas().draw_circle(center, radius, &shadow);
+
+            build_moon_path(
+                &mut self.moon_path,
+                center,
+                radius,
+                self.phase,
+                config.latitude < 0.0,
+            );
+
+            let mut lit = paint.clone();
+            lit.set_anti_alias(true);
+            data.canvas().draw_path(&self.moon_path, &lit);
+        }
+
+        let mut line_y = y + (height - text_height) / 2.0 - metrics.ascent;
+        for line in lines {
+            font.draw_str(data.canvas(), line, (x + text_x, line_y), paint);
+            line_y += line_height;
+        }
+    }
+}
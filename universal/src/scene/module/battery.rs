@@ -0,0 +1,455 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use serde::{Deserialize, Serialize};
+use skia_safe::{paint::Style, scalar, Paint, Rect};
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::util::{
+    expand_placeholders, read_batteries, BatteryStatus, Sampler, DEFAULT_POWER_SUPPLY_ROOT,
+};
+
+pub(super) struct BatteryModule;
+
+impl Module for BatteryModule {
+    type Config = BatteryModuleConfig;
+    type Renderer = BatteryModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        BatteryModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Battery".into()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BatteryDisplay {
+    Text,
+    Gauge,
+    GaugeAndText,
+}
+
+impl BatteryDisplay {
+    const VALUES: [Self; 3] = [Self::Text, Self::Gauge, Self::GaugeAndText];
+
+    fn has_text(&self) -> bool {
+        matches!(self, Self::Text | Self::GaugeAndText)
+    }
+
+    fn has_gauge(&self) -> bool {
+        matches!(self, Self::Gauge | Self::GaugeAndText)
+    }
+}
+
+impl Default for BatteryDisplay {
+    fn default() -> Self {
+        Self::GaugeAndText
+    }
+}
+
+impl ModuleConfig for BatteryDisplay {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Display", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Text => "Text",
+                Self::Gauge => "Gauge",
+                Self::GaugeAndText => "Gauge and text",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current].clone();
+    }
+}
+
+/// Changes the color once the charge level drops to or below a level.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatteryThreshold {
+    /// Charge level in percent.
+    level: i32,
+    paint: PaintSetting,
+}
+
+impl Default for BatteryThreshold {
+    fn default() -> Self {
+        Self {
+            level: 20,
+            paint: PaintSetting::from_rgba(1.0, 0.3, 0.2, 1.0),
+        }
+    }
+}
+
+impl ModuleConfig for BatteryThreshold {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        Drag::new("Level (%)")
+            .range(0, 100)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut self.level);
+
+        self.paint.represent(ui, ctx);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatteryModuleConfig {
+    position: ModulePosition,
+
+    /// Directory containing the power supplies, usually `/sys/class/power_supply`.
+    root: String,
+
+    /// Name of the battery to display, the first battery is used if empty.
+    battery: String,
+
+    /// Time between two readings, in seconds.
+    interval: i32,
+
+    display: BatteryDisplay,
+
+    /// The text template, see [`BatteryModuleConfig::TEMPLATE_HELP`].
+    template: String,
+
+    gauge_width: f32,
+    gauge_height: f32,
+
+    thresholds: Vec<BatteryThreshold>,
+
+    paint: PaintSetting,
+    font: FontSetting,
+}
+
+impl BatteryModuleConfig {
+    const TEMPLATE_HELP: &'static str = "\
+Placeholders: {name} {capacity} {state} {remaining}
+{remaining} is empty if the battery doesn't report a rate.";
+
+    /// Selects the paint for the given charge level.
+    ///
+    /// The threshold with the lowest level which is still at or above the charge level wins.
+    fn paint_for(&self, capacity: f32) -> &Paint {
+        let percent = (capacity * 100.0).round() as i32;
+
+        self.thresholds
+            .iter()
+            .filter(|threshold| percent <= threshold.level)
+            .min_by_key(|threshold| threshold.level)
+            .map_or(self.paint.get_paint(), |threshold| {
+                threshold.paint.get_paint()
+            })
+    }
+
+    /// Renders the text template for a battery.
+    fn render(&self, battery: &BatteryStatus, out: &mut String) {
+        expand_placeholders(&self.template, out, |name, argument, out| {
+            let _ = match (name, argument) {
+                ("name", None) => write!(out, "{}", battery.name),
+                ("capacity", None) => write!(out, "{:.0}", battery.capacity * 100.0),
+                ("state", None) => write!(out, "{}", battery.state.name()),
+                ("remaining", None) => match battery.time_remaining {
+                    None => Ok(()),
+                    Some(remaining) => {
+                        let minutes = remaining.as_secs() / 60;
+                        write!(out, "{}:{:02}", minutes / 60, minutes % 60)
+                    }
+                },
+                _ => return false,
+            };
+
+            true
+        });
+    }
+}
+
+impl Default for BatteryModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            root: String::from(DEFAULT_POWER_SUPPLY_ROOT),
+            battery: String::new(),
+            interval: 10,
+            display: BatteryDisplay::default(),
+            template: String::from("{capacity}% {state} {remaining}"),
+            gauge_width: 64.0,
+            gauge_height: 32.0,
+            thresholds: vec![
+                BatteryThreshold {
+                    level: 30,
+                    paint: PaintSetting::from_rgba(1.0, 0.7, 0.2, 1.0),
+                },
+                BatteryThreshold::default(),
+            ],
+            paint: PaintSetting::default(),
+            font: FontSetting::default(),
+        }
+    }
+}
+
+impl ModuleConfig for BatteryModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            if let Some(_tok) = ui.begin_table("Module Options", 2) {
+                ui.table_next_row();
+                ui.table_next_column();
+
+                InputText::new(ui, "Power supply directory", &mut self.root).build();
+                ui.table_next_column();
+                if ui.small_button("...") {
+                    match FileDialog::new().show_open_single_dir() {
+                        Ok(None) => {}
+                        Ok(Some(p)) => self.root = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+
+            InputText::new(ui, "Battery", &mut self.battery)
+                .hint("First battery, or for example BAT0")
+                .build();
+
+            Drag::new("Interval (s)")
+                .range(1, 60 * 60)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.interval);
+        }
+
+        if ui.collapsing_header("Format", TreeNodeFlags::FRAMED) {
+            self.display.represent(ui, ctx);
+
+            if self.display.has_text() {
+                InputText::new(ui, "Template", &mut self.template).build();
+
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(Self::TEMPLATE_HELP);
+                }
+            }
+
+            if self.display.has_gauge() {
+                Drag::new("Gauge width")
+                    .range(4.0, 10000.0)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.gauge_width);
+
+                Drag::new("Gauge height")
+                    .range(4.0, 10000.0)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.gauge_height);
+            }
+        }
+
+        if ui.collapsing_header("Thresholds", TreeNodeFlags::FRAMED) {
+            let mut to_remove = None;
+
+            for (i, threshold) in self.thresholds.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                threshold.represent(ui, ctx);
+
+                if ui.button("Remove threshold") {
+                    to_remove = Some(i);
+                }
+
+                ui.separator();
+            }
+
+            if let Some(i) = to_remove {
+                self.thresholds.remove(i);
+            }
+
+            if ui.button("Add threshold") {
+                self.thresholds.push(BatteryThreshold::default());
+            }
+        }
+    }
+}
+
+type BatteryReading = Result<Vec<BatteryStatus>, String>;
+
+pub struct BatteryModuleRenderer {
+    root: Arc<Mutex<PathBuf>>,
+    sampler: Sampler<BatteryReading>,
+    reading: BatteryReading,
+    text: String,
+}
+
+impl BatteryModuleRenderer {
+    pub fn new() -> Self {
+        let root = Arc::new(Mutex::new(PathBuf::from(DEFAULT_POWER_SUPPLY_ROOT)));
+        let sampler_root = root.clone();
+
+        Self {
+            root,
+            sampler: Sampler::spawn("Battery", Duration::from_secs(10), move || {
+                let root = sampler_root.lock().unwrap().clone();
+                read_batteries(&root).map_err(|err| format!("{}: {}", root.display(), err))
+            }),
+            reading: Ok(Vec::new()),
+            text: String::new(),
+        }
+    }
+
+    /// Passes configuration changes on to the sampler.
+    fn update_sampler(&mut self, config: &BatteryModuleConfig) {
+        self.sampler
+            .set_interval(Duration::from_secs(config.interval.max(1) as u64));
+
+        let mut root = self.root.lock().unwrap();
+        if root.as_os_str() != config.root.as_str() {
+            *root = PathBuf::from(&config.root);
+            self.sampler.trigger();
+        }
+    }
+
+    /// Draws a battery shaped gauge with its top left corner at the given position.
+    fn draw_gauge(
+        config: &BatteryModuleConfig,
+        data: &mut SceneData,
+        x: scalar,
+        y: scalar,
+        capacity: f32,
+        paint: &Paint,
+    ) {
+        let canvas = data.canvas();
+
+        let stroke = (config.gauge_height / 12.0).max(1.0);
+        let terminal_width = config.gauge_width / 12.0;
+        let body_width = config.gauge_width - terminal_width;
+
+        let mut outline = paint.clone();
+        outline.set_style(Style::Stroke);
+        outline.set_stroke_width(stroke);
+
+        let mut fill = paint.clone();
+        fill.set_style(Style::Fill);
+
+        let body = Rect::from_xywh(x, y, body_width, config.gauge_height)
+            .with_inset((stroke / 2.0, stroke / 2.0));
+        canvas.draw_round_rect(body, stroke * 2.0, stroke * 2.0, &outline);
+
+        let terminal = Rect::from_xywh(
+            x + body_width,
+            y + config.gauge_height / 3.0,
+            terminal_width,
+            config.gauge_height / 3.0,
+        );
+        canvas.draw_rect(terminal, &fill);
+
+        let inner = body.with_inset((stroke * 1.5, stroke * 1.5));
+        let level = Rect::from_xywh(
+            inner.left,
+            inner.top,
+            inner.width() * capacity,
+            inner.height(),
+        );
+        canvas.draw_rect(level, &fill);
+    }
+}
+
+impl ModuleRenderer for BatteryModuleRenderer {
+    type Config = BatteryModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        self.update_sampler(config);
+
+        if let Some(reading) = self.sampler.take() {
+            if let Err(err) = &reading {
+                if self.reading.is_ok() {
+                    log::warn!("Failed to read batteries from {}", err);
+                }
+            }
+
+            self.reading = reading;
+        }
+
+        let battery = match &self.reading {
+            Ok(batteries) => batteries
+                .iter()
+                .find(|battery| config.battery.is_empty() || battery.name == config.battery),
+            Err(_) => None,
+        };
+
+        self.text.clear();
+        let (capacity, paint) = match battery {
+            Some(battery) => {
+                config.render(battery, &mut self.text);
+                (battery.capacity, config.paint_for(battery.capacity))
+            }
+            None => {
+                self.text.push_str("No battery");
+                (0.0, config.paint.get_paint())
+            }
+        };
+
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+        let (text_width, _) = font.measure_str(self.text.trim_end(), None);
+
+        let (gauge_width, gauge_height) = if config.display.has_gauge() {
+            (config.gauge_width, config.gauge_height)
+        } else {
+            (0.0, 0.0)
+        };
+
+        let (text_x, text_width) = match config.display {
+            BatteryDisplay::Text => (0.0, text_width),
+            BatteryDisplay::Gauge => (0.0, 0.0),
+            BatteryDisplay::GaugeAndText => (gauge_width + line_height / 4.0, text_width),
+        };
+
+        let width = text_x + text_width;
+        let height = gauge_height.max(if text_width > 0.0 { line_height } else { 0.0 });
+
+        let (x, y) = match config
+            .position
+            .compute_position(data, width as i32, height as i32)
+        {
+            None => return,
+            Some((x, y)) => (x as scalar, y as scalar),
+        };
+
+        if config.display.has_gauge() {
+            Self::draw_gauge(
+                config,
+                data,
+                x,
+                y + (height - gauge_height) / 2.0,
+                capacity,
+                paint,
+            );
+        }
+
+        if config.display.has_text() {
+            let text_y = y + (height - line_height) / 2.0 - metrics.ascent;
+
+            font.draw_str(
+                data.canvas(),
+                self.text.trim_end(),
+                (x + text_x, text_y),
+                paint,
+            );
+        }
+    }
+}
@@ -0,0 +1,573 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, Duration, Local, Locale, NaiveDate, TimeZone, Utc, Weekday};
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use serde::{Deserialize, Serialize};
+use skia_safe::{scalar, Paint, Rect, TextBlob};
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+
+pub(super) struct CalendarModule;
+
+impl Module for CalendarModule {
+    type Config = CalendarModuleConfig;
+    type Renderer = CalendarModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        CalendarModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Calendar".into()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FirstWeekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl FirstWeekday {
+    const VALUES: [Self; 7] = [
+        Self::Monday,
+        Self::Tuesday,
+        Self::Wednesday,
+        Self::Thursday,
+        Self::Friday,
+        Self::Saturday,
+        Self::Sunday,
+    ];
+
+    fn weekday(&self) -> Weekday {
+        match self {
+            FirstWeekday::Monday => Weekday::Mon,
+            FirstWeekday::Tuesday => Weekday::Tue,
+            FirstWeekday::Wednesday => Weekday::Wed,
+            FirstWeekday::Thursday => Weekday::Thu,
+            FirstWeekday::Friday => Weekday::Fri,
+            FirstWeekday::Saturday => Weekday::Sat,
+            FirstWeekday::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+impl Default for FirstWeekday {
+    fn default() -> Self {
+        Self::Monday
+    }
+}
+
+impl ModuleConfig for FirstWeekday {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("First weekday", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Monday => "Monday",
+                Self::Tuesday => "Tuesday",
+                Self::Wednesday => "Wednesday",
+                Self::Thursday => "Thursday",
+                Self::Friday => "Friday",
+                Self::Saturday => "Saturday",
+                Self::Sunday => "Sunday",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current].clone();
+    }
+}
+
+/// A date highlighted in the calendar.
+///
+/// Dates without a year (`MM-DD`) are marked every year.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MarkedDate {
+    date: String,
+}
+
+impl MarkedDate {
+    /// Determines whether this entry marks the given date, invalid entries mark nothing.
+    fn matches(&self, date: NaiveDate) -> bool {
+        let value = self.date.trim();
+
+        if let Ok(marked) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return marked == date;
+        }
+
+        match value.split_once('-') {
+            Some((month, day)) => {
+                month.parse() == Ok(date.month()) && day.parse() == Ok(date.day())
+            }
+            None => false,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        let value = self.date.trim();
+
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+            || NaiveDate::parse_from_str(&format!("2000-{}", value), "%Y-%m-%d").is_ok()
+    }
+}
+
+impl Default for MarkedDate {
+    fn default() -> Self {
+        Self {
+            date: Local::today().format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+impl ModuleConfig for MarkedDate {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        InputText::new(ui, "Date", &mut self.date)
+            .hint("YYYY-MM-DD or MM-DD")
+            .build();
+
+        if !self.is_valid() {
+            ui.text_colored(ERROR_COLOR, "Invalid date, expected YYYY-MM-DD or MM-DD");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarModuleConfig {
+    position: ModulePosition,
+    first_weekday: FirstWeekday,
+    week_numbers: bool,
+
+    /// Whether the month and year are displayed above the grid.
+    title: bool,
+
+    /// The POSIX name of the locale used for month and weekday names.
+    locale: String,
+
+    /// Additional space around each cell.
+    spacing: f32,
+
+    marked_dates: Vec<MarkedDate>,
+
+    font: FontSetting,
+    paint: PaintSetting,
+    title_paint: PaintSetting,
+    weekday_paint: PaintSetting,
+    week_number_paint: PaintSetting,
+    weekend_paint: PaintSetting,
+    today_paint: PaintSetting,
+    today_background_paint: PaintSetting,
+    marked_paint: PaintSetting,
+}
+
+impl CalendarModuleConfig {
+    fn locale(&self) -> Locale {
+        self.locale.parse::<Locale>().unwrap_or(Locale::POSIX)
+    }
+
+    fn is_marked(&self, date: NaiveDate) -> bool {
+        self.marked_dates.iter().any(|marked| marked.matches(date))
+    }
+}
+
+impl Default for CalendarModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            first_weekday: FirstWeekday::default(),
+            week_numbers: true,
+            title: true,
+            locale: String::new(),
+            spacing: 8.0,
+            marked_dates: Vec::new(),
+            font: FontSetting::default(),
+            paint: PaintSetting::default(),
+            title_paint: PaintSetting::default(),
+            weekday_paint: PaintSetting::from_rgba(0.7, 0.7, 0.7, 1.0),
+            week_number_paint: PaintSetting::from_rgba(0.5, 0.5, 0.5, 1.0),
+            weekend_paint: PaintSetting::from_rgba(0.6, 0.8, 1.0, 1.0),
+            today_paint: PaintSetting::from_rgba(0.0, 0.0, 0.0, 1.0),
+            today_background_paint: PaintSetting::default(),
+            marked_paint: PaintSetting::from_rgba(1.0, 0.4, 0.3, 1.0),
+        }
+    }
+}
+
+impl ModuleConfig for CalendarModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Colors", TreeNodeFlags::FRAMED) {
+            let paints = [
+                ("Days", &mut self.paint),
+                ("Title", &mut self.title_paint),
+                ("Weekday names", &mut self.weekday_paint),
+                ("Week numbers", &mut self.week_number_paint),
+                ("Weekends", &mut self.weekend_paint),
+                ("Today", &mut self.today_paint),
+                ("Today background", &mut self.today_background_paint),
+                ("Marked dates", &mut self.marked_paint),
+            ];
+
+            for (i, (name, paint)) in paints.into_iter().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                ui.text(name);
+                paint.represent(ui, ctx);
+                ui.separator();
+            }
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            self.first_weekday.represent(ui, ctx);
+            ui.checkbox("Week numbers", &mut self.week_numbers);
+            ui.checkbox("Title", &mut self.title);
+
+            InputText::new(ui, "Locale", &mut self.locale)
+                .hint("Default, or for example de_DE")
+                .build();
+
+            if !self.locale.is_empty() && self.locale.parse::<Locale>().is_err() {
+                ui.text_colored(ERROR_COLOR, "Unknown locale, using default");
+            }
+
+            Drag::new("Spacing")
+                .range(0.0, 100.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.spacing);
+        }
+
+        if ui.collapsing_header("Marked dates", TreeNodeFlags::FRAMED) {
+            let mut to_remove = None;
+
+            for (i, marked) in self.marked_dates.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                marked.represent(ui, ctx);
+                ui.same_line();
+
+                if ui.small_button("Remove") {
+                    to_remove = Some(i);
+                }
+            }
+
+            if let Some(i) = to_remove {
+                self.marked_dates.remove(i);
+            }
+
+            if ui.button("Add date") {
+                self.marked_dates.push(MarkedDate::default());
+            }
+        }
+    }
+}
+
+/// The role of a cell, decides which paint it is drawn with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum CellKind {
+    Title,
+    Weekday,
+    WeekNumber,
+    Day,
+    Weekend,
+    Today,
+}
+
+/// A single piece of text laid out in the grid.
+struct Cell {
+    blob: Option<TextBlob>,
+
+    /// Position of the text relative to the top left of the calendar.
+    x: scalar,
+    y: scalar,
+
+    /// Bounds of the cell relative to the top left of the calendar.
+    bounds: Rect,
+
+    kind: CellKind,
+    marked: bool,
+}
+
+/// Everything the layout depends on, the layout is redone when any of this changes.
+#[derive(Debug)]
+struct LayoutKey {
+    today: NaiveDate,
+    font: u64,
+    first_weekday: FirstWeekday,
+    week_numbers: bool,
+    title: bool,
+    locale: String,
+    spacing: f32,
+    marked_dates: Vec<MarkedDate>,
+}
+
+impl LayoutKey {
+    fn new(config: &CalendarModuleConfig, today: NaiveDate) -> Self {
+        Self {
+            today,
+            font: config.font.cache_key(),
+            first_weekday: config.first_weekday.clone(),
+            week_numbers: config.week_numbers,
+            title: config.title,
+            locale: config.locale.clone(),
+            spacing: config.spacing,
+            marked_dates: config.marked_dates.clone(),
+        }
+    }
+
+    fn matches(&self, config: &CalendarModuleConfig, today: NaiveDate) -> bool {
+        self.today == today
+            && self.font == config.font.cache_key()
+            && self.first_weekday == config.first_weekday
+            && self.week_numbers == config.week_numbers
+            && self.title == config.title
+            && self.locale == config.locale
+            && self.spacing == config.spacing
+            && self.marked_dates == config.marked_dates
+    }
+}
+
+pub struct CalendarModuleRenderer {
+    key: Option<LayoutKey>,
+    cells: Vec<Cell>,
+    width: scalar,
+    height: scalar,
+}
+
+impl CalendarModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            key: None,
+            cells: Vec::new(),
+            width: 0.0,
+            height: 0.0,
+        }
+    }
+
+    /// Lays out the month containing `today`.
+    fn layout(&mut self, config: &CalendarModuleConfig, today: NaiveDate) {
+        let font = &config.font;
+        let locale = config.locale();
+        let (line_height, metrics) = font.get_font().metrics();
+        let spacing = config.spacing;
+
+        let first_weekday = config.first_weekday.weekday();
+        let weekdays = (0..7)
+            .map(|i| {
+                let day = first_weekday.num_days_from_monday() + i;
+                // 2001-01-01 has been a monday
+                NaiveDate::from_ymd(2001, 1, 1 + day % 7)
+            })
+            .collect::<Vec<_>>();
+
+        let mut text = String::new();
+        let mut weekday_names = Vec::with_capacity(7);
+        for day in &weekdays {
+            text.clear();
+            let _ = write!(
+                text,
+                "{}",
+                Utc.from_utc_date(day).format_localized("%a", locale)
+            );
+            weekday_names.push(text.clone());
+        }
+
+        let cell_width = weekday_names
+            .iter()
+            .map(String::as_str)
+            .chain(["00"])
+            .map(|text| font.measure_str(text, None).0)
+            .fold(0.0, scalar::max)
+            + spacing;
+        let cell_height = line_height + spacing;
+
+        let first_column = if config.week_numbers { 1 } else { 0 };
+        let columns = first_column + 7;
+        let mut row = 0;
+
+        self.cells.clear();
+
+        if config.title {
+            text.clear();
+            let _ = write!(
+                text,
+                "{}",
+                Utc.from_utc_date(&today).format_localized("%B %Y", locale)
+            );
+
+            let (width, _) = font.measure_str(&text, None);
+            let total_width = columns as scalar * cell_width;
+
+            self.cells.push(Cell {
+                blob: font.text_blob(&text),
+                x: (total_width - width) / 2.0,
+                y: (spacing / 2.0) - metrics.ascent,
+                bounds: Rect::from_xywh(0.0, 0.0, total_width, cell_height),
+                kind: CellKind::Title,
+                marked: false,
+            });
+
+            row += 1;
+        }
+
+        let mut push = |text: &str, column: usize, row: usize, kind: CellKind, marked: bool| {
+            let (width, _) = font.measure_str(text, None);
+            let bounds = Rect::from_xywh(
+                column as scalar * cell_width,
+                row as scalar * cell_height,
+                cell_width,
+                cell_height,
+            );
+
+            self.cells.push(Cell {
+                blob: font.text_blob(text),
+                x: bounds.left + (cell_width - width) / 2.0,
+                y: bounds.top + (spacing / 2.0) - metrics.ascent,
+                bounds,
+                kind,
+                marked,
+            });
+        };
+
+        for (i, name) in weekday_names.iter().enumerate() {
+            push(name, first_column + i, row, CellKind::Weekday, false);
+        }
+        row += 1;
+
+        let first_of_month = NaiveDate::from_ymd(today.year(), today.month(), 1);
+        let offset = (first_of_month.weekday().num_days_from_monday() + 7
+            - first_weekday.num_days_from_monday())
+            % 7;
+        let mut week_start = first_of_month - Duration::days(offset as i64);
+
+        while week_start.month() == today.month() || week_start < first_of_month {
+            if config.week_numbers {
+                // The ISO week is determined by the thursday of the week
+                let thursday = (0..7)
+                    .map(|i| week_start + Duration::days(i))
+                    .find(|day| day.weekday() == Weekday::Thu)
+                    .unwrap();
+
+                text.clear();
+                let _ = write!(text, "{}", thursday.iso_week().week());
+                push(&text, 0, row, CellKind::WeekNumber, false);
+            }
+
+            for i in 0..7 {
+                let day = week_start + Duration::days(i);
+                if day.month() != today.month() {
+                    continue;
+                }
+
+                let kind = if day == today {
+                    CellKind::Today
+                } else if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                    CellKind::Weekend
+                } else {
+                    CellKind::Day
+                };
+
+                text.clear();
+                let _ = write!(text, "{}", day.day());
+                push(
+                    &text,
+                    first_column + i as usize,
+                    row,
+                    kind,
+                    config.is_marked(day),
+                );
+            }
+
+            week_start = week_start + Duration::weeks(1);
+            row += 1;
+        }
+
+        self.width = columns as scalar * cell_width;
+        self.height = row as scalar * cell_height;
+    }
+
+    fn paint_for<'a>(config: &'a CalendarModuleConfig, kind: CellKind) -> &'a Paint {
+        match kind {
+            CellKind::Title => config.title_paint.get_paint(),
+            CellKind::Weekday => config.weekday_paint.get_paint(),
+            CellKind::WeekNumber => config.week_number_paint.get_paint(),
+            CellKind::Day => config.paint.get_paint(),
+            CellKind::Weekend => config.weekend_paint.get_paint(),
+            CellKind::Today => config.today_paint.get_paint(),
+        }
+    }
+}
+
+impl ModuleRenderer for CalendarModuleRenderer {
+    type Config = CalendarModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let today = Local::today().naive_local();
+
+        let outdated = self
+            .key
+            .as_ref()
+            .map_or(true, |key| !key.matches(config, today));
+        if outdated {
+            self.layout(config, today);
+            self.key = Some(LayoutKey::new(config, today));
+        }
+
+        let (x, y) =
+            match config
+                .position
+                .compute_position(data, self.width as i32, self.height as i32)
+            {
+                None => return,
+                Some((x, y)) => (x as scalar, y as scalar),
+            };
+
+        let canvas = data.canvas();
+        let font = config.font.get_font();
+        let marker_radius = font.size() / 12.0;
+
+        for cell in &self.cells {
+            let bounds = cell.bounds.with_offset((x, y));
+
+            if cell.kind == CellKind::Today {
+                canvas.draw_round_rect(
+                    bounds.with_inset((config.spacing / 4.0, config.spacing / 4.0)),
+                    marker_radius * 2.0,
+                    marker_radius * 2.0,
+                    config.today_background_paint.get_paint(),
+                );
+            }
+
+            if let Some(blob) = &cell.blob {
+                canvas.draw_text_blob(
+                    blob,
+                    (x + cell.x, y + cell.y),
+                    Self::paint_for(config, cell.kind),
+                );
+            }
+
+            if cell.marked {
+                canvas.draw_circle(
+                    (bounds.center_x(), bounds.bottom - marker_radius * 1.5),
+                    marker_radius,
+                    config.marked_paint.get_paint(),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,1047 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use serde::{Deserialize, Serialize};
+use skia_safe::paint::{Join, Style};
+use skia_safe::{scalar, Path as SkPath, Point, Rect};
+
+use crate::scene::module::command::edit_command;
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::{CommandOptions, CommandOutput, CommandRunner, WatchedFile};
+
+/// Time after which a command which has not printed its value yet is killed.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Colors given to newly added series, in order.
+const SERIES_COLORS: [[f32; 3]; 5] = [
+    [0.3, 0.7, 1.0],
+    [1.0, 0.6, 0.2],
+    [0.4, 0.9, 0.4],
+    [1.0, 0.4, 0.6],
+    [0.8, 0.6, 1.0],
+];
+
+const SOURCE_HELP: &str = "Each line is a row, fields are separated by commas, semicolons, \
+tabs or spaces.\nA first line without any numbers names the columns, and a first column \
+which is not a number labels the rows.\nCommands add the last line they print as a row each \
+time they are run.";
+
+pub(super) struct ChartModule;
+
+impl Module for ChartModule {
+    type Config = ChartModuleConfig;
+    type Renderer = ChartModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        ChartModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Chart".into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChartSource {
+    Csv { path: String },
+    Command { command: String, interval: f32 },
+}
+
+impl ChartSource {
+    pub const fn ordinal(&self) -> usize {
+        match self {
+            ChartSource::Csv { .. } => 0,
+            ChartSource::Command { .. } => 1,
+        }
+    }
+
+    pub fn from_ordinal(ordinal: usize) -> Self {
+        match ordinal {
+            0 => ChartSource::Csv {
+                path: String::new(),
+            },
+            // There is no command which works on every platform, the input shows a hint
+            1 => ChartSource::Command {
+                command: String::new(),
+                interval: 5.0,
+            },
+            _ => panic!("Invalid chart source ordinal"),
+        }
+    }
+
+    pub const fn type_names() -> [&'static str; 2] {
+        ["CSV file", "Command"]
+    }
+
+    /// Determines whether data read from the other source can be kept when switching to
+    /// this one, which is the case as long as only the interval changed.
+    fn same_origin(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ChartSource::Csv { path: a }, ChartSource::Csv { path: b }) => a == b,
+            (ChartSource::Command { command: a, .. }, ChartSource::Command { command: b, .. }) => {
+                a == b
+            }
+            _ => false,
+        }
+    }
+
+    fn represent(&mut self, ui: &Ui, pending_command: &mut Option<String>) {
+        let mut current_type_ordinal = self.ordinal();
+        if ui.combo_simple_string(
+            "Source",
+            &mut current_type_ordinal,
+            ChartSource::type_names().as_slice(),
+        ) {
+            *self = ChartSource::from_ordinal(current_type_ordinal);
+            *pending_command = None;
+        }
+
+        if ui.is_item_hovered() {
+            ui.tooltip_text(SOURCE_HELP);
+        }
+
+        match self {
+            ChartSource::Csv { path } => {
+                if let Some(_tok) = ui.begin_table("Source Options", 2) {
+                    ui.table_next_row();
+                    ui.table_next_column();
+
+                    InputText::new(ui, "Path", path).build();
+                    ui.table_next_column();
+                    if ui.small_button("...") {
+                        match FileDialog::new()
+                            .add_filter("CSV file", &["csv", "tsv", "txt"])
+                            .show_open_single_file()
+                        {
+                            Ok(None) => {}
+                            Ok(Some(p)) => *path = p.to_string_lossy().into(),
+                            Err(err) => {
+                                log::error!("Failed to show a file dialog: {}", err)
+                            }
+                        };
+                    }
+                }
+            }
+            ChartSource::Command { command, interval } => {
+                edit_command(ui, command, pending_command);
+
+                Drag::new("Interval (s)")
+                    .range(0.1, 24.0 * 60.0 * 60.0)
+                    .speed(0.1)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, interval);
+            }
+        }
+    }
+}
+
+impl Default for ChartSource {
+    fn default() -> Self {
+        Self::from_ordinal(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ChartKind {
+    Line,
+    Area,
+    Bar,
+}
+
+impl ChartKind {
+    const VALUES: [Self; 3] = [Self::Line, Self::Area, Self::Bar];
+}
+
+impl Default for ChartKind {
+    fn default() -> Self {
+        Self::Line
+    }
+}
+
+impl ModuleConfig for ChartKind {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Kind", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Line => "Line chart",
+                Self::Area => "Area chart",
+                Self::Bar => "Bar chart",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current];
+    }
+}
+
+/// A column of the data drawn as one line, area or set of bars.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSeries {
+    /// Name of the column as given by the header, or its number starting at 1.
+    column: String,
+
+    /// Name shown in the legend.
+    label: String,
+
+    paint: PaintSetting,
+}
+
+impl ChartSeries {
+    fn new(column: &str, label: &str, color: [f32; 3]) -> Self {
+        Self {
+            column: String::from(column),
+            label: String::from(label),
+            paint: PaintSetting::from_rgba(color[0], color[1], color[2], 1.0),
+        }
+    }
+}
+
+impl ModuleConfig for ChartSeries {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        InputText::new(ui, "Column", &mut self.column)
+            .hint("Header name or number, starting at 1")
+            .build();
+        InputText::new(ui, "Label", &mut self.label).build();
+
+        self.paint.represent(ui, ctx);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartModuleConfig {
+    position: ModulePosition,
+    source: ChartSource,
+    kind: ChartKind,
+
+    width: f32,
+    height: f32,
+
+    /// Amount of rows shown, counted from the last one.
+    window: i32,
+
+    /// Whether the bottom of the value axis follows the data instead of `min`.
+    auto_min: bool,
+    min: f32,
+
+    /// Whether the top of the value axis follows the data instead of `max`.
+    auto_max: bool,
+    max: f32,
+
+    line_width: f32,
+
+    /// Opacity of the area below the lines of area charts, relative to the series paint.
+    fill_opacity: f32,
+
+    show_axes: bool,
+    show_labels: bool,
+    show_legend: bool,
+
+    series: Vec<ChartSeries>,
+
+    /// Paint of the labels and the legend.
+    paint: PaintSetting,
+    axis_paint: PaintSetting,
+    font: FontSetting,
+
+    /// The command being edited, see [`edit_command`].
+    #[serde(skip)]
+    pending_command: Option<String>,
+
+    /// Problems with the source or the series, set by the renderer.
+    #[serde(skip)]
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl ChartModuleConfig {
+    fn window(&self) -> usize {
+        self.window.max(1) as usize
+    }
+}
+
+impl Default for ChartModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            source: ChartSource::default(),
+            kind: ChartKind::default(),
+            width: 400.0,
+            height: 200.0,
+            window: 60,
+            auto_min: false,
+            min: 0.0,
+            auto_max: true,
+            max: 100.0,
+            line_width: 2.0,
+            fill_opacity: 0.3,
+            show_axes: true,
+            show_labels: true,
+            show_legend: true,
+            series: vec![ChartSeries::new("1", "Load", SERIES_COLORS[0])],
+            paint: PaintSetting::default(),
+            axis_paint: PaintSetting::from_rgba(1.0, 1.0, 1.0, 0.5),
+            font: FontSetting::default(),
+            pending_command: None,
+            errors: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl ModuleConfig for ChartModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Colors", TreeNodeFlags::FRAMED) {
+            ui.text("Labels");
+            {
+                let _id = ui.push_id("Labels");
+                self.paint.represent(ui, ctx);
+            }
+
+            ui.text("Axes");
+            {
+                let _id = ui.push_id("Axes");
+                self.axis_paint.represent(ui, ctx);
+            }
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            self.source.represent(ui, &mut self.pending_command);
+
+            Drag::new("Rows")
+                .range(1, 10000)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.window);
+
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Amount of rows shown, counted from the last one");
+            }
+
+            for err in self.errors.lock().unwrap().iter() {
+                ui.text_colored(ERROR_COLOR, err);
+            }
+        }
+
+        if ui.collapsing_header("Format", TreeNodeFlags::FRAMED) {
+            self.kind.represent(ui, ctx);
+
+            Drag::new("Width")
+                .range(16.0, 10000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.width);
+
+            Drag::new("Height")
+                .range(16.0, 10000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.height);
+
+            ui.checkbox("Automatic minimum", &mut self.auto_min);
+            if !self.auto_min {
+                Drag::new("Minimum").speed(0.1).build(ui, &mut self.min);
+            }
+
+            ui.checkbox("Automatic maximum", &mut self.auto_max);
+            if !self.auto_max {
+                Drag::new("Maximum").speed(0.1).build(ui, &mut self.max);
+            }
+
+            if self.kind != ChartKind::Bar {
+                Drag::new("Line width")
+                    .range(0.5, 32.0)
+                    .speed(0.1)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.line_width);
+            }
+
+            if self.kind == ChartKind::Area {
+                Drag::new("Fill opacity")
+                    .range(0.0, 1.0)
+                    .speed(0.01)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.fill_opacity);
+            }
+
+            ui.checkbox("Show axes", &mut self.show_axes);
+            ui.checkbox("Show labels", &mut self.show_labels);
+            ui.checkbox("Show legend", &mut self.show_legend);
+        }
+
+        if ui.collapsing_header("Series", TreeNodeFlags::FRAMED) {
+            let mut to_remove = None;
+
+            for (i, series) in self.series.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                series.represent(ui, ctx);
+
+                if ui.button("Remove series") {
+                    to_remove = Some(i);
+                }
+
+                ui.separator();
+            }
+
+            if let Some(i) = to_remove {
+                self.series.remove(i);
+            }
+
+            if ui.button("Add series") {
+                let number = self.series.len() + 1;
+                let color = SERIES_COLORS[self.series.len() % SERIES_COLORS.len()];
+
+                self.series.push(ChartSeries::new(
+                    &number.to_string(),
+                    &format!("Series {}", number),
+                    color,
+                ));
+            }
+        }
+    }
+}
+
+/// A row of the data, with a value for each field which is a number.
+#[derive(Debug)]
+struct Row {
+    /// The first field, which labels the row if it is not a number.
+    label: String,
+    values: Vec<Option<f64>>,
+}
+
+impl Row {
+    fn value(&self, column: usize) -> Option<f64> {
+        self.values.get(column).copied().flatten()
+    }
+
+    fn has_label(&self) -> bool {
+        !self.label.is_empty() && self.value(0).is_none()
+    }
+}
+
+#[derive(Debug, Default)]
+struct DataSet {
+    headers: Vec<String>,
+    rows: VecDeque<Row>,
+}
+
+impl DataSet {
+    /// Reads a CSV file, see [`SOURCE_HELP`] for the supported format.
+    fn parse(content: &str) -> Self {
+        let mut data = Self::default();
+
+        for line in content.lines() {
+            let fields = split_fields(line);
+            if fields.iter().all(|field| field.is_empty()) || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let is_header = data.headers.is_empty()
+                && data.rows.is_empty()
+                && fields.iter().all(|field| parse_value(field).is_none());
+
+            if is_header {
+                data.headers = fields.iter().map(ToString::to_string).collect();
+            } else {
+                data.push(&fields);
+            }
+        }
+
+        data
+    }
+
+    fn push(&mut self, fields: &[&str]) {
+        self.rows.push_back(Row {
+            label: fields.first().map_or(String::new(), ToString::to_string),
+            values: fields.iter().map(|field| parse_value(field)).collect(),
+        });
+    }
+
+    /// Finds a column by its header name, or by its number starting at 1.
+    fn column(&self, name: &str) -> Option<usize> {
+        let name = name.trim();
+
+        self.headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+            .or_else(|| {
+                name.parse::<usize>()
+                    .ok()
+                    .filter(|number| *number > 0)
+                    .map(|number| number - 1)
+            })
+    }
+}
+
+fn split_fields(line: &str) -> Vec<&str> {
+    let separator = [',', ';', '\t']
+        .iter()
+        .copied()
+        .find(|separator| line.contains(*separator));
+
+    let fields: Vec<&str> = match separator {
+        Some(separator) => line.split(separator).collect(),
+        None => line.split_whitespace().collect(),
+    };
+
+    fields
+        .into_iter()
+        .map(|field| field.trim().trim_matches('"'))
+        .collect()
+}
+
+/// Parses a field as a number, allowing a trailing percent sign.
+fn parse_value(field: &str) -> Option<f64> {
+    field
+        .trim_end_matches('%')
+        .trim_end()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// Formats a value of the value axis with fewer decimals the larger it is.
+fn format_value(value: f64) -> String {
+    let magnitude = value.abs();
+
+    if magnitude >= 100.0 || value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else if magnitude >= 10.0 {
+        format!("{:.1}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+/// The source data is read from, along with everything read so far.
+struct LoadedSource {
+    source: ChartSource,
+    file: WatchedFile,
+    runner: Option<CommandRunner>,
+    data: DataSet,
+    error: Option<String>,
+}
+
+impl LoadedSource {
+    fn new(source: &ChartSource) -> Self {
+        let mut loaded = Self {
+            source: source.clone(),
+            file: WatchedFile::new(),
+            runner: None,
+            data: DataSet::default(),
+            error: None,
+        };
+
+        match source {
+            ChartSource::Csv { path } if path.is_empty() => {
+                loaded.error = Some(String::from("No file selected"));
+            }
+            // Read by the first update
+            ChartSource::Csv { .. } => {}
+            ChartSource::Command { command, interval } => {
+                let options = CommandOptions {
+                    command_line: command.clone(),
+                    continuous: false,
+                    timeout: Some(COMMAND_TIMEOUT),
+                    capture_stderr: false,
+                    max_lines: 16,
+                };
+
+                loaded.runner = Some(CommandRunner::spawn(options, interval_of(*interval)));
+            }
+        }
+
+        loaded
+    }
+
+    fn read_file(&mut self, path: &str) {
+        match std::fs::read_to_string(path) {
+            Ok(content) => {
+                self.data = DataSet::parse(&content);
+                self.error = None;
+            }
+            Err(err) => {
+                self.data = DataSet::default();
+                self.error = Some(format!("Failed to read {}: {}", path, err));
+            }
+        }
+    }
+
+    /// Reads the file again if it changed, or adds the output of the command as a row.
+    fn update(&mut self, config: &ChartModuleConfig) {
+        match &config.source {
+            ChartSource::Csv { path } => {
+                if !path.is_empty() && self.file.update(path) {
+                    self.read_file(path);
+                }
+            }
+            ChartSource::Command { interval, .. } => {
+                let runner = match &self.runner {
+                    None => return,
+                    Some(v) => v,
+                };
+
+                runner.set_interval(interval_of(*interval));
+                if let Some(output) = runner.take() {
+                    self.add_output(output, config.window());
+                }
+            }
+        }
+    }
+
+    fn add_output(&mut self, output: CommandOutput, window: usize) {
+        self.error = output.error;
+        if self.error.is_some() {
+            return;
+        }
+
+        let line = output
+            .lines
+            .iter()
+            .rev()
+            .find(|line| !line.trim().is_empty());
+        match line {
+            None => self.error = Some(String::from("The command printed nothing")),
+            Some(line) => self.data.push(&split_fields(line)),
+        }
+
+        while self.data.rows.len() > window {
+            self.data.rows.pop_front();
+        }
+    }
+}
+
+fn interval_of(seconds: f32) -> Duration {
+    Duration::from_secs_f32(seconds.max(0.1))
+}
+
+/// Maps rows and values to positions in the area the series are drawn in.
+struct Plot {
+    area: Rect,
+    min: f64,
+    max: f64,
+    rows: usize,
+    bars: bool,
+}
+
+impl Plot {
+    /// Horizontal center of a row.
+    fn x(&self, row: usize) -> scalar {
+        if self.bars {
+            let slot = self.area.width() / self.rows as scalar;
+            self.area.left + slot * (row as scalar + 0.5)
+        } else if self.rows < 2 {
+            self.area.center_x()
+        } else {
+            self.area.left + self.area.width() * row as scalar / (self.rows - 1) as scalar
+        }
+    }
+
+    fn y(&self, value: f64) -> scalar {
+        let relative = ((value - self.min) / (self.max - self.min)) as scalar;
+        self.area.bottom - self.area.height() * relative
+    }
+
+    /// Vertical position areas and bars start from, zero if it is visible.
+    fn baseline(&self) -> scalar {
+        self.y(0.0_f64.max(self.min).min(self.max))
+    }
+
+    /// Splits a series into the runs of rows which have a value.
+    fn segments(&self, rows: &[&Row], column: usize) -> Vec<Vec<Point>> {
+        let mut segments = Vec::new();
+        let mut current = Vec::new();
+
+        for (i, row) in rows.iter().enumerate() {
+            match row.value(column) {
+                Some(value) => current.push(Point::new(self.x(i), self.y(value))),
+                None if !current.is_empty() => segments.push(std::mem::take(&mut current)),
+                None => {}
+            }
+        }
+
+        if !current.is_empty() {
+            segments.push(current);
+        }
+
+        segments
+    }
+}
+
+/// Determines the range of the value axis.
+fn value_range(config: &ChartModuleConfig, rows: &[&Row], columns: &[usize]) -> (f64, f64) {
+    let (mut min, mut max) = rows
+        .iter()
+        .flat_map(|row| columns.iter().filter_map(move |column| row.value(*column)))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+
+    if min > max {
+        min = 0.0;
+        max = 1.0;
+    }
+
+    if config.kind != ChartKind::Line {
+        // Areas and bars are measured from zero
+        min = min.min(0.0);
+        max = max.max(0.0);
+    }
+
+    if !config.auto_min {
+        min = config.min as f64;
+    }
+
+    if !config.auto_max {
+        max = config.max as f64;
+    }
+
+    if max <= min {
+        // Flat data is drawn in the middle
+        let center = if config.auto_min { max } else { min };
+        min = center - 1.0;
+        max = center + 1.0;
+    }
+
+    (min, max)
+}
+
+pub struct ChartModuleRenderer {
+    loaded: Option<LoadedSource>,
+}
+
+impl ChartModuleRenderer {
+    pub fn new() -> Self {
+        Self { loaded: None }
+    }
+
+    /// Switches to another source if it changed and reads new data.
+    fn update(&mut self, config: &ChartModuleConfig) -> &LoadedSource {
+        let outdated = self
+            .loaded
+            .as_ref()
+            .map_or(true, |loaded| !loaded.source.same_origin(&config.source));
+
+        if outdated {
+            // Dropping the old source kills its command
+            self.loaded = Some(LoadedSource::new(&config.source));
+        }
+
+        let loaded = self.loaded.as_mut().unwrap();
+        loaded.update(config);
+
+        let errors = loaded
+            .error
+            .iter()
+            .cloned()
+            .chain(
+                config
+                    .series
+                    .iter()
+                    .filter(|series| loaded.data.column(&series.column).is_none())
+                    .map(|series| format!("Column {} not found", series.column)),
+            )
+            .collect::<Vec<_>>();
+
+        *config.errors.lock().unwrap() = errors;
+
+        loaded
+    }
+
+    fn draw_labels(
+        config: &ChartModuleConfig,
+        data: &mut SceneData<'_>,
+        plot: &Plot,
+        rows: &[&Row],
+    ) {
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+        let paint = config.paint.get_paint();
+        let gap = line_height * 0.3;
+
+        let max = format_value(plot.max);
+        let max_width = font.measure_str(&max, None).0;
+        font.draw_str(
+            data.canvas(),
+            &max,
+            (
+                plot.area.left - gap - max_width,
+                plot.area.top - metrics.ascent,
+            ),
+            paint,
+        );
+
+        let min = format_value(plot.min);
+        let min_width = font.measure_str(&min, None).0;
+        font.draw_str(
+            data.canvas(),
+            &min,
+            (
+                plot.area.left - gap - min_width,
+                plot.area.bottom - metrics.descent,
+            ),
+            paint,
+        );
+
+        let baseline = plot.area.bottom + gap - metrics.ascent;
+
+        let first = rows.first().filter(|row| row.has_label());
+        let first_width = match first {
+            None => 0.0,
+            Some(row) => {
+                font.draw_str(data.canvas(), &row.label, (plot.area.left, baseline), paint);
+                font.measure_str(&row.label, None).0
+            }
+        };
+
+        let last = rows.last().filter(|row| rows.len() > 1 && row.has_label());
+        if let Some(row) = last {
+            let width = font.measure_str(&row.label, None).0;
+
+            // Leave out the last label rather than drawing it over the first one
+            if first_width + gap + width <= plot.area.width() {
+                font.draw_str(
+                    data.canvas(),
+                    &row.label,
+                    (plot.area.right - width, baseline),
+                    paint,
+                );
+            }
+        }
+    }
+
+    fn draw_legend(config: &ChartModuleConfig, data: &mut SceneData<'_>, x: scalar, y: scalar) {
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+        let size = line_height * 0.6;
+        let gap = line_height * 0.3;
+
+        let mut entry_x = x;
+        for series in &config.series {
+            let mut fill = series.paint.get_paint().clone();
+            fill.set_style(Style::Fill);
+
+            let square = Rect::from_xywh(entry_x, y + (line_height - size) / 2.0, size, size);
+            data.canvas().draw_rect(square, &fill);
+            entry_x += size + gap;
+
+            font.draw_str(
+                data.canvas(),
+                &series.label,
+                (entry_x, y - metrics.ascent),
+                config.paint.get_paint(),
+            );
+            entry_x += font.measure_str(&series.label, None).0 + line_height;
+        }
+    }
+
+    fn draw_lines(
+        config: &ChartModuleConfig,
+        data: &mut SceneData<'_>,
+        plot: &Plot,
+        rows: &[&Row],
+        columns: &[Option<usize>],
+    ) {
+        let baseline = plot.baseline();
+
+        for (series, column) in config.series.iter().zip(columns) {
+            let column = match column {
+                None => continue,
+                Some(v) => *v,
+            };
+
+            let mut stroke = series.paint.get_paint().clone();
+            stroke
+                .set_anti_alias(true)
+                .set_style(Style::Stroke)
+                .set_stroke_width(config.line_width)
+                .set_stroke_join(Join::Round);
+
+            let mut fill = series.paint.get_paint().clone();
+            fill.set_anti_alias(true).set_style(Style::Fill);
+            fill.set_alpha_f(fill.alpha_f() * config.fill_opacity);
+
+            for segment in plot.segments(rows, column) {
+                if segment.len() == 1 {
+                    // A lone value has no line to be part of
+                    let mut dot = stroke.clone();
+                    dot.set_style(Style::Fill);
+                    data.canvas()
+                        .draw_circle(segment[0], config.line_width, &dot);
+                    continue;
+                }
+
+                let mut line = SkPath::new();
+                line.move_to(segment[0]);
+                for point in &segment[1..] {
+                    line.line_to(*point);
+                }
+
+                if config.kind == ChartKind::Area {
+                    let mut area = line.clone();
+                    area.line_to((segment[segment.len() - 1].x, baseline))
+                        .line_to((segment[0].x, baseline))
+                        .close();
+
+                    data.canvas().draw_path(&area, &fill);
+                }
+
+                data.canvas().draw_path(&line, &stroke);
+            }
+        }
+    }
+
+    fn draw_bars(
+        config: &ChartModuleConfig,
+        data: &mut SceneData<'_>,
+        plot: &Plot,
+        rows: &[&Row],
+        columns: &[Option<usize>],
+    ) {
+        let slot = plot.area.width() / rows.len() as scalar;
+        let bar_width = slot * 0.8 / config.series.len().max(1) as scalar;
+        let baseline = plot.baseline();
+
+        for (i, (series, column)) in config.series.iter().zip(columns).enumerate() {
+            let column = match column {
+                None => continue,
+                Some(v) => *v,
+            };
+
+            let mut fill = series.paint.get_paint().clone();
+            fill.set_style(Style::Fill);
+
+            for (row_index, row) in rows.iter().enumerate() {
+                let value = match row.value(column) {
+                    None => continue,
+                    Some(v) => v,
+                };
+
+                let left =
+                    plot.area.left + slot * (row_index as scalar + 0.1) + bar_width * i as scalar;
+                let top = plot.y(value);
+
+                let bar = Rect::new(left, top.min(baseline), left + bar_width, top.max(baseline));
+                data.canvas().draw_rect(bar, &fill);
+            }
+        }
+    }
+}
+
+impl ModuleRenderer for ChartModuleRenderer {
+    type Config = ChartModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let loaded = self.update(config);
+
+        let (x, y) =
+            match config
+                .position
+                .compute_position(data, config.width as i32, config.height as i32)
+            {
+                None => return,
+                Some((x, y)) => (x as scalar, y as scalar),
+            };
+
+        let all_rows = &loaded.data.rows;
+        let rows = all_rows
+            .iter()
+            .skip(all_rows.len().saturating_sub(config.window()))
+            .collect::<Vec<_>>();
+
+        let columns = config
+            .series
+            .iter()
+            .map(|series| loaded.data.column(&series.column))
+            .collect::<Vec<_>>();
+
+        let (min, max) = value_range(
+            config,
+            &rows,
+            &columns.iter().flatten().copied().collect::<Vec<_>>(),
+        );
+
+        let font = &config.font;
+        let (line_height, _) = font.get_font().metrics();
+        let gap = line_height * 0.3;
+
+        let mut area = Rect::from_xywh(x, y, config.width, config.height);
+
+        if config.show_legend && !config.series.is_empty() {
+            Self::draw_legend(config, data, area.left, area.top);
+            area.top += line_height + gap;
+        }
+
+        if config.show_labels {
+            let label_width = font
+                .measure_str(&format_value(min), None)
+                .0
+                .max(font.measure_str(&format_value(max), None).0);
+
+            area.left += label_width + gap;
+            area.bottom -= line_height + gap;
+        }
+
+        if area.width() <= 0.0 || area.height() <= 0.0 {
+            return;
+        }
+
+        let plot = Plot {
+            area,
+            min,
+            max,
+            rows: rows.len(),
+            bars: config.kind == ChartKind::Bar,
+        };
+
+        if config.show_labels {
+            Self::draw_labels(config, data, &plot, &rows);
+        }
+
+        if config.show_axes {
+            let mut axis = config.axis_paint.get_paint().clone();
+            axis.set_style(Style::Stroke).set_stroke_width(1.0);
+
+            let mut lines = SkPath::new();
+            lines
+                .move_to((area.left, area.top))
+                .line_to((area.left, area.bottom))
+                .line_to((area.right, area.bottom));
+
+            data.canvas().draw_path(&lines, &axis);
+        }
+
+        if rows.is_empty() {
+            return;
+        }
+
+        // Values outside of fixed bounds are cut off at the edges of the plot
+        let canvas = data.canvas();
+        canvas.save();
+        canvas.clip_rect(
+            area.with_outset((config.line_width, config.line_width)),
+            None,
+            Some(true),
+        );
+
+        match config.kind {
+            ChartKind::Line | ChartKind::Area => {
+                Self::draw_lines(config, data, &plot, &rows, &columns)
+            }
+            ChartKind::Bar => Self::draw_bars(config, data, &plot, &rows, &columns),
+        }
+
+        data.canvas().restore();
+    }
+}
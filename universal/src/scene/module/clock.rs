@@ -0,0 +1,282 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Locale, TimeZone, Utc};
+use chrono_tz::Tz;
+use imgui::{InputText, TreeNodeFlags, Ui};
+use serde::{Deserialize, Serialize};
+use skia_safe::{scalar, TextBlob};
+
+use crate::scene::module::part::{
+    FontSetting, HorizontalPositionAnchor, ModulePosition, PaintSetting,
+};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+
+pub(super) struct ClockModule;
+
+impl Module for ClockModule {
+    type Config = ClockModuleConfig;
+    type Renderer = ClockModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        ClockModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Clock".into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockLine {
+    /// The chrono (strftime like) format string.
+    format: String,
+
+    /// The IANA name of the time zone, or empty for the local time zone.
+    time_zone: String,
+
+    /// The POSIX name of the locale, or empty for the default locale.
+    locale: String,
+}
+
+impl ClockLine {
+    /// Formats the given point in time according to this line.
+    ///
+    /// Invalid time zones and locales fall back to their defaults, invalid format strings
+    /// yield [`None`].
+    fn format(&self, now: &DateTime<Utc>, out: &mut String) -> Option<()> {
+        let locale = self.locale.parse::<Locale>().unwrap_or(Locale::POSIX);
+
+        match self.time_zone.parse::<Tz>() {
+            Ok(zone) => Self::format_in(now, &zone, &self.format, locale, out),
+            Err(_) => Self::format_in(now, &Local, &self.format, locale, out),
+        }
+    }
+
+    fn format_in<Z>(
+        now: &DateTime<Utc>,
+        zone: &Z,
+        format: &str,
+        locale: Locale,
+        out: &mut String,
+    ) -> Option<()>
+    where
+        Z: TimeZone,
+        Z::Offset: std::fmt::Display,
+    {
+        write!(
+            out,
+            "{}",
+            now.with_timezone(zone).format_localized(format, locale)
+        )
+        .ok()
+    }
+}
+
+impl Default for ClockLine {
+    fn default() -> Self {
+        Self {
+            format: String::from("%H:%M:%S"),
+            time_zone: String::new(),
+            locale: String::new(),
+        }
+    }
+}
+
+impl ModuleConfig for ClockLine {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        InputText::new(ui, "Format", &mut self.format)
+            .hint("%H:%M:%S")
+            .build();
+
+        InputText::new(ui, "Time zone", &mut self.time_zone)
+            .hint("Local, or for example Europe/Berlin")
+            .build();
+
+        if !self.time_zone.is_empty() && self.time_zone.parse::<Tz>().is_err() {
+            ui.text_colored(ERROR_COLOR, "Unknown time zone, using local time");
+        }
+
+        InputText::new(ui, "Locale", &mut self.locale)
+            .hint("Default, or for example de_DE")
+            .build();
+
+        if !self.locale.is_empty() && self.locale.parse::<Locale>().is_err() {
+            ui.text_colored(ERROR_COLOR, "Unknown locale, using default");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockModuleConfig {
+    position: ModulePosition,
+    alignment: HorizontalPositionAnchor,
+    lines: Vec<ClockLine>,
+    paint: PaintSetting,
+    font: FontSetting,
+}
+
+impl Default for ClockModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            alignment: Default::default(),
+            lines: vec![ClockLine::default()],
+            paint: PaintSetting::default(),
+            font: FontSetting::default(),
+        }
+    }
+}
+
+impl ModuleConfig for ClockModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Lines", TreeNodeFlags::FRAMED) {
+            ui.text("Line alignment");
+            self.alignment.represent(ui, ctx);
+            ui.separator();
+
+            let mut to_remove = None;
+
+            for (i, line) in self.lines.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                line.represent(ui, ctx);
+
+                if ui.button("Remove line") {
+                    to_remove = Some(i);
+                }
+
+                ui.separator();
+            }
+
+            if let Some(i) = to_remove {
+                self.lines.remove(i);
+            }
+
+            if ui.button("Add line") {
+                self.lines.push(ClockLine::default());
+            }
+        }
+    }
+}
+
+/// A formatted line of text prepared for drawing.
+struct PreparedLine {
+    blob: Option<TextBlob>,
+    width: scalar,
+}
+
+pub struct ClockModuleRenderer {
+    values: Vec<String>,
+    scratch: Vec<String>,
+    prepared: Vec<PreparedLine>,
+    prepared_font: Option<u64>,
+}
+
+impl ClockModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            scratch: Vec::new(),
+            prepared: Vec::new(),
+            prepared_font: None,
+        }
+    }
+
+    /// Formats all lines into the scratch buffer and determines whether they differ from
+    /// the currently prepared lines.
+    fn update_values(&mut self, config: &ClockModuleConfig) -> bool {
+        let now = Utc::now();
+
+        self.scratch.resize_with(config.lines.len(), String::new);
+
+        for (line, out) in config.lines.iter().zip(self.scratch.iter_mut()) {
+            out.clear();
+
+            if line.format(&now, out).is_none() {
+                out.clear();
+                out.push_str("<invalid format>");
+            }
+        }
+
+        if self.scratch == self.values {
+            false
+        } else {
+            std::mem::swap(&mut self.scratch, &mut self.values);
+            true
+        }
+    }
+
+    /// Lays out the current values as text blobs.
+    fn prepare(&mut self, font: &FontSetting) {
+        self.prepared = self
+            .values
+            .iter()
+            .map(|value| {
+                let (width, _) = font.measure_str(value, None);
+
+                PreparedLine {
+                    blob: font.text_blob(value),
+                    width,
+                }
+            })
+            .collect();
+
+        self.prepared_font = Some(font.cache_key());
+    }
+}
+
+impl ModuleRenderer for ClockModuleRenderer {
+    type Config = ClockModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let font = &config.font;
+
+        let changed = self.update_values(config);
+        if changed || self.prepared_font != Some(font.cache_key()) {
+            self.prepare(font);
+        }
+
+        let (line_height, metrics) = font.get_font().metrics();
+
+        let width = self
+            .prepared
+            .iter()
+            .fold(0.0, |width: scalar, line| width.max(line.width));
+        let height = line_height * self.prepared.len() as scalar;
+
+        if let Some((x, y)) = config
+            .position
+            .compute_position(data, width as i32, height as i32)
+        {
+            let canvas = data.canvas();
+
+            for (i, line) in self.prepared.iter().enumerate() {
+                if let Some(blob) = &line.blob {
+                    let line_x = x + config.alignment.compute(width as i32, line.width as i32);
+                    let line_y = y as scalar - metrics.ascent + (line_height * i as scalar);
+
+                    canvas.draw_text_blob(
+                        blob,
+                        (line_x as scalar, line_y),
+                        config.paint.get_paint(),
+                    );
+                }
+            }
+        }
+    }
+}
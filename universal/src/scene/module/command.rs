@@ -0,0 +1,341 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use serde::{Deserialize, Serialize};
+use skia_safe::scalar;
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::{parse_ansi, AnsiSpan, CommandOptions, CommandOutput, CommandRunner};
+
+pub(super) struct CommandModule;
+
+impl Module for CommandModule {
+    type Config = CommandModuleConfig;
+    type Renderer = CommandModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        CommandModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Command".into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CommandMode {
+    /// The command is run again after it has exited and the interval elapsed.
+    Interval,
+
+    /// The command keeps running and its output is shown as it arrives.
+    Continuous,
+}
+
+impl CommandMode {
+    const VALUES: [Self; 2] = [Self::Interval, Self::Continuous];
+}
+
+impl Default for CommandMode {
+    fn default() -> Self {
+        Self::Interval
+    }
+}
+
+impl ModuleConfig for CommandMode {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Mode", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Interval => "Run at an interval",
+                Self::Continuous => "Run continuously",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current];
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandModuleConfig {
+    position: ModulePosition,
+
+    /// Command line passed to the system shell.
+    command: String,
+
+    mode: CommandMode,
+
+    /// Time between two runs, or before restarting a continuous command, in seconds.
+    interval: f32,
+
+    /// Time after which the command is killed in seconds, 0 to never kill it.
+    timeout: f32,
+
+    capture_stderr: bool,
+
+    /// Amount of lines shown, counted from the end of the output.
+    max_lines: i32,
+
+    /// Whether ANSI color codes are used, they are removed from the output either way.
+    ansi_colors: bool,
+
+    paint: PaintSetting,
+    font: FontSetting,
+
+    /// The command being edited, it is only applied when pressing enter so incomplete
+    /// commands are never run.
+    #[serde(skip)]
+    pending_command: Option<String>,
+
+    /// Why the last run failed, set by the renderer.
+    #[serde(skip)]
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl CommandModuleConfig {
+    fn options(&self) -> CommandOptions {
+        CommandOptions {
+            command_line: self.command.clone(),
+            continuous: self.mode == CommandMode::Continuous,
+            timeout: if self.timeout > 0.0 {
+                Some(Duration::from_secs_f32(self.timeout))
+            } else {
+                None
+            },
+            capture_stderr: self.capture_stderr,
+            max_lines: self.max_lines.max(1) as usize,
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs_f32(self.interval.max(0.1))
+    }
+}
+
+impl Default for CommandModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            command: String::from("uptime"),
+            mode: CommandMode::default(),
+            interval: 5.0,
+            timeout: 10.0,
+            capture_stderr: false,
+            max_lines: 10,
+            ansi_colors: true,
+            paint: PaintSetting::default(),
+            font: FontSetting::default(),
+            pending_command: None,
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Edits a command line which is only applied when pressing enter, so incomplete commands
+/// are never run. Until then the edited text is kept in `pending`.
+pub(super) fn edit_command(ui: &Ui, command: &mut String, pending: &mut Option<String>) {
+    let mut edited = pending.clone().unwrap_or_else(|| command.clone());
+
+    let entered = InputText::new(ui, "Command", &mut edited)
+        .hint("For example: df -h /")
+        .enter_returns_true(true)
+        .build();
+
+    if ui.is_item_hovered() {
+        ui.tooltip_text(if cfg!(windows) {
+            "Run with cmd /C"
+        } else {
+            "Run with sh -c"
+        });
+    }
+
+    if entered || edited == *command {
+        *command = edited;
+        *pending = None;
+    } else {
+        *pending = Some(edited);
+        ui.text_disabled("Press enter to run the changed command");
+    }
+}
+
+impl ModuleConfig for CommandModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Command", TreeNodeFlags::FRAMED) {
+            edit_command(ui, &mut self.command, &mut self.pending_command);
+
+            self.mode.represent(ui, ctx);
+
+            let interval_label = match self.mode {
+                CommandMode::Interval => "Interval (s)",
+                CommandMode::Continuous => "Restart delay (s)",
+            };
+
+            Drag::new(interval_label)
+                .range(0.1, 24.0 * 60.0 * 60.0)
+                .speed(0.1)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.interval);
+
+            if self.mode == CommandMode::Interval {
+                Drag::new("Timeout (s)")
+                    .range(0.0, 60.0 * 60.0)
+                    .speed(0.1)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.timeout);
+
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("0 to never kill the command");
+                }
+            }
+
+            ui.checkbox("Capture stderr", &mut self.capture_stderr);
+
+            if let Some(err) = self.error.lock().unwrap().as_ref() {
+                ui.text_colored(ERROR_COLOR, err);
+            }
+        }
+
+        if ui.collapsing_header("Format", TreeNodeFlags::FRAMED) {
+            Drag::new("Lines")
+                .range(1, 1000)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.max_lines);
+
+            ui.checkbox("ANSI colors", &mut self.ansi_colors);
+        }
+    }
+}
+
+pub struct CommandModuleRenderer {
+    runner: Option<CommandRunner>,
+
+    /// The output split into colored spans, one list per line.
+    lines: Vec<Vec<AnsiSpan>>,
+}
+
+impl CommandModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            runner: None,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Restarts the command if its options changed and picks up new output.
+    fn update(&mut self, config: &CommandModuleConfig) {
+        let options = config.options();
+
+        let outdated = self
+            .runner
+            .as_ref()
+            .map_or(true, |runner| *runner.options() != options);
+
+        if outdated {
+            // Dropping the old runner kills its command
+            self.runner = Some(CommandRunner::spawn(options, config.interval()));
+        }
+
+        let runner = self.runner.as_ref().unwrap();
+        runner.set_interval(config.interval());
+
+        if let Some(output) = runner.take() {
+            self.set_output(config, output);
+        }
+    }
+
+    fn set_output(&mut self, config: &CommandModuleConfig, output: CommandOutput) {
+        if let Some(err) = &output.error {
+            log::debug!("Command {} failed: {}", config.command, err);
+        }
+
+        *config.error.lock().unwrap() = output.error;
+
+        let mut color = None;
+        self.lines = output
+            .lines
+            .iter()
+            .map(|line| {
+                let mut spans = Vec::new();
+                parse_ansi(line, &mut color, &mut spans);
+                spans
+            })
+            .collect();
+    }
+}
+
+impl ModuleRenderer for CommandModuleRenderer {
+    type Config = CommandModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        self.update(config);
+
+        let lines = &self.lines;
+        if lines.is_empty() {
+            return;
+        }
+
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+
+        let text_width = lines
+            .iter()
+            .map(|spans| {
+                spans
+                    .iter()
+                    .map(|span| font.measure_str(&span.text, None).0)
+                    .sum::<scalar>()
+            })
+            .fold(0.0, scalar::max);
+        let text_height = line_height * lines.len() as scalar;
+
+        let (x, y) =
+            match config
+                .position
+                .compute_position(data, text_width as i32, text_height as i32)
+            {
+                None => return,
+                Some((x, y)) => (x as scalar, y as scalar),
+            };
+
+        let base_paint = config.paint.get_paint();
+        let mut span_paint = base_paint.clone();
+
+        let mut line_y = y - metrics.ascent;
+        for spans in lines {
+            let mut span_x = x;
+
+            for span in spans {
+                let paint = match span.color {
+                    Some(color) if config.ansi_colors => {
+                        span_paint.set_color(color.with_a(base_paint.alpha()));
+                        &span_paint
+                    }
+                    _ => base_paint,
+                };
+
+                font.draw_str(data.canvas(), &span.text, (span_x, line_y), paint);
+                span_x += font.measure_str(&span.text, None).0;
+            }
+
+            line_y += line_height;
+        }
+    }
+}
@@ -1,7 +1,11 @@
-use std::fmt::{Display, Formatter};
-
-use chrono::{DateTime, Datelike, Local, NaiveTime};
-use imgui::{TreeNodeFlags, Ui};
+use std::fmt::{Display, Formatter, Write};
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
 use serde::{Deserialize, Serialize};
 use skia_safe::Point;
 
@@ -9,6 +13,11 @@ use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
 use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
 use crate::scene::SceneData;
 use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::{expand_placeholders, CalendarFile};
+
+/// Formats accepted for custom target date times, tried in order.
+const DATE_TIME_FORMATS: [&str; 3] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%d"];
 
 pub(super) struct CountdownModule;
 
@@ -17,7 +26,7 @@ impl Module for CountdownModule {
     type Renderer = CountdownModuleRenderer;
 
     fn create_renderer() -> Self::Renderer {
-        CountdownModuleRenderer
+        CountdownModuleRenderer::new()
     }
 
     fn name() -> String {
@@ -25,17 +34,228 @@ impl Module for CountdownModule {
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Recurrence {
+    Once,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Recurrence {
+    const VALUES: [Self; 4] = [Self::Once, Self::Weekly, Self::Monthly, Self::Yearly];
+
+    /// Computes the n-th occurrence relative to the base date time.
+    ///
+    /// Days which do not exist in a month (such as the 31st) are clamped to the last day
+    /// of that month.
+    fn nth(&self, base: NaiveDateTime, n: i64) -> NaiveDateTime {
+        match self {
+            Recurrence::Once => base,
+            Recurrence::Weekly => base + Duration::weeks(n),
+            Recurrence::Monthly => add_months(base, n),
+            Recurrence::Yearly => add_months(base, n * 12),
+        }
+    }
+
+    /// Rough length of one period in days, used to estimate the occurrence index.
+    fn approximate_days(&self) -> i64 {
+        match self {
+            Recurrence::Once => 1,
+            Recurrence::Weekly => 7,
+            Recurrence::Monthly => 30,
+            Recurrence::Yearly => 365,
+        }
+    }
+}
+
+impl Default for Recurrence {
+    fn default() -> Self {
+        Self::Once
+    }
+}
+
+impl ModuleConfig for Recurrence {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Recurrence", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Once => "Once",
+                Self::Weekly => "Weekly",
+                Self::Monthly => "Monthly",
+                Self::Yearly => "Yearly",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current].clone();
+    }
+}
+
+/// Adds a (possibly negative) amount of months to a date time.
+fn add_months(base: NaiveDateTime, months: i64) -> NaiveDateTime {
+    let total = (base.year() as i64 * 12) + base.month0() as i64 + months;
+    let year = total.div_euclid(12) as i32;
+    let month = total.rem_euclid(12) as u32 + 1;
+
+    let day = (28..=base.day())
+        .rev()
+        .find(|&day| NaiveDate::from_ymd_opt(year, month, day).is_some())
+        .unwrap_or_else(|| base.day().min(28));
+
+    NaiveDate::from_ymd(year, month, day).and_time(base.time())
+}
+
+/// A user defined target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomTarget {
+    name: String,
+    date_time: String,
+    time_zone: String,
+    recurrence: Recurrence,
+}
+
+impl CustomTarget {
+    fn parse_date_time(&self) -> Option<NaiveDateTime> {
+        let value = self.date_time.trim();
+
+        DATE_TIME_FORMATS.iter().find_map(|format| {
+            NaiveDateTime::parse_from_str(value, format)
+                .ok()
+                .or_else(|| {
+                    NaiveDate::parse_from_str(value, format)
+                        .ok()
+                        .map(|d| d.and_hms(0, 0, 0))
+                })
+        })
+    }
+
+    fn zone(&self) -> TargetZone {
+        match self.time_zone.parse::<Tz>() {
+            Ok(zone) => TargetZone::Named(zone),
+            Err(_) => TargetZone::Local,
+        }
+    }
+}
+
+impl Default for CustomTarget {
+    fn default() -> Self {
+        let tomorrow = Local::today().naive_local().succ().and_hms(0, 0, 0);
+
+        Self {
+            name: String::from("Event"),
+            date_time: tomorrow.format("%Y-%m-%d %H:%M").to_string(),
+            time_zone: String::new(),
+            recurrence: Recurrence::Once,
+        }
+    }
+}
+
+impl ModuleConfig for CustomTarget {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        InputText::new(ui, "Name", &mut self.name).build();
+
+        InputText::new(ui, "Date and time", &mut self.date_time)
+            .hint("YYYY-MM-DD HH:MM:SS")
+            .build();
+
+        if self.parse_date_time().is_none() {
+            ui.text_colored(ERROR_COLOR, "Invalid date, expected YYYY-MM-DD HH:MM:SS");
+        }
+
+        InputText::new(ui, "Time zone", &mut self.time_zone)
+            .hint("Local, or for example Europe/Berlin")
+            .build();
+
+        if !self.time_zone.is_empty() && self.time_zone.parse::<Tz>().is_err() {
+            ui.text_colored(ERROR_COLOR, "Unknown time zone, using local time");
+        }
+
+        self.recurrence.represent(ui, ctx);
+    }
+}
+
+/// A target taken from the events of an iCalendar file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CalendarTarget {
+    path: String,
+}
+
+impl ModuleConfig for CalendarTarget {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        InputText::new(ui, "Calendar", &mut self.path)
+            .hint("Path to an .ics file")
+            .build();
+
+        ui.same_line();
+        if ui.small_button("...") {
+            match FileDialog::new()
+                .add_filter("iCalendar", &["ics"])
+                .show_open_single_file()
+            {
+                Ok(None) => {}
+                Ok(Some(p)) => self.path = p.to_string_lossy().into(),
+                Err(err) => {
+                    log::error!("Failed to show a file dialog: {}", err)
+                }
+            };
+        }
+
+        ui.text_disabled("Counts down to the next event of the calendar");
+    }
+}
+
+/// The time zone a target is defined in.
+enum TargetZone {
+    Local,
+    Named(Tz),
+}
+
+impl TargetZone {
+    /// Converts a point in time to the wall clock time in this zone.
+    fn to_local(&self, time: &DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            TargetZone::Local => time.with_timezone(&Local).naive_local(),
+            TargetZone::Named(zone) => time.with_timezone(zone).naive_local(),
+        }
+    }
+
+    /// Converts a wall clock time in this zone to a point in time.
+    ///
+    /// Times which fall into a gap (such as a daylight saving time switch) are moved
+    /// forward by an hour.
+    fn to_utc(&self, time: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        let resolve = |time: &NaiveDateTime| match self {
+            TargetZone::Local => Local
+                .from_local_datetime(time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            TargetZone::Named(zone) => zone
+                .from_local_datetime(time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        };
+
+        resolve(time).or_else(|| resolve(&(*time + Duration::hours(1))))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CountdownTarget {
     Christmas,
     NewYear,
+    Custom(CustomTarget),
+    Calendar(CalendarTarget),
 }
 
 impl CountdownTarget {
-    pub const fn name(&self) -> &'static str {
+    pub fn name(&self) -> &str {
         match self {
             CountdownTarget::Christmas => "Christmas",
             CountdownTarget::NewYear => "New Year",
+            CountdownTarget::Custom(target) => &target.name,
+            CountdownTarget::Calendar(_) => "Calendar",
         }
     }
 
@@ -43,45 +263,98 @@ impl CountdownTarget {
         match self {
             CountdownTarget::Christmas => 0,
             CountdownTarget::NewYear => 1,
+            CountdownTarget::Custom(_) => 2,
+            CountdownTarget::Calendar(_) => 3,
         }
     }
 
-    pub const fn from_ordinal(ordinal: usize) -> Self {
+    pub fn from_ordinal(ordinal: usize) -> Self {
         match ordinal {
             0 => CountdownTarget::Christmas,
             1 => CountdownTarget::NewYear,
+            2 => CountdownTarget::Custom(CustomTarget::default()),
+            3 => CountdownTarget::Calendar(CalendarTarget::default()),
             _ => panic!("Invalid countdown target ordinal"),
         }
     }
 
-    pub const fn names() -> [&'static str; 2] {
-        [Self::from_ordinal(0).name(), Self::from_ordinal(1).name()]
+    pub const fn type_names() -> [&'static str; 4] {
+        ["Christmas", "New Year", "Custom", "Calendar"]
     }
 
-    pub fn get_date_time(&self) -> DateTime<Local> {
-        let now = Local::now();
+    /// Retrieves the base date time, zone and recurrence of the target.
+    ///
+    /// Calendar targets have no fixed definition, their occurrences are resolved by the
+    /// renderer from the calendar file.
+    fn definition(&self) -> Option<(NaiveDateTime, TargetZone, Recurrence)> {
+        let midnight = NaiveTime::from_hms(0, 0, 0);
 
         match self {
-            CountdownTarget::Christmas => Local::today()
-                .with_day(25)
-                .and_then(|d| d.with_month(12))
-                .and_then(|d| {
-                    if now.date() > d {
-                        d.with_year(now.year() + 1)
-                    } else {
-                        Some(d)
-                    }
-                })
-                .and_then(|d| d.and_time(NaiveTime::from_hms(0, 0, 0)))
-                .unwrap(),
+            CountdownTarget::Christmas => Some((
+                NaiveDate::from_ymd(2000, 12, 25).and_time(midnight),
+                TargetZone::Local,
+                Recurrence::Yearly,
+            )),
+            CountdownTarget::NewYear => Some((
+                NaiveDate::from_ymd(2000, 1, 1).and_time(midnight),
+                TargetZone::Local,
+                Recurrence::Yearly,
+            )),
+            CountdownTarget::Custom(target) => target
+                .parse_date_time()
+                .map(|base| (base, target.zone(), target.recurrence.clone())),
+            CountdownTarget::Calendar(_) => None,
+        }
+    }
 
-            CountdownTarget::NewYear => Local::today()
-                .with_day(1)
-                .and_then(|d| d.with_month(1))
-                .and_then(|d| d.with_year(now.year() + 1))
-                .and_then(|d| d.and_time(NaiveTime::from_hms(0, 0, 0)))
-                .unwrap(),
+    /// Finds the index of the first occurrence after the given wall clock time.
+    fn first_index_after(
+        base: NaiveDateTime,
+        recurrence: &Recurrence,
+        local_now: NaiveDateTime,
+    ) -> i64 {
+        let mut n = (local_now - base).num_days() / recurrence.approximate_days();
+
+        while recurrence.nth(base, n) <= local_now {
+            n += 1;
+        }
+
+        while recurrence.nth(base, n - 1) > local_now {
+            n -= 1;
         }
+
+        n
+    }
+
+    /// Retrieves the next occurrence of the target after now, if any.
+    pub fn next_occurrence(&self, now: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let (base, zone, recurrence) = self.definition()?;
+        let local_now = zone.to_local(now);
+
+        let local = match recurrence {
+            Recurrence::Once if base > local_now => base,
+            Recurrence::Once => return None,
+            _ => recurrence.nth(base, Self::first_index_after(base, &recurrence, local_now)),
+        };
+
+        zone.to_utc(&local)
+    }
+
+    /// Retrieves the last occurrence of the target up to now, if any.
+    pub fn previous_occurrence(&self, now: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let (base, zone, recurrence) = self.definition()?;
+        let local_now = zone.to_local(now);
+
+        let local = match recurrence {
+            Recurrence::Once if base <= local_now => base,
+            Recurrence::Once => return None,
+            _ => recurrence.nth(
+                base,
+                Self::first_index_after(base, &recurrence, local_now) - 1,
+            ),
+        };
+
+        zone.to_utc(&local)
     }
 }
 
@@ -98,7 +371,238 @@ impl Default for CountdownTarget {
 }
 
 impl ModuleConfig for CountdownTarget {
-    fn represent(&mut self, _ui: &Ui, _ctx: &Context<'_>) {}
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        match self {
+            CountdownTarget::Custom(target) => target.represent(ui, ctx),
+            CountdownTarget::Calendar(target) => target.represent(ui, ctx),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CountdownMode {
+    /// Counts down to the next occurrence.
+    CountDown,
+
+    /// Counts up since the last occurrence.
+    CountUp,
+}
+
+impl CountdownMode {
+    const VALUES: [Self; 2] = [Self::CountDown, Self::CountUp];
+}
+
+impl Default for CountdownMode {
+    fn default() -> Self {
+        Self::CountDown
+    }
+}
+
+impl ModuleConfig for CountdownMode {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Mode", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::CountDown => "Count down",
+                Self::CountUp => "Count up (time since)",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current].clone();
+    }
+}
+
+/// The units a duration can be split into, from largest to smallest.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum TimeUnit {
+    Weeks,
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+}
+
+impl TimeUnit {
+    const VALUES: [Self; 5] = [
+        Self::Weeks,
+        Self::Days,
+        Self::Hours,
+        Self::Minutes,
+        Self::Seconds,
+    ];
+
+    fn placeholder(&self) -> &'static str {
+        match self {
+            TimeUnit::Weeks => "weeks",
+            TimeUnit::Days => "days",
+            TimeUnit::Hours => "hours",
+            TimeUnit::Minutes => "minutes",
+            TimeUnit::Seconds => "seconds",
+        }
+    }
+
+    fn seconds(&self) -> i64 {
+        match self {
+            TimeUnit::Weeks => 7 * 24 * 60 * 60,
+            TimeUnit::Days => 24 * 60 * 60,
+            TimeUnit::Hours => 60 * 60,
+            TimeUnit::Minutes => 60,
+            TimeUnit::Seconds => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountdownFormat {
+    /// The output template, see [`CountdownFormat::TEMPLATE_HELP`].
+    template: String,
+
+    /// The message displayed when the target has been reached.
+    reached_message: String,
+
+    /// How long the reached message is displayed for recurring targets, in minutes.
+    reached_duration: i32,
+
+    weeks: bool,
+    days: bool,
+    hours: bool,
+    minutes: bool,
+    seconds: bool,
+}
+
+impl CountdownFormat {
+    const TEMPLATE_HELP: &'static str = "\
+Placeholders: {weeks} {days} {hours} {minutes} {seconds} {name}
+Zero padding: {days:3}
+Plural words: {days|day|days}
+Disabled units are carried over into the next smaller enabled unit.";
+
+    fn is_enabled(&self, unit: TimeUnit) -> bool {
+        match unit {
+            TimeUnit::Weeks => self.weeks,
+            TimeUnit::Days => self.days,
+            TimeUnit::Hours => self.hours,
+            TimeUnit::Minutes => self.minutes,
+            TimeUnit::Seconds => self.seconds,
+        }
+    }
+
+    /// Splits a duration into the enabled units.
+    fn split(&self, duration: Duration) -> [i64; 5] {
+        let mut remaining = duration.num_seconds().max(0);
+        let mut values = [0; 5];
+
+        for (value, unit) in values.iter_mut().zip(TimeUnit::VALUES) {
+            if self.is_enabled(unit) {
+                *value = remaining / unit.seconds();
+                remaining %= unit.seconds();
+            }
+        }
+
+        values
+    }
+
+    /// Renders a template with the given unit values.
+    fn render(template: &str, name: &str, values: &[i64; 5], out: &mut String) {
+        expand_placeholders(template, out, |key, width, out| {
+            Self::render_placeholder(key, width, name, values, out)
+        });
+    }
+
+    /// Renders a single placeholder, returns `false` if the placeholder is unknown.
+    ///
+    /// Values are padded to the width given as argument, `{key|one|other}` chooses a word
+    /// depending on whether the value is one.
+    fn render_placeholder(
+        key: &str,
+        width: Option<&str>,
+        name: &str,
+        values: &[i64; 5],
+        out: &mut String,
+    ) -> bool {
+        if key == "name" && width.is_none() {
+            out.push_str(name);
+            return true;
+        }
+
+        let (key, words) = match key.split_once('|') {
+            None => (key, None),
+            Some((key, words)) => (key, Some(words)),
+        };
+
+        let value = match TimeUnit::VALUES
+            .iter()
+            .position(|unit| unit.placeholder() == key)
+        {
+            None => return false,
+            Some(i) => values[i],
+        };
+
+        match (width, words) {
+            (None, None) => write!(out, "{}", value),
+            (Some(width), None) => {
+                let width = width.parse::<usize>().unwrap_or(0);
+                write!(out, "{:0>width$}", value, width = width)
+            }
+            (None, Some(words)) => {
+                let mut words = words.split('|');
+                let one = words.next().unwrap_or("");
+                let other = words.next().unwrap_or(one);
+
+                write!(out, "{}", if value == 1 { one } else { other })
+            }
+            (Some(_), Some(_)) => return false,
+        }
+        .is_ok()
+    }
+}
+
+impl Default for CountdownFormat {
+    fn default() -> Self {
+        Self {
+            template: String::from(
+                "{days:3} {days|day|days}, {hours:2} {hours|hour|hours}, \
+                {minutes:2} {minutes|minute|minutes} and {seconds:2} {seconds|second|seconds}",
+            ),
+            reached_message: String::from("It's {name}!"),
+            reached_duration: 24 * 60,
+            weeks: false,
+            days: true,
+            hours: true,
+            minutes: true,
+            seconds: true,
+        }
+    }
+}
+
+impl ModuleConfig for CountdownFormat {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        InputText::new(ui, "Template", &mut self.template).build();
+
+        if ui.is_item_hovered() {
+            ui.tooltip_text(Self::TEMPLATE_HELP);
+        }
+
+        ui.checkbox("Weeks", &mut self.weeks);
+        ui.same_line();
+        ui.checkbox("Days", &mut self.days);
+        ui.same_line();
+        ui.checkbox("Hours", &mut self.hours);
+        ui.same_line();
+        ui.checkbox("Minutes", &mut self.minutes);
+        ui.same_line();
+        ui.checkbox("Seconds", &mut self.seconds);
+
+        InputText::new(ui, "Reached message", &mut self.reached_message).build();
+
+        Drag::new("Reached message duration (minutes)")
+            .range(0, i32::MAX)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut self.reached_duration);
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -107,6 +611,10 @@ pub struct CountdownModuleConfig {
     target: CountdownTarget,
     paint: PaintSetting,
     font: FontSetting,
+    #[serde(default)]
+    mode: CountdownMode,
+    #[serde(default)]
+    format: CountdownFormat,
 }
 
 impl ModuleConfig for CountdownModuleConfig {
@@ -119,51 +627,107 @@ impl ModuleConfig for CountdownModuleConfig {
             self.paint.represent(ui, ctx);
         }
 
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
         if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
             let mut current_type_ordinal = self.target.ordinal();
             if ui.combo_simple_string(
                 "Type",
                 &mut current_type_ordinal,
-                CountdownTarget::names().as_slice(),
+                CountdownTarget::type_names().as_slice(),
             ) {
                 self.target = CountdownTarget::from_ordinal(current_type_ordinal);
             }
 
             self.target.represent(ui, ctx);
+            self.mode.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Format", TreeNodeFlags::FRAMED) {
+            self.format.represent(ui, ctx);
         }
     }
 }
 
-pub struct CountdownModuleRenderer;
+/// Amount of calendar events looked at when searching for the next one.
+const CALENDAR_LOOKAHEAD: usize = 16;
+
+pub struct CountdownModuleRenderer {
+    calendar: CalendarFile,
+}
 
 impl CountdownModuleRenderer {
-    fn pluralize<'a>(value: i64, one: &'a str, other: &'a str) -> &'a str {
-        match value {
-            1 => one,
-            _ => other,
+    pub fn new() -> Self {
+        Self {
+            calendar: CalendarFile::new(),
         }
     }
 
-    fn make_countdown_string(target: DateTime<Local>) -> String {
-        let now = Local::now();
-        let diff = target - now;
+    /// Resolves the name, previous and next occurrence of the configured target.
+    ///
+    /// For calendar targets an ongoing event counts as the previous occurrence and the
+    /// name is the summary of the relevant event.
+    fn resolve(
+        &mut self,
+        target: &CountdownTarget,
+        now: &DateTime<Utc>,
+    ) -> (String, Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let calendar = match target {
+            CountdownTarget::Calendar(calendar) => calendar,
+            _ => {
+                return (
+                    target.name().to_string(),
+                    target.previous_occurrence(now),
+                    target.next_occurrence(now),
+                )
+            }
+        };
+
+        self.calendar.update(&calendar.path);
+        let upcoming = self.calendar.upcoming(now, CALENDAR_LOOKAHEAD);
+
+        let ongoing = upcoming.iter().find(|o| o.is_ongoing(now));
+        let next = upcoming.iter().find(|o| o.start > *now);
+
+        let name = ongoing
+            .or(next)
+            .map_or_else(|| target.name().to_string(), |o| o.summary.clone());
+
+        (name, ongoing.map(|o| o.start), next.map(|o| o.start))
+    }
+
+    fn make_countdown_string(&mut self, config: &CountdownModuleConfig) -> String {
+        let now = Utc::now();
+        let format = &config.format;
+
+        let reached_duration = Duration::minutes(format.reached_duration as i64);
+        let (name, previous, next) = self.resolve(&config.target, &now);
 
-        let days = diff.num_days();
-        let hours = diff.num_hours() % 24;
-        let minutes = diff.num_minutes() % 60;
-        let seconds = diff.num_seconds() % 60;
+        let duration = match config.mode {
+            CountdownMode::CountDown => match previous {
+                Some(previous) if now - previous < reached_duration => None,
+                _ => next.map(|next| next - now),
+            },
+            CountdownMode::CountUp => {
+                Some(previous.map_or_else(Duration::zero, |previous| now - previous))
+            }
+        };
+
+        let mut value = String::new();
+
+        match duration {
+            None => CountdownFormat::render(&format.reached_message, &name, &[0; 5], &mut value),
+            Some(duration) => CountdownFormat::render(
+                &format.template,
+                &name,
+                &format.split(duration),
+                &mut value,
+            ),
+        }
 
-        format!(
-            "{:0>3} {} {:0>2} {} {:0>2} {} and {:0>2} {}",
-            days,
-            Self::pluralize(days, "day, ", "days,"),
-            hours,
-            Self::pluralize(hours, "hour, ", "hours,"),
-            minutes,
-            Self::pluralize(minutes, "minute ", "minutes"),
-            seconds,
-            Self::pluralize(seconds, "second ", "seconds")
-        )
+        value
     }
 }
 
@@ -171,11 +735,10 @@ impl ModuleRenderer for CountdownModuleRenderer {
     type Config = CountdownModuleConfig;
 
     fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
-        let value = Self::make_countdown_string(config.target.get_date_time());
+        let value = self.make_countdown_string(config);
 
         let (_, rect) = config
             .font
-            .get_font()
             .measure_str(&value, Some(config.paint.get_paint()));
 
         if let Some((x, y)) = config.position.compute_position_baselined(
@@ -185,10 +748,10 @@ impl ModuleRenderer for CountdownModuleRenderer {
         ) {
             let canvas = data.canvas();
 
-            canvas.draw_str(
+            config.font.draw_str(
+                canvas,
                 &value,
                 Point::new(x as _, y as _),
-                config.font.get_font(),
                 config.paint.get_paint(),
             );
         }
@@ -1,13 +1,13 @@
 use imgui::{InputText, TreeNodeFlags, Ui};
 use native_dialog::FileDialog;
 use serde::{Deserialize, Serialize};
-use skia_safe::Image;
+use skia_safe::{Image, Paint, Rect};
 
-use crate::scene::module::part::{ModulePosition, PaintSetting};
+use crate::scene::module::part::{ModulePosition, PaintSetting, ScaleSetting};
 use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
 use crate::scene::SceneData;
 use crate::ui::context::Context;
-use crate::util::OwnedCodec;
+use crate::util::load_image;
 
 pub(super) struct ImageModule;
 
@@ -27,6 +27,8 @@ impl Module for ImageModule {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageModuleConfig {
     position: ModulePosition,
+    #[serde(default)]
+    scale: ScaleSetting,
     path: String,
     paint_enabled: bool,
     paint: PaintSetting,
@@ -36,6 +38,7 @@ impl Default for ImageModuleConfig {
     fn default() -> Self {
         Self {
             position: Default::default(),
+            scale: Default::default(),
             path: "".into(),
             paint_enabled: false,
             paint: PaintSetting::default(),
@@ -47,6 +50,7 @@ impl ModuleConfig for ImageModuleConfig {
     fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
         if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
             self.position.represent(ui, ctx);
+            self.scale.represent(ui, ctx);
         }
 
         if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
@@ -104,30 +108,11 @@ impl ModuleRenderer for ImageModuleRenderer {
                 return;
             }
 
-            let mut codec = match std::fs::read(&self.current_path).map(OwnedCodec::new) {
-                Ok(Some(v)) => v,
-                Ok(None) => {
-                    log::error!(
-                        "Failed to decode image from \"{}\" as no decoder is available",
-                        self.current_path
-                    );
-                    return;
-                }
-                Err(err) => {
-                    log::error!(
-                        "Failed to read image from \"{}\": {}",
-                        self.current_path,
-                        err
-                    );
-                    return;
-                }
-            };
-
-            let image = match codec.get_image(None, None) {
+            let image = match load_image(&self.current_path) {
                 Ok(image) => image,
                 Err(err) => {
                     log::error!(
-                        "Failed to decode image from \"{}\": {:?}",
+                        "Failed to load image from \"{}\": {}",
                         self.current_path,
                         err
                     );
@@ -139,13 +124,37 @@ impl ModuleRenderer for ImageModuleRenderer {
         }
 
         if let Some(image) = &self.current_image {
-            if let Some(pos) = config
-                .position
-                .compute_position(data, image.width(), image.height())
+            let (available_width, available_height) = match config.position.available_size(data) {
+                None => return,
+                Some(v) => v,
+            };
+
+            let (width, height) = config.scale.compute_size(
+                image.width() as _,
+                image.height() as _,
+                available_width,
+                available_height,
+            );
+
+            if let Some((x, y)) =
+                config
+                    .position
+                    .compute_position(data, width as i32, height as i32)
             {
                 let canvas = data.canvas();
-                let paint = config.paint_enabled.then(|| config.paint.get_paint());
-                canvas.draw_image(image, pos, paint);
+                let default_paint = Paint::default();
+                let paint = if config.paint_enabled {
+                    config.paint.get_paint()
+                } else {
+                    &default_paint
+                };
+
+                canvas.draw_image_rect(
+                    image,
+                    None,
+                    Rect::from_xywh(x as _, y as _, width, height),
+                    paint,
+                );
             }
         }
     }
@@ -0,0 +1,589 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use imgui::{InputText, InputTextMultiline, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use skia_safe::scalar;
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::{
+    expand_json_template, json_number, write_json_value, JsonPath, JsonSocketReader, WatchedFile,
+    JSON_TEMPLATE_HELP,
+};
+
+/// Time before connecting to a socket again after the connection has been closed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+pub(super) struct JsonDataModule;
+
+impl Module for JsonDataModule {
+    type Config = JsonDataModuleConfig;
+    type Renderer = JsonDataModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        JsonDataModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "JSON data".into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JsonSource {
+    File { path: String },
+    Socket { path: String },
+}
+
+impl JsonSource {
+    pub const fn ordinal(&self) -> usize {
+        match self {
+            JsonSource::File { .. } => 0,
+            JsonSource::Socket { .. } => 1,
+        }
+    }
+
+    pub fn from_ordinal(ordinal: usize) -> Self {
+        match ordinal {
+            0 => JsonSource::File {
+                path: String::new(),
+            },
+            1 => JsonSource::Socket {
+                path: String::new(),
+            },
+            _ => panic!("Invalid JSON source ordinal"),
+        }
+    }
+
+    pub const fn type_names() -> [&'static str; 2] {
+        ["File", "Unix socket"]
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            JsonSource::File { path } | JsonSource::Socket { path } => path,
+        }
+    }
+
+    fn represent(&mut self, ui: &Ui, pending_path: &mut Option<String>) {
+        let mut current_type_ordinal = self.ordinal();
+        if ui.combo_simple_string(
+            "Source",
+            &mut current_type_ordinal,
+            JsonSource::type_names().as_slice(),
+        ) {
+            *self = JsonSource::from_ordinal(current_type_ordinal);
+            *pending_path = None;
+        }
+
+        match self {
+            JsonSource::File { path } => {
+                if let Some(_tok) = ui.begin_table("Source Options", 2) {
+                    ui.table_next_row();
+                    ui.table_next_column();
+
+                    InputText::new(ui, "Path", path).build();
+                    ui.table_next_column();
+                    if ui.small_button("...") {
+                        match FileDialog::new()
+                            .add_filter("JSON file", &["json"])
+                            .show_open_single_file()
+                        {
+                            Ok(None) => {}
+                            Ok(Some(p)) => *path = p.to_string_lossy().into(),
+                            Err(err) => {
+                                log::error!("Failed to show a file dialog: {}", err)
+                            }
+                        };
+                    }
+                }
+            }
+            JsonSource::Socket { path } => {
+                // Every new path starts another reader, so only apply it once it's complete
+                let mut edited = pending_path.clone().unwrap_or_else(|| path.clone());
+
+                let entered = InputText::new(ui, "Socket", &mut edited)
+                    .hint("For example: /run/user/1000/weather.sock")
+                    .enter_returns_true(true)
+                    .build();
+
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(
+                        "The socket is read until the connection is closed, \
+                        every document sent replaces the previous one",
+                    );
+                }
+
+                if entered || edited == *path {
+                    *path = edited;
+                    *pending_path = None;
+                } else {
+                    *pending_path = Some(edited);
+                    ui.text_disabled("Press enter to connect to the changed socket");
+                }
+            }
+        }
+    }
+}
+
+impl Default for JsonSource {
+    fn default() -> Self {
+        Self::from_ordinal(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    Contains,
+}
+
+impl Comparison {
+    const VALUES: [Self; 7] = [
+        Self::Greater,
+        Self::GreaterOrEqual,
+        Self::Less,
+        Self::LessOrEqual,
+        Self::Equal,
+        Self::NotEqual,
+        Self::Contains,
+    ];
+
+    /// Compares a value of the document with the one entered by the user.
+    ///
+    /// Both are compared as numbers if they are numbers, otherwise only the equality and
+    /// containment comparisons apply, using the text of the value.
+    fn test(self, actual: &Value, expected: &str) -> bool {
+        let expected = expected.trim();
+        let numbers = json_number(actual).zip(expected.parse::<f64>().ok());
+
+        let text = || {
+            let mut text = String::new();
+            write_json_value(actual, None, &mut text);
+            text
+        };
+
+        match (self, numbers) {
+            (Self::Greater, Some((a, b))) => a > b,
+            (Self::GreaterOrEqual, Some((a, b))) => a >= b,
+            (Self::Less, Some((a, b))) => a < b,
+            (Self::LessOrEqual, Some((a, b))) => a <= b,
+            (Self::Equal, Some((a, b))) => a == b,
+            (Self::NotEqual, Some((a, b))) => a != b,
+            (Self::Equal, None) => text() == expected,
+            (Self::NotEqual, None) => text() != expected,
+            (Self::Contains, _) => text().contains(expected),
+            _ => false,
+        }
+    }
+}
+
+impl Default for Comparison {
+    fn default() -> Self {
+        Self::Greater
+    }
+}
+
+impl ModuleConfig for Comparison {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Comparison", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Greater => "is greater than",
+                Self::GreaterOrEqual => "is at least",
+                Self::Less => "is less than",
+                Self::LessOrEqual => "is at most",
+                Self::Equal => "is equal to",
+                Self::NotEqual => "is not equal to",
+                Self::Contains => "contains",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current];
+    }
+}
+
+/// Colors the text when a value of the document passes a comparison.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonColorRule {
+    /// JSONPath expression selecting the values compared, the rule applies if any passes.
+    path: String,
+    comparison: Comparison,
+    value: String,
+    paint: PaintSetting,
+}
+
+impl Default for JsonColorRule {
+    fn default() -> Self {
+        Self {
+            path: String::from("$.value"),
+            comparison: Comparison::default(),
+            value: String::from("90"),
+            paint: PaintSetting::from_rgba(1.0, 0.3, 0.3, 1.0),
+        }
+    }
+}
+
+impl ModuleConfig for JsonColorRule {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        InputText::new(ui, "Path", &mut self.path)
+            .hint("JSONPath, for example $.value")
+            .build();
+        self.comparison.represent(ui, ctx);
+        InputText::new(ui, "Value", &mut self.value).build();
+
+        self.paint.represent(ui, ctx);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonDataModuleConfig {
+    position: ModulePosition,
+    source: JsonSource,
+
+    /// Text shown, with placeholders for values of the document.
+    template: String,
+
+    /// Rules applied in order, the first matching one determines the color.
+    rules: Vec<JsonColorRule>,
+
+    paint: PaintSetting,
+    font: FontSetting,
+
+    /// The socket path being edited, applied once enter is pressed.
+    #[serde(skip)]
+    pending_path: Option<String>,
+
+    /// Problems with the source or the rules, set by the renderer.
+    #[serde(skip)]
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl Default for JsonDataModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            source: JsonSource::default(),
+            template: String::from("{$.value|.1}"),
+            rules: vec![JsonColorRule::default()],
+            paint: PaintSetting::default(),
+            font: FontSetting::default(),
+            pending_path: None,
+            errors: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl ModuleConfig for JsonDataModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            self.source.represent(ui, &mut self.pending_path);
+
+            InputTextMultiline::new(ui, "Template", &mut self.template, [0.0, 120.0]).build();
+            if ui.is_item_hovered() {
+                ui.tooltip_text(JSON_TEMPLATE_HELP);
+            }
+
+            for err in self.errors.lock().unwrap().iter() {
+                ui.text_colored(ERROR_COLOR, err);
+            }
+        }
+
+        if ui.collapsing_header("Conditional colors", TreeNodeFlags::FRAMED) {
+            let mut to_remove = None;
+
+            for (i, rule) in self.rules.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                rule.represent(ui, ctx);
+
+                if ui.button("Remove rule") {
+                    to_remove = Some(i);
+                }
+
+                ui.separator();
+            }
+
+            if let Some(i) = to_remove {
+                self.rules.remove(i);
+            }
+
+            if ui.button("Add rule") {
+                self.rules.push(JsonColorRule::default());
+            }
+        }
+    }
+}
+
+/// Where documents are read from, along with what is needed to notice new ones.
+struct LoadedSource {
+    source: JsonSource,
+    file: WatchedFile,
+    reader: Option<JsonSocketReader>,
+}
+
+impl LoadedSource {
+    fn new(source: &JsonSource) -> Self {
+        let reader = match source {
+            JsonSource::Socket { path } if !path.is_empty() => {
+                Some(JsonSocketReader::spawn(path, RECONNECT_DELAY))
+            }
+            _ => None,
+        };
+
+        Self {
+            source: source.clone(),
+            file: WatchedFile::new(),
+            reader,
+        }
+    }
+}
+
+/// Identifies everything the displayed text depends on.
+struct TextKey {
+    revision: u64,
+    template: String,
+    rules: Vec<(String, Comparison, String)>,
+}
+
+impl TextKey {
+    fn new(config: &JsonDataModuleConfig, revision: u64) -> Self {
+        Self {
+            revision,
+            template: config.template.clone(),
+            rules: Self::rules(config)
+                .map(|(path, comparison, value)| (path.into(), comparison, value.into()))
+                .collect(),
+        }
+    }
+
+    fn rules(config: &JsonDataModuleConfig) -> impl Iterator<Item = (&str, Comparison, &str)> + '_ {
+        config
+            .rules
+            .iter()
+            .map(|rule| (rule.path.as_str(), rule.comparison, rule.value.as_str()))
+    }
+
+    fn matches(&self, config: &JsonDataModuleConfig, revision: u64) -> bool {
+        self.revision == revision
+            && self.template == config.template
+            && self
+                .rules
+                .iter()
+                .map(|(path, comparison, value)| (path.as_str(), *comparison, value.as_str()))
+                .eq(Self::rules(config))
+    }
+}
+
+/// The template expanded with the current document.
+struct DisplayedText {
+    key: TextKey,
+    lines: Vec<String>,
+
+    /// Index of the rule determining the color.
+    rule: Option<usize>,
+    rule_errors: Vec<String>,
+}
+
+impl DisplayedText {
+    fn new(config: &JsonDataModuleConfig, key: TextKey, document: Option<&Value>) -> Self {
+        let mut text = String::new();
+        if let Some(document) = document {
+            expand_json_template(&config.template, document, &mut text);
+        }
+
+        let mut rule = None;
+        let mut rule_errors = Vec::new();
+
+        for (i, candidate) in config.rules.iter().enumerate() {
+            let path = match JsonPath::parse(&candidate.path) {
+                Ok(v) => v,
+                Err(err) => {
+                    rule_errors.push(format!("Invalid rule path: {}", err));
+                    continue;
+                }
+            };
+
+            let matches = document.map_or(false, |document| {
+                path.select(document)
+                    .into_iter()
+                    .any(|value| candidate.comparison.test(value, &candidate.value))
+            });
+
+            if matches && rule.is_none() {
+                rule = Some(i);
+            }
+        }
+
+        Self {
+            key,
+            lines: text.lines().map(ToString::to_string).collect(),
+            rule,
+            rule_errors,
+        }
+    }
+}
+
+pub struct JsonDataModuleRenderer {
+    loaded: Option<LoadedSource>,
+
+    /// The last document read, kept while the source fails so the text doesn't flicker.
+    document: Option<Value>,
+    source_error: Option<String>,
+
+    /// Incremented whenever the document or the source changes.
+    revision: u64,
+    text: Option<DisplayedText>,
+}
+
+impl JsonDataModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            loaded: None,
+            document: None,
+            source_error: None,
+            revision: 0,
+            text: None,
+        }
+    }
+
+    /// Switches to another source if it changed and picks up new documents.
+    fn update_source(&mut self, config: &JsonDataModuleConfig) {
+        let outdated = self
+            .loaded
+            .as_ref()
+            .map_or(true, |loaded| loaded.source != config.source);
+
+        if outdated {
+            // Dropping the old reader stops it
+            self.loaded = Some(LoadedSource::new(&config.source));
+            self.document = None;
+            self.source_error = None;
+            self.revision += 1;
+        }
+
+        let path = config.source.path();
+        if path.is_empty() {
+            self.source_error = Some(String::from("No source selected"));
+            return;
+        }
+
+        let loaded = self.loaded.as_mut().unwrap();
+        let result = match &config.source {
+            JsonSource::File { .. } => {
+                if loaded.file.update(path) {
+                    Some(read_file(path))
+                } else {
+                    None
+                }
+            }
+            JsonSource::Socket { .. } => loaded.reader.as_ref().and_then(JsonSocketReader::take),
+        };
+
+        match result {
+            None => {}
+            Some(Ok(document)) => {
+                self.document = Some(document);
+                self.source_error = None;
+                self.revision += 1;
+            }
+            Some(Err(err)) => self.source_error = Some(err),
+        }
+    }
+
+    fn update(&mut self, config: &JsonDataModuleConfig) -> &DisplayedText {
+        self.update_source(config);
+
+        let outdated = self
+            .text
+            .as_ref()
+            .map_or(true, |text| !text.key.matches(config, self.revision));
+        if outdated {
+            let key = TextKey::new(config, self.revision);
+            self.text = Some(DisplayedText::new(config, key, self.document.as_ref()));
+        }
+
+        let text = self.text.as_ref().unwrap();
+        *config.errors.lock().unwrap() = self
+            .source_error
+            .iter()
+            .chain(&text.rule_errors)
+            .cloned()
+            .collect();
+
+        text
+    }
+}
+
+fn read_file(path: &str) -> Result<Value, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+
+    serde_json::from_str(&content).map_err(|err| format!("Invalid JSON in {}: {}", path, err))
+}
+
+impl ModuleRenderer for JsonDataModuleRenderer {
+    type Config = JsonDataModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let text = self.update(config);
+        if text.lines.is_empty() {
+            return;
+        }
+
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+
+        let text_width = text
+            .lines
+            .iter()
+            .map(|line| font.measure_str(line, None).0)
+            .fold(0.0, scalar::max);
+        let text_height = line_height * text.lines.len() as scalar;
+
+        let (x, y) =
+            match config
+                .position
+                .compute_position(data, text_width as i32, text_height as i32)
+            {
+                None => return,
+                Some((x, y)) => (x as scalar, y as scalar),
+            };
+
+        let paint = text
+            .rule
+            .and_then(|i| config.rules.get(i))
+            .map_or(config.paint.get_paint(), |rule| rule.paint.get_paint());
+
+        let mut line_y = y - metrics.ascent;
+        for line in &text.lines {
+            font.draw_str(data.canvas(), line, (x, line_y), paint);
+            line_y += line_height;
+        }
+    }
+}
@@ -1,11 +1,27 @@
 use std::collections::HashMap;
 use std::lazy::SyncLazy;
 
+use crate::scene::module::agenda::AgendaModule;
+use crate::scene::module::astronomy::AstronomyModule;
+use crate::scene::module::battery::BatteryModule;
+use crate::scene::module::calendar::CalendarModule;
+use crate::scene::module::chart::ChartModule;
 use crate::scene::module::clear::ClearModule;
+use crate::scene::module::clock::ClockModule;
+use crate::scene::module::command::CommandModule;
 use crate::scene::module::countdown::CountdownModule;
 use crate::scene::module::image::ImageModule;
+use crate::scene::module::json_data::JsonDataModule;
+use crate::scene::module::log_tail::LogTailModule;
+use crate::scene::module::notes::NotesModule;
+use crate::scene::module::particle::ParticleModule;
+use crate::scene::module::shader::ShaderModule;
+use crate::scene::module::sky::SkyModule;
 use crate::scene::module::snow::SnowModule;
+use crate::scene::module::svg::SvgModule;
+use crate::scene::module::system_monitor::SystemMonitorModule;
 use crate::scene::module::text::TextModule;
+use crate::scene::module::todo::TodoModule;
 use crate::scene::module::{Module, ModuleWrapper};
 
 // Ideally all of this would be one huge const initializer, but there are no const
@@ -22,6 +38,22 @@ static KNOWN_MODULES: SyncLazy<HashMap<String, ModuleWrapper>> = SyncLazy::new(|
     insert_helper::<SnowModule>(&mut map);
     insert_helper::<ImageModule>(&mut map);
     insert_helper::<CountdownModule>(&mut map);
+    insert_helper::<SvgModule>(&mut map);
+    insert_helper::<ClockModule>(&mut map);
+    insert_helper::<AgendaModule>(&mut map);
+    insert_helper::<CalendarModule>(&mut map);
+    insert_helper::<SystemMonitorModule>(&mut map);
+    insert_helper::<BatteryModule>(&mut map);
+    insert_helper::<ParticleModule>(&mut map);
+    insert_helper::<ShaderModule>(&mut map);
+    insert_helper::<SkyModule>(&mut map);
+    insert_helper::<AstronomyModule>(&mut map);
+    insert_helper::<CommandModule>(&mut map);
+    insert_helper::<LogTailModule>(&mut map);
+    insert_helper::<NotesModule>(&mut map);
+    insert_helper::<TodoModule>(&mut map);
+    insert_helper::<ChartModule>(&mut map);
+    insert_helper::<JsonDataModule>(&mut map);
 
     map
 });
@@ -0,0 +1,451 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use skia_safe::scalar;
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::FileTail;
+
+/// Amount of bytes read from the end of the file when starting to follow it.
+const INITIAL_BYTES: u64 = 256 * 1024;
+
+pub(super) struct LogTailModule;
+
+impl Module for LogTailModule {
+    type Config = LogTailModuleConfig;
+    type Renderer = LogTailModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        LogTailModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Log tail".into()
+    }
+}
+
+/// Draws the parts of lines matching a regular expression in a different color.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogHighlight {
+    pattern: String,
+
+    /// Whether the entire line is colored instead of only the match.
+    whole_line: bool,
+
+    paint: PaintSetting,
+}
+
+impl LogHighlight {
+    fn new(pattern: &str, paint: PaintSetting) -> Self {
+        Self {
+            pattern: String::from(pattern),
+            whole_line: false,
+            paint,
+        }
+    }
+}
+
+impl Default for LogHighlight {
+    fn default() -> Self {
+        Self::new("", PaintSetting::from_rgba(1.0, 1.0, 0.3, 1.0))
+    }
+}
+
+impl ModuleConfig for LogHighlight {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        InputText::new(ui, "Pattern", &mut self.pattern)
+            .hint("Regular expression")
+            .build();
+        ui.checkbox("Color whole line", &mut self.whole_line);
+
+        self.paint.represent(ui, ctx);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogTailModuleConfig {
+    position: ModulePosition,
+    path: String,
+
+    /// Amount of lines shown, counted from the end of the file.
+    max_lines: i32,
+
+    /// Regular expression lines need to match in order to be shown, all lines are shown
+    /// if empty.
+    filter: String,
+
+    /// Highlights are applied in order, earlier highlights win over later ones.
+    highlights: Vec<LogHighlight>,
+
+    paint: PaintSetting,
+    font: FontSetting,
+
+    /// Problems with the file or the expressions, set by the renderer.
+    #[serde(skip)]
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl Default for LogTailModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            path: String::new(),
+            max_lines: 20,
+            filter: String::new(),
+            highlights: vec![
+                LogHighlight::new(
+                    "(?i)error|fail(ed|ure)?",
+                    PaintSetting::from_rgba(1.0, 0.3, 0.3, 1.0),
+                ),
+                LogHighlight::new(
+                    "(?i)warn(ing)?",
+                    PaintSetting::from_rgba(1.0, 0.7, 0.2, 1.0),
+                ),
+            ],
+            paint: PaintSetting::default(),
+            font: FontSetting::default(),
+            errors: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl ModuleConfig for LogTailModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            if let Some(_tok) = ui.begin_table("Module Options", 2) {
+                ui.table_next_row();
+                ui.table_next_column();
+
+                InputText::new(ui, "Path", &mut self.path).build();
+                ui.table_next_column();
+                if ui.small_button("...") {
+                    match FileDialog::new().show_open_single_file() {
+                        Ok(None) => {}
+                        Ok(Some(p)) => self.path = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+
+            Drag::new("Lines")
+                .range(1, 1000)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.max_lines);
+
+            InputText::new(ui, "Filter", &mut self.filter)
+                .hint("Regular expression, empty to show all lines")
+                .build();
+
+            for err in self.errors.lock().unwrap().iter() {
+                ui.text_colored(ERROR_COLOR, err);
+            }
+        }
+
+        if ui.collapsing_header("Highlights", TreeNodeFlags::FRAMED) {
+            let mut to_remove = None;
+
+            for (i, highlight) in self.highlights.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                highlight.represent(ui, ctx);
+
+                if ui.button("Remove highlight") {
+                    to_remove = Some(i);
+                }
+
+                ui.separator();
+            }
+
+            if let Some(i) = to_remove {
+                self.highlights.remove(i);
+            }
+
+            if ui.button("Add highlight") {
+                self.highlights.push(LogHighlight::default());
+            }
+        }
+    }
+}
+
+/// A line split into ranges, each drawn with the paint of a highlight or the default paint.
+struct HighlightedLine {
+    text: String,
+    spans: Vec<(Option<usize>, Range<usize>)>,
+}
+
+/// Compiles a regular expression entered by the user, empty patterns are ignored.
+fn compile(pattern: &str, name: &str, errors: &mut Vec<String>) -> Option<Regex> {
+    if pattern.is_empty() {
+        return None;
+    }
+
+    match Regex::new(pattern) {
+        Ok(regex) => Some(regex),
+        Err(err) => {
+            errors.push(format!("Invalid {}: {}", name, err));
+            None
+        }
+    }
+}
+
+/// A followed file along with the settings its lines have been processed with.
+struct Following {
+    path: String,
+    max_lines: usize,
+    filter: String,
+    patterns: Vec<(String, bool)>,
+
+    tail: Option<FileTail>,
+    filter_regex: Option<Regex>,
+    highlight_regexes: Vec<Option<Regex>>,
+    lines: VecDeque<HighlightedLine>,
+
+    /// Errors of the expressions, which don't change while following.
+    pattern_errors: Vec<String>,
+    tail_error: Option<String>,
+}
+
+impl Following {
+    fn new(config: &LogTailModuleConfig) -> Self {
+        let mut pattern_errors = Vec::new();
+
+        let filter_regex = compile(&config.filter, "filter", &mut pattern_errors);
+        let highlight_regexes = config
+            .highlights
+            .iter()
+            .map(|highlight| compile(&highlight.pattern, "highlight", &mut pattern_errors))
+            .collect();
+
+        let (tail, tail_error) = if config.path.is_empty() {
+            (None, Some(String::from("No file selected")))
+        } else {
+            match FileTail::new(&config.path, INITIAL_BYTES) {
+                Ok(tail) => (Some(tail), None),
+                Err(err) => (
+                    None,
+                    Some(format!("Failed to watch {}: {}", config.path, err)),
+                ),
+            }
+        };
+
+        Self {
+            path: config.path.clone(),
+            max_lines: config.max_lines.max(1) as usize,
+            filter: config.filter.clone(),
+            patterns: Self::patterns(config)
+                .map(|(pattern, whole_line)| (pattern.into(), whole_line))
+                .collect(),
+            tail,
+            filter_regex,
+            highlight_regexes,
+            lines: VecDeque::new(),
+            pattern_errors,
+            tail_error,
+        }
+    }
+
+    fn patterns(config: &LogTailModuleConfig) -> impl Iterator<Item = (&str, bool)> + '_ {
+        config
+            .highlights
+            .iter()
+            .map(|highlight| (highlight.pattern.as_str(), highlight.whole_line))
+    }
+
+    fn matches(&self, config: &LogTailModuleConfig) -> bool {
+        self.path == config.path
+            && self.max_lines == config.max_lines.max(1) as usize
+            && self.filter == config.filter
+            && self
+                .patterns
+                .iter()
+                .map(|(pattern, whole_line)| (pattern.as_str(), *whole_line))
+                .eq(Self::patterns(config))
+    }
+
+    /// Reads new lines from the file, returns whether the errors changed.
+    fn update(&mut self) -> bool {
+        let tail = match &mut self.tail {
+            None => return false,
+            Some(v) => v,
+        };
+
+        let mut new_lines = Vec::new();
+        let tail_error = tail
+            .read_lines(&mut new_lines)
+            .err()
+            .map(|err| format!("Failed to read {}: {}", self.path, err));
+
+        for line in new_lines {
+            if let Some(filter) = &self.filter_regex {
+                if !filter.is_match(&line) {
+                    continue;
+                }
+            }
+
+            let line = self.highlight(line);
+
+            self.lines.push_back(line);
+            while self.lines.len() > self.max_lines {
+                self.lines.pop_front();
+            }
+        }
+
+        if self.tail_error != tail_error {
+            self.tail_error = tail_error;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Determines which highlight applies to each part of a line.
+    fn highlight(&self, text: String) -> HighlightedLine {
+        let mut owners = vec![None; text.len()];
+
+        // Applied in reverse, so earlier highlights overwrite later ones
+        for (i, (regex, (_, whole_line))) in self
+            .highlight_regexes
+            .iter()
+            .zip(&self.patterns)
+            .enumerate()
+            .rev()
+        {
+            let regex = match regex {
+                None => continue,
+                Some(v) => v,
+            };
+
+            if *whole_line {
+                if regex.is_match(&text) {
+                    owners.fill(Some(i));
+                }
+            } else {
+                for found in regex.find_iter(&text) {
+                    owners[found.range()].fill(Some(i));
+                }
+            }
+        }
+
+        let mut spans: Vec<(Option<usize>, Range<usize>)> = Vec::new();
+        for (position, owner) in owners.into_iter().enumerate() {
+            match spans.last_mut() {
+                Some((last, range)) if *last == owner => range.end = position + 1,
+                _ => spans.push((owner, position..position + 1)),
+            }
+        }
+
+        HighlightedLine { text, spans }
+    }
+
+    fn errors(&self) -> Vec<String> {
+        self.pattern_errors
+            .iter()
+            .chain(&self.tail_error)
+            .cloned()
+            .collect()
+    }
+}
+
+pub struct LogTailModuleRenderer {
+    following: Option<Following>,
+}
+
+impl LogTailModuleRenderer {
+    pub fn new() -> Self {
+        Self { following: None }
+    }
+
+    /// Follows the file again if the settings changed and reads new lines.
+    fn update(&mut self, config: &LogTailModuleConfig) -> &Following {
+        let outdated = self
+            .following
+            .as_ref()
+            .map_or(true, |following| !following.matches(config));
+
+        let mut errors_changed = outdated;
+        if outdated {
+            self.following = Some(Following::new(config));
+        }
+
+        let following = self.following.as_mut().unwrap();
+        errors_changed |= following.update();
+
+        if errors_changed {
+            *config.errors.lock().unwrap() = following.errors();
+        }
+
+        following
+    }
+}
+
+impl ModuleRenderer for LogTailModuleRenderer {
+    type Config = LogTailModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let following = self.update(config);
+        if following.lines.is_empty() {
+            return;
+        }
+
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+
+        let text_width = following
+            .lines
+            .iter()
+            .map(|line| font.measure_str(&line.text, None).0)
+            .fold(0.0, scalar::max);
+        let text_height = line_height * following.lines.len() as scalar;
+
+        let (x, y) =
+            match config
+                .position
+                .compute_position(data, text_width as i32, text_height as i32)
+            {
+                None => return,
+                Some((x, y)) => (x as scalar, y as scalar),
+            };
+
+        let mut line_y = y - metrics.ascent;
+        for line in &following.lines {
+            let mut span_x = x;
+
+            for (owner, range) in &line.spans {
+                let paint = owner
+                    .and_then(|i| config.highlights.get(i))
+                    .map_or(config.paint.get_paint(), |highlight| {
+                        highlight.paint.get_paint()
+                    });
+
+                let text = &line.text[range.clone()];
+                font.draw_str(data.canvas(), text, (span_x, line_y), paint);
+                span_x += font.measure_str(text, None).0;
+            }
+
+            line_y += line_height;
+        }
+    }
+}
@@ -10,13 +10,29 @@ pub use known::*;
 use crate::scene::SceneData;
 use crate::ui::context::Context;
 
+mod agenda;
+mod astronomy;
+mod battery;
+mod calendar;
+mod chart;
 mod clear;
+mod clock;
+mod command;
 mod countdown;
 mod image;
+mod json_data;
 mod known;
+mod log_tail;
+mod notes;
 mod part;
+mod particle;
+mod shader;
+mod sky;
 mod snow;
+mod svg;
+mod system_monitor;
 mod text;
+mod todo;
 
 pub trait Module {
     type Config: ModuleConfig;
@@ -0,0 +1,787 @@
+use std::sync::{Arc, Mutex};
+
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use skia_safe::font_style::{Slant, Weight};
+use skia_safe::paint::Style;
+use skia_safe::textlayout::{
+    Paragraph, ParagraphBuilder, ParagraphStyle, TextDecoration, TextStyle,
+};
+use skia_safe::{scalar, FontStyle, Paint, Path as SkPath, Rect};
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::{SendCell, WatchedFile};
+
+/// Font sizes of the heading levels relative to the body text.
+const HEADING_SIZES: [scalar; 6] = [2.0, 1.6, 1.35, 1.2, 1.1, 1.0];
+
+pub(super) struct NotesModule;
+
+impl Module for NotesModule {
+    type Config = NotesModuleConfig;
+    type Renderer = NotesModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        NotesModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Notes".into()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotesModuleConfig {
+    position: ModulePosition,
+
+    /// Path of the markdown file.
+    path: String,
+
+    /// Width text is wrapped at, 0 to only wrap at explicit line breaks.
+    max_width: f32,
+
+    /// Height of a line relative to the font size.
+    line_spacing: f32,
+
+    /// Space between two blocks, such as paragraphs or list items.
+    block_spacing: f32,
+
+    /// Indentation per level of nested lists and quotes.
+    indent: f32,
+
+    /// Scales the font size of headings relative to their default size.
+    heading_scale: f32,
+
+    /// Font family used for code, looked up from the system fonts.
+    code_family: String,
+
+    /// Whether the text of checked items is struck through.
+    strike_done: bool,
+
+    font: FontSetting,
+    paint: PaintSetting,
+    heading_paint: PaintSetting,
+    code_paint: PaintSetting,
+    code_background: PaintSetting,
+    checkbox_paint: PaintSetting,
+    done_paint: PaintSetting,
+
+    /// Why the file could not be loaded, set by the renderer.
+    #[serde(skip)]
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl NotesModuleConfig {
+    /// Font size of the body text.
+    fn font_size(&self) -> scalar {
+        self.font.text_style().font_size()
+    }
+}
+
+impl Default for NotesModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            path: String::new(),
+            max_width: 600.0,
+            line_spacing: 1.2,
+            block_spacing: 8.0,
+            indent: 28.0,
+            heading_scale: 1.0,
+            code_family: String::from("monospace"),
+            strike_done: true,
+            font: FontSetting::default(),
+            paint: PaintSetting::default(),
+            heading_paint: PaintSetting::default(),
+            code_paint: PaintSetting::from_rgba(0.6, 0.9, 0.6, 1.0),
+            code_background: PaintSetting::from_rgba(1.0, 1.0, 1.0, 0.1),
+            checkbox_paint: PaintSetting::default(),
+            done_paint: PaintSetting::from_rgba(1.0, 1.0, 1.0, 0.5),
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl ModuleConfig for NotesModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+
+            InputText::new(ui, "Code font", &mut self.code_family)
+                .hint("monospace")
+                .build();
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            if let Some(_tok) = ui.begin_table("Module Options", 2) {
+                ui.table_next_row();
+                ui.table_next_column();
+
+                InputText::new(ui, "Path", &mut self.path).build();
+                ui.table_next_column();
+                if ui.small_button("...") {
+                    match FileDialog::new()
+                        .add_filter("Markdown", &["md", "markdown", "txt"])
+                        .show_open_single_file()
+                    {
+                        Ok(None) => {}
+                        Ok(Some(p)) => self.path = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+
+            if let Some(err) = self.error.lock().unwrap().as_ref() {
+                ui.text_colored(ERROR_COLOR, err);
+            }
+        }
+
+        if ui.collapsing_header("Layout", TreeNodeFlags::FRAMED) {
+            Drag::new("Maximum width")
+                .range(0.0, 10000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.max_width);
+
+            if ui.is_item_hovered() {
+                ui.tooltip_text("0 to only break lines where the file does");
+            }
+
+            Drag::new("Line spacing")
+                .range(0.5, 4.0)
+                .speed(0.01)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.line_spacing);
+
+            Drag::new("Block spacing")
+                .range(0.0, 200.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.block_spacing);
+
+            Drag::new("Indent")
+                .range(0.0, 200.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.indent);
+
+            Drag::new("Heading scale")
+                .range(0.5, 4.0)
+                .speed(0.01)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.heading_scale);
+        }
+
+        if ui.collapsing_header("Colors", TreeNodeFlags::FRAMED) {
+            let paints = [
+                ("Text", &mut self.paint),
+                ("Headings", &mut self.heading_paint),
+                ("Code", &mut self.code_paint),
+                ("Code background", &mut self.code_background),
+                ("Checkboxes", &mut self.checkbox_paint),
+                ("Checked items", &mut self.done_paint),
+            ];
+
+            for (name, paint) in paints {
+                let _id = ui.push_id(name);
+
+                ui.text(name);
+                paint.represent(ui, ctx);
+                ui.separator();
+            }
+
+            ui.checkbox("Strike through checked items", &mut self.strike_done);
+        }
+    }
+}
+
+/// Inline formatting of a piece of text.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SpanStyle {
+    strong: bool,
+    emphasis: bool,
+    strikethrough: bool,
+    code: bool,
+}
+
+impl SpanStyle {
+    fn apply(&self, base: &TextStyle, config: &NotesModuleConfig) -> TextStyle {
+        let mut style = base.clone();
+        let font_style = base.font_style();
+
+        if self.strong || self.emphasis {
+            let weight = if self.strong {
+                Weight::BOLD
+            } else {
+                font_style.weight()
+            };
+
+            let slant = if self.emphasis {
+                Slant::Italic
+            } else {
+                font_style.slant()
+            };
+
+            style.set_font_style(FontStyle::new(weight, font_style.width(), slant));
+        }
+
+        if self.strikethrough {
+            style.set_decoration_type(TextDecoration::LINE_THROUGH);
+        }
+
+        if self.code {
+            style
+                .set_font_families(&[config.code_family.as_str()])
+                .set_foreground_color(config.code_paint.get_paint().clone())
+                .set_background_color(config.code_background.get_paint().clone());
+        }
+
+        style
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockKind {
+    Paragraph,
+    Heading(u32),
+    Code,
+    Rule,
+}
+
+/// What is drawn in front of a list item.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Marker {
+    Bullet,
+    Number(u64),
+    Checkbox(bool),
+}
+
+/// A block of text, laid out as a paragraph of its own.
+#[derive(Debug)]
+struct Block {
+    kind: BlockKind,
+
+    /// Nesting level of the lists and quotes the block is in.
+    depth: usize,
+    marker: Option<Marker>,
+    spans: Vec<(SpanStyle, String)>,
+}
+
+impl Block {
+    fn new(kind: BlockKind, depth: usize, marker: Option<Marker>) -> Self {
+        Self {
+            kind,
+            depth,
+            marker,
+            spans: Vec::new(),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.marker == Some(Marker::Checkbox(true))
+    }
+}
+
+/// Splits a markdown document into blocks.
+///
+/// Only the subset of markdown which makes sense on a wallpaper is supported, links for
+/// example are shown as plain text.
+fn parse_document(source: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Block> = None;
+
+    let mut style = SpanStyle::default();
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut quote_depth = 0;
+    let mut marker = None;
+
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(source, options) {
+        let depth = lists.len() + quote_depth;
+
+        match event {
+            Event::Start(Tag::Paragraph) => flush(&mut current, &mut blocks),
+            Event::Start(Tag::Heading(level)) => {
+                flush(&mut current, &mut blocks);
+                current = Some(Block::new(BlockKind::Heading(level), depth, None));
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                flush(&mut current, &mut blocks);
+                current = Some(Block::new(BlockKind::Code, depth, None));
+                style.code = true;
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                if let Some((_, text)) = current.as_mut().and_then(|block| block.spans.last_mut()) {
+                    text.truncate(text.trim_end_matches('\n').len());
+                }
+
+                flush(&mut current, &mut blocks);
+                style.code = false;
+            }
+            Event::Start(Tag::List(start)) => {
+                flush(&mut current, &mut blocks);
+                lists.push(start);
+            }
+            Event::End(Tag::List(_)) => {
+                flush(&mut current, &mut blocks);
+                lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut current, &mut blocks);
+                marker = Some(match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        Marker::Number(*number - 1)
+                    }
+                    _ => Marker::Bullet,
+                });
+            }
+            Event::TaskListMarker(checked) => {
+                // Items may consist of nothing but the checkbox
+                marker = Some(Marker::Checkbox(checked));
+                current
+                    .get_or_insert_with(|| Block::new(BlockKind::Paragraph, depth, marker.take()));
+            }
+            Event::Start(Tag::BlockQuote) => {
+                flush(&mut current, &mut blocks);
+                quote_depth += 1;
+            }
+            Event::End(Tag::BlockQuote) => {
+                flush(&mut current, &mut blocks);
+                quote_depth -= 1;
+            }
+            Event::Start(Tag::Emphasis) => style.emphasis = true,
+            Event::End(Tag::Emphasis) => style.emphasis = false,
+            Event::Start(Tag::Strong) => style.strong = true,
+            Event::End(Tag::Strong) => style.strong = false,
+            Event::Start(Tag::Strikethrough) => style.strikethrough = true,
+            Event::End(Tag::Strikethrough) => style.strikethrough = false,
+            Event::Text(text) => push_text(&mut current, depth, &mut marker, style, &text),
+            Event::Code(text) => {
+                let style = SpanStyle {
+                    code: true,
+                    ..style
+                };
+                push_text(&mut current, depth, &mut marker, style, &text);
+            }
+            Event::SoftBreak => push_text(&mut current, depth, &mut marker, style, " "),
+            Event::HardBreak => push_text(&mut current, depth, &mut marker, style, "\n"),
+            Event::Rule => {
+                flush(&mut current, &mut blocks);
+                blocks.push(Block::new(BlockKind::Rule, depth, None));
+            }
+            Event::End(Tag::Paragraph | Tag::Heading(_) | Tag::Item) => {
+                flush(&mut current, &mut blocks)
+            }
+            _ => {}
+        }
+    }
+
+    flush(&mut current, &mut blocks);
+    blocks
+}
+
+/// Finishes the current block, blocks without any content are dropped.
+fn flush(current: &mut Option<Block>, blocks: &mut Vec<Block>) {
+    if let Some(block) = current.take() {
+        if !block.spans.is_empty() || block.marker.is_some() {
+            blocks.push(block);
+        }
+    }
+}
+
+/// Appends text to the current block, starting a new paragraph if there is none.
+fn push_text(
+    current: &mut Option<Block>,
+    depth: usize,
+    marker: &mut Option<Marker>,
+    style: SpanStyle,
+    text: &str,
+) {
+    let block =
+        current.get_or_insert_with(|| Block::new(BlockKind::Paragraph, depth, marker.take()));
+
+    match block.spans.last_mut() {
+        Some((last, existing)) if *last == style => existing.push_str(text),
+        _ => block.spans.push((style, text.to_string())),
+    }
+}
+
+/// The markdown file as currently loaded by the renderer.
+struct LoadedDocument {
+    blocks: Vec<Block>,
+
+    /// Changes whenever the file is loaded again.
+    revision: u64,
+}
+
+/// Everything the layout depends on, the blocks are laid out again when any of this changes.
+struct LayoutKey {
+    revision: u64,
+    max_width: f32,
+    line_spacing: f32,
+    block_spacing: f32,
+    indent: f32,
+    heading_scale: f32,
+    code_family: String,
+    strike_done: bool,
+    font: u64,
+    paints: [Paint; 5],
+}
+
+impl LayoutKey {
+    fn new(config: &NotesModuleConfig, revision: u64) -> Self {
+        Self {
+            revision,
+            max_width: config.max_width,
+            line_spacing: config.line_spacing,
+            block_spacing: config.block_spacing,
+            indent: config.indent,
+            heading_scale: config.heading_scale,
+            code_family: config.code_family.clone(),
+            strike_done: config.strike_done,
+            font: config.font.cache_key(),
+            paints: Self::paints(config).map(Clone::clone),
+        }
+    }
+
+    fn paints(config: &NotesModuleConfig) -> [&Paint; 5] {
+        [
+            config.paint.get_paint(),
+            config.heading_paint.get_paint(),
+            config.code_paint.get_paint(),
+            config.code_background.get_paint(),
+            config.done_paint.get_paint(),
+        ]
+    }
+
+    fn matches(&self, config: &NotesModuleConfig, revision: u64) -> bool {
+        self.revision == revision
+            && self.max_width == config.max_width
+            && self.line_spacing == config.line_spacing
+            && self.block_spacing == config.block_spacing
+            && self.indent == config.indent
+            && self.heading_scale == config.heading_scale
+            && self.code_family == config.code_family
+            && self.strike_done == config.strike_done
+            && self.font == config.font.cache_key()
+            && self.paints.iter().eq(Self::paints(config))
+    }
+}
+
+/// A block which has been laid out, positioned relative to the top left corner of the notes.
+struct LaidOutBlock {
+    kind: BlockKind,
+    marker: Option<Marker>,
+
+    /// Paragraphs are missing for rules.
+    paragraph: Option<Paragraph>,
+    x: scalar,
+    y: scalar,
+    height: scalar,
+}
+
+struct LaidOutNotes {
+    key: LayoutKey,
+    blocks: Vec<LaidOutBlock>,
+    width: scalar,
+    height: scalar,
+}
+
+pub struct NotesModuleRenderer {
+    file: WatchedFile,
+    document: Option<LoadedDocument>,
+    laid_out: Option<SendCell<LaidOutNotes>>,
+    revision: u64,
+}
+
+impl NotesModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            file: WatchedFile::new(),
+            document: None,
+            laid_out: None,
+            revision: 0,
+        }
+    }
+
+    /// Loads the file again if the path changed or the file has been modified.
+    fn update_document(&mut self, config: &NotesModuleConfig) {
+        if !self.file.update(&config.path) {
+            return;
+        }
+
+        let (blocks, error) = if config.path.is_empty() {
+            (Vec::new(), Some(String::from("No file selected")))
+        } else {
+            match std::fs::read_to_string(&config.path) {
+                Ok(source) => (parse_document(&source), None),
+                Err(err) => (
+                    Vec::new(),
+                    Some(format!("Failed to read {}: {}", config.path, err)),
+                ),
+            }
+        };
+
+        *config.error.lock().unwrap() = error;
+
+        self.revision += 1;
+        self.document = Some(LoadedDocument {
+            blocks,
+            revision: self.revision,
+        });
+    }
+
+    /// Creates the base text style of a block.
+    fn block_style(config: &NotesModuleConfig, block: &Block) -> TextStyle {
+        let mut style = config.font.text_style();
+        style
+            .set_height(config.line_spacing)
+            .set_height_override(true);
+
+        let paint = match block.kind {
+            _ if block.is_done() => &config.done_paint,
+            BlockKind::Heading(_) => &config.heading_paint,
+            _ => &config.paint,
+        };
+        style.set_foreground_color(paint.get_paint().clone());
+
+        match block.kind {
+            BlockKind::Heading(level) => {
+                let relative = HEADING_SIZES[(level as usize).clamp(1, 6) - 1];
+                let size = config.font_size() * (1.0 + (relative - 1.0) * config.heading_scale);
+
+                let font_style = style.font_style();
+                style.set_font_size(size).set_font_style(FontStyle::new(
+                    Weight::BOLD,
+                    font_style.width(),
+                    font_style.slant(),
+                ));
+            }
+            BlockKind::Code => {
+                style
+                    .set_font_families(&[config.code_family.as_str()])
+                    .set_foreground_color(config.code_paint.get_paint().clone());
+            }
+            BlockKind::Paragraph | BlockKind::Rule => {}
+        }
+
+        if block.is_done() && config.strike_done {
+            style.set_decoration_type(TextDecoration::LINE_THROUGH);
+        }
+
+        style
+    }
+
+    fn layout(config: &NotesModuleConfig, document: &LoadedDocument) -> LaidOutNotes {
+        let collection = config.font.font_collection();
+        let font_size = config.font_size();
+
+        let mut blocks = Vec::with_capacity(document.blocks.len());
+        let mut y = 0.0;
+        let mut width: scalar = 0.0;
+
+        for (i, block) in document.blocks.iter().enumerate() {
+            if i > 0 {
+                y += config.block_spacing;
+            }
+
+            let x = config.indent * block.depth as scalar;
+
+            let paragraph = if block.kind == BlockKind::Rule {
+                None
+            } else {
+                let base = Self::block_style(config, block);
+
+                let mut paragraph_style = ParagraphStyle::new();
+                paragraph_style.set_text_style(&base);
+
+                let mut builder = ParagraphBuilder::new(&paragraph_style, collection.clone());
+                for (style, text) in &block.spans {
+                    // Code blocks have a background of their own
+                    let style = SpanStyle {
+                        code: style.code && block.kind != BlockKind::Code,
+                        ..*style
+                    };
+
+                    builder.push_style(&style.apply(&base, config));
+                    builder.add_text(text);
+                    builder.pop();
+                }
+
+                let mut paragraph = builder.build();
+
+                let available = if config.max_width > 0.0 {
+                    (config.max_width - x).max(font_size)
+                } else {
+                    // Lay out without any limit first to find the width of the longest line
+                    paragraph.layout(scalar::INFINITY);
+                    paragraph.max_intrinsic_width().ceil()
+                };
+
+                paragraph.layout(available);
+                width = width.max(x + available);
+
+                Some(paragraph)
+            };
+
+            let height = paragraph
+                .as_ref()
+                .map_or(font_size / 2.0, Paragraph::height);
+
+            blocks.push(LaidOutBlock {
+                kind: block.kind,
+                marker: block.marker,
+                paragraph,
+                x,
+                y,
+                height,
+            });
+
+            y += height;
+        }
+
+        LaidOutNotes {
+            key: LayoutKey::new(config, document.revision),
+            blocks,
+            width,
+            height: y,
+        }
+    }
+
+    /// Draws the bullet, number or checkbox in front of a list item.
+    fn draw_marker(
+        config: &NotesModuleConfig,
+        data: &mut SceneData,
+        block: &LaidOutBlock,
+        x: scalar,
+        y: scalar,
+    ) {
+        let (marker, paragraph) = match (block.marker, &block.paragraph) {
+            (Some(marker), Some(paragraph)) => (marker, paragraph),
+            _ => return,
+        };
+
+        let font_size = config.font_size();
+        let baseline = y + paragraph.alphabetic_baseline();
+        let center_x = x - config.indent / 2.0;
+        let center_y = baseline - font_size * 0.35;
+
+        let mut paint = config.paint.get_paint().clone();
+        paint.set_anti_alias(true);
+
+        match marker {
+            Marker::Bullet => {
+                data.canvas()
+                    .draw_circle((center_x, center_y), font_size * 0.15, &paint);
+            }
+            Marker::Number(number) => {
+                let text = format!("{}.", number);
+                let (text_width, _) = config.font.measure_str(&text, None);
+
+                let right = x - font_size * 0.3;
+                config
+                    .font
+                    .draw_str(data.canvas(), &text, (right - text_width, baseline), &paint);
+            }
+            Marker::Checkbox(checked) => {
+                let size = font_size * 0.7;
+                let stroke = (font_size / 12.0).max(1.0);
+                let rect =
+                    Rect::from_xywh(center_x - size / 2.0, center_y - size / 2.0, size, size);
+
+                let mut outline = config.checkbox_paint.get_paint().clone();
+                outline
+                    .set_anti_alias(true)
+                    .set_style(Style::Stroke)
+                    .set_stroke_width(stroke);
+                data.canvas()
+                    .draw_round_rect(rect, stroke * 1.5, stroke * 1.5, &outline);
+
+                if checked {
+                    let mut check = SkPath::new();
+                    check
+                        .move_to((rect.left + size * 0.2, rect.top + size * 0.5))
+                        .line_to((rect.left + size * 0.42, rect.top + size * 0.72))
+                        .line_to((rect.left + size * 0.8, rect.top + size * 0.28));
+
+                    data.canvas().draw_path(&check, &outline);
+                }
+            }
+        }
+    }
+}
+
+impl ModuleRenderer for NotesModuleRenderer {
+    type Config = NotesModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        self.update_document(config);
+
+        let document = self.document.as_ref().unwrap();
+        let outdated = self.laid_out.as_ref().map_or(true, |laid_out| {
+            !laid_out.key.matches(config, document.revision)
+        });
+
+        if outdated {
+            // Each block owns its paragraph
+            self.laid_out = Some(unsafe { SendCell::new(Self::layout(config, document)) });
+        }
+
+        let laid_out = self.laid_out.as_ref().unwrap();
+        if laid_out.blocks.is_empty() {
+            return;
+        }
+
+        let (x, y) = match config.position.compute_position(
+            data,
+            laid_out.width as i32,
+            laid_out.height as i32,
+        ) {
+            None => return,
+            Some((x, y)) => (x as scalar, y as scalar),
+        };
+
+        for block in &laid_out.blocks {
+            let block_x = x + block.x;
+            let block_y = y + block.y;
+
+            match &block.paragraph {
+                None => {
+                    let mut paint = config.paint.get_paint().clone();
+                    paint.set_stroke_width(1.0);
+
+                    let rule_y = block_y + block.height / 2.0;
+                    data.canvas().draw_line(
+                        (block_x, rule_y),
+                        (x + laid_out.width, rule_y),
+                        &paint,
+                    );
+                }
+                Some(paragraph) => {
+                    if block.kind == BlockKind::Code {
+                        let padding = config.font_size() / 4.0;
+                        let background =
+                            Rect::from_xywh(block_x, block_y, paragraph.max_width(), block.height)
+                                .with_outset((padding, padding));
+
+                        data.canvas()
+                            .draw_rect(background, config.code_background.get_paint());
+                    }
+
+                    Self::draw_marker(config, data, block, block_x, block_y);
+                    paragraph.paint(data.canvas(), (block_x, block_y));
+                }
+            }
+        }
+    }
+}
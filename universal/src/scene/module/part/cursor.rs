@@ -0,0 +1,110 @@
+use imgui::{Drag, SliderFlags, Ui};
+use serde::{Deserialize, Serialize};
+use skia_safe::{Point, Vector};
+
+use crate::scene::module::ModuleConfig;
+use crate::ui::context::Context;
+
+/// How objects react to the pointer.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CursorInteraction {
+    None,
+    Repel,
+    Swirl,
+}
+
+impl CursorInteraction {
+    const VALUES: [Self; 3] = [Self::None, Self::Repel, Self::Swirl];
+}
+
+impl Default for CursorInteraction {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// Describes how objects close to the pointer are moved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorSetting {
+    interaction: CursorInteraction,
+
+    /// Distance in pixels up to which the pointer has an effect.
+    radius: f32,
+
+    /// Speed in pixels per second objects right at the pointer are moved with.
+    strength: f32,
+}
+
+impl CursorSetting {
+    /// Calculates the velocity in pixels per second the pointer adds to an object.
+    ///
+    /// The effect fades out linearly towards the edge of the radius.
+    pub fn velocity(&self, pointer: Option<Point>, position: Point) -> Vector {
+        let pointer = match pointer {
+            Some(v) if self.interaction != CursorInteraction::None => v,
+            _ => return Vector::default(),
+        };
+
+        let (dx, dy) = (position.x - pointer.x, position.y - pointer.y);
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        if distance >= self.radius || distance <= f32::EPSILON {
+            return Vector::default();
+        }
+
+        let speed = self.strength * (1.0 - distance / self.radius);
+        let (dx, dy) = (dx / distance * speed, dy / distance * speed);
+
+        match self.interaction {
+            CursorInteraction::None => Vector::default(),
+            CursorInteraction::Repel => Vector::new(dx, dy),
+            CursorInteraction::Swirl => Vector::new(-dy, dx),
+        }
+    }
+}
+
+impl Default for CursorSetting {
+    fn default() -> Self {
+        Self {
+            interaction: CursorInteraction::default(),
+            radius: 150.0,
+            strength: 300.0,
+        }
+    }
+}
+
+impl ModuleConfig for CursorSetting {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = CursorInteraction::VALUES
+            .iter()
+            .position(|v| *v == self.interaction)
+            .unwrap();
+
+        ui.combo(
+            "Cursor interaction",
+            &mut current,
+            &CursorInteraction::VALUES,
+            |v| {
+                match v {
+                    CursorInteraction::None => "None",
+                    CursorInteraction::Repel => "Repel",
+                    CursorInteraction::Swirl => "Swirl",
+                }
+                .into()
+            },
+        );
+
+        self.interaction = CursorInteraction::VALUES[current].clone();
+
+        if self.interaction != CursorInteraction::None {
+            Drag::new("Cursor radius")
+                .range(1.0, 10000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.radius);
+
+            Drag::new("Cursor strength (pixels per second)")
+                .range(-100000.0, 100000.0)
+                .build(ui, &mut self.strength);
+        }
+    }
+}
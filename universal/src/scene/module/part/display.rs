@@ -1,6 +1,8 @@
 use imgui::{Selectable, Ui};
 use serde::{Deserialize, Serialize};
+use skia_safe::IRect;
 
+use crate::rendering::display::Display;
 use crate::scene::module::ModuleConfig;
 use crate::scene::SceneData;
 use crate::ui::context::Context;
@@ -63,22 +65,47 @@ impl DisplaySelection {
         }
     }
 
-    pub fn perform_calculation_with<F>(&self, data: &SceneData, calc: F) -> Option<(i32, i32)>
-    where
-        F: FnOnce(i32, i32) -> (i32, i32),
-    {
-        let (width, height, start_x, start_y) = match self {
-            DisplaySelection::None => (data.width(), data.height(), 0, 0),
+    /// Resolves the selected area into its width, height and upper left corner.
+    fn resolve(&self, data: &SceneData) -> Option<(i32, i32, i32, i32)> {
+        match self {
+            DisplaySelection::None => Some((data.width(), data.height(), 0, 0)),
             DisplaySelection::Primary => {
                 let display = data.primary_display();
 
-                (display.width(), display.height(), display.x(), display.y())
+                Some((display.width(), display.height(), display.x(), display.y()))
             }
-            DisplaySelection::Identified { id, .. } => match data.lookup_display(id) {
-                None => return None,
-                Some(d) => (d.width(), d.height(), d.x(), d.y()),
-            },
-        };
+            DisplaySelection::Identified { id, .. } => data
+                .lookup_display(id)
+                .map(|d| (d.width(), d.height(), d.x(), d.y())),
+        }
+    }
+
+    /// Looks up the selected display, [`None`] if the entire scene is selected.
+    pub fn display<'a>(&self, data: &SceneData<'a>) -> Option<&'a Display> {
+        match self {
+            DisplaySelection::None => None,
+            DisplaySelection::Primary => Some(data.primary_display()),
+            DisplaySelection::Identified { id, .. } => data.lookup_display(id),
+        }
+    }
+
+    /// Retrieves the selected area in scene coordinates.
+    pub fn area(&self, data: &SceneData) -> Option<IRect> {
+        self.resolve(data)
+            .map(|(width, height, x, y)| IRect::from_xywh(x, y, width, height))
+    }
+
+    /// Retrieves the width and height of the selected area.
+    pub fn available_size(&self, data: &SceneData) -> Option<(i32, i32)> {
+        self.resolve(data)
+            .map(|(width, height, _, _)| (width, height))
+    }
+
+    pub fn perform_calculation_with<F>(&self, data: &SceneData, calc: F) -> Option<(i32, i32)>
+    where
+        F: FnOnce(i32, i32) -> (i32, i32),
+    {
+        let (width, height, start_x, start_y) = self.resolve(data)?;
 
         let (x, y) = calc(width, height);
 
@@ -1,47 +1,621 @@
-use imgui::Ui;
+use std::collections::HashMap;
+use std::lazy::SyncLazy;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use imgui::{Drag, InputText, Selectable, SliderFlags, Ui};
+use native_dialog::FileDialog;
 use serde::{Deserialize, Serialize};
-use skia_safe::Font;
+use skia_safe::font_style::{Slant, Weight, Width};
+use skia_safe::textlayout::{FontCollection, TextStyle, TypefaceFontProvider};
+use skia_safe::{
+    scalar, Canvas, Data, Font, FontHinting, FontMgr, FontStyle, GlyphId, Paint, Point, Rect,
+    TextBlob, TextBlobBuilder, Typeface, Unichar,
+};
 
 use crate::rendering::fonts;
 use crate::scene::module::ModuleConfig;
 use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+
+/// Family name the typeface is registered under when laying out paragraphs.
+const PARAGRAPH_FAMILY_ALIAS: &str = "Snowland Font";
+
+/// Source of the revisions handed out to rebuilt fonts.
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Sorted names of all font families the system font manager knows about.
+static SYSTEM_FAMILIES: SyncLazy<Vec<String>> = SyncLazy::new(|| {
+    let manager = FontMgr::default();
+
+    let mut families = (0..manager.count_families())
+        .map(|i| manager.family_name(i))
+        .collect::<Vec<_>>();
+
+    families.sort_by_key(|family| family.to_lowercase());
+    families.dedup();
+
+    families
+});
+
+/// Where the typeface of a font comes from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FontSource {
+    Embedded,
+    System { family: String },
+    File { path: String },
+}
+
+impl FontSource {
+    pub const fn ordinal(&self) -> usize {
+        match self {
+            FontSource::Embedded => 0,
+            FontSource::System { .. } => 1,
+            FontSource::File { .. } => 2,
+        }
+    }
+
+    pub fn from_ordinal(ordinal: usize) -> Self {
+        match ordinal {
+            0 => FontSource::Embedded,
+            1 => FontSource::System {
+                family: String::new(),
+            },
+            2 => FontSource::File {
+                path: String::new(),
+            },
+            _ => panic!("Invalid font source ordinal"),
+        }
+    }
+
+    pub const fn type_names() -> [&'static str; 3] {
+        ["Embedded", "System", "File"]
+    }
+}
+
+impl Default for FontSource {
+    fn default() -> Self {
+        Self::Embedded
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FontSlant {
+    Upright,
+    Italic,
+    Oblique,
+}
+
+impl FontSlant {
+    const VALUES: [Self; 3] = [Self::Upright, Self::Italic, Self::Oblique];
+
+    fn to_skia(self) -> Slant {
+        match self {
+            FontSlant::Upright => Slant::Upright,
+            FontSlant::Italic => Slant::Italic,
+            FontSlant::Oblique => Slant::Oblique,
+        }
+    }
+}
+
+impl Default for FontSlant {
+    fn default() -> Self {
+        Self::Upright
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FontHintingSetting {
+    None,
+    Slight,
+    Normal,
+    Full,
+}
+
+impl FontHintingSetting {
+    const VALUES: [Self; 4] = [Self::None, Self::Slight, Self::Normal, Self::Full];
+
+    fn to_skia(self) -> FontHinting {
+        match self {
+            FontHintingSetting::None => FontHinting::None,
+            FontHintingSetting::Slight => FontHinting::Slight,
+            FontHintingSetting::Normal => FontHinting::Normal,
+            FontHintingSetting::Full => FontHinting::Full,
+        }
+    }
+}
+
+impl Default for FontHintingSetting {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// The persisted part of a [`FontSetting`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontDescription {
+    source: FontSource,
+
+    /// CSS like weight, 400 is regular and 700 is bold.
+    weight: i32,
+
+    /// Width from 1 (ultra condensed) to 9 (ultra expanded), 5 is normal.
+    width: i32,
+
+    slant: FontSlant,
+    size: f32,
+
+    /// Additional space in pixels between two characters.
+    letter_spacing: f32,
+
+    hinting: FontHintingSetting,
+
+    /// System font families tried in order for characters the font has no glyph for.
+    fallback: Vec<String>,
+
+    /// Whether the system font manager is asked for characters no fallback covers either.
+    system_fallback: bool,
+}
+
+impl FontDescription {
+    fn style(&self) -> FontStyle {
+        FontStyle::new(
+            Weight::from(self.weight),
+            Width::from(self.width),
+            self.slant.to_skia(),
+        )
+    }
+
+    /// Looks up the typeface, errors are meant to be displayed to the user.
+    ///
+    /// Font files are only read again once their path changes, `loaded_file` holds the
+    /// typeface read last.
+    fn load_typeface(
+        &self,
+        loaded_file: &mut Option<(String, Typeface)>,
+    ) -> Result<Typeface, String> {
+        match &self.source {
+            FontSource::Embedded => Ok(fonts::load_embedded_font(fonts::Font::NotoSansMono)),
+            FontSource::System { family } if family.is_empty() => {
+                Err("No font family selected".into())
+            }
+            FontSource::System { family } => FontMgr::default()
+                .match_family_style(family, self.style())
+                .ok_or_else(|| format!("Font family {} not found", family)),
+            FontSource::File { path } if path.is_empty() => Err("No font file selected".into()),
+            FontSource::File { path } => {
+                if let Some((loaded, typeface)) = loaded_file.as_ref() {
+                    if loaded == path {
+                        return Ok(typeface.clone());
+                    }
+                }
+
+                let bytes = std::fs::read(path)
+                    .map_err(|err| format!("Failed to read {}: {}", path, err))?;
+
+                let typeface = Typeface::from_data(Data::new_copy(&bytes), None)
+                    .ok_or_else(|| format!("{} is not a supported font file", path))?;
+
+                *loaded_file = Some((path.clone(), typeface.clone()));
+                Ok(typeface)
+            }
+        }
+    }
+
+    /// Looks up the configured fallback fonts, missing families are reported as errors.
+    fn load_fallback(&self) -> (Vec<Font>, Vec<String>) {
+        let manager = FontMgr::default();
+        let mut fonts = Vec::with_capacity(self.fallback.len());
+        let mut errors = Vec::new();
+
+        for family in self.fallback.iter().filter(|family| !family.is_empty()) {
+            match manager.match_family_style(family, self.style()) {
+                Some(typeface) => fonts.push(self.make_font(typeface)),
+                None => errors.push(format!("Fallback font family {} not found", family)),
+            }
+        }
+
+        (fonts, errors)
+    }
+
+    fn make_font(&self, typeface: Typeface) -> Font {
+        let mut font = Font::from_typeface(typeface, Some(self.size));
+        font.set_hinting(self.hinting.to_skia());
+
+        font
+    }
+}
+
+impl Default for FontDescription {
+    fn default() -> Self {
+        Self {
+            source: FontSource::default(),
+            weight: 400,
+            width: 5,
+            slant: FontSlant::default(),
+            size: 32.0,
+            letter_spacing: 0.0,
+            hinting: FontHintingSetting::default(),
+            fallback: Vec::new(),
+            system_fallback: true,
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "FontDescription", into = "FontDescription")]
 pub struct FontSetting {
-    #[serde(skip, default = "make_default_font")]
+    description: FontDescription,
     inner: Font,
+
+    /// Fonts used for characters the main font doesn't cover, in order.
+    fallback: Vec<Font>,
+
+    /// Fonts the system font manager picked per character, [`None`] if no font covers it.
+    ///
+    /// Asking the font manager is slow, so this is shared between clones of the setting.
+    system_fallback: Arc<Mutex<HashMap<char, Option<Font>>>>,
+
+    /// Changes whenever the font is rebuilt, see [`FontSetting::cache_key`].
+    revision: u64,
+
+    /// Why typefaces couldn't be loaded, the embedded font is used instead of the main one.
+    errors: Vec<String>,
+
+    /// The typeface of the font file read last together with its path.
+    ///
+    /// Rebuilding happens on every change of the options, reading the file each time would
+    /// be slow.
+    loaded_file: Option<(String, Typeface)>,
+
+    /// Text the family list in the user interface is filtered by.
+    family_filter: String,
 }
 
 impl Clone for FontSetting {
     fn clone(&self) -> Self {
-        let font = Font::from_typeface_with_params(
-            self.inner.typeface_or_default(),
-            self.inner.size(),
-            self.inner.scale_x(),
-            self.inner.skew_x(),
-        );
+        Self {
+            description: self.description.clone(),
+            inner: self.description.make_font(self.inner.typeface_or_default()),
+            fallback: self
+                .fallback
+                .iter()
+                .map(|font| self.description.make_font(font.typeface_or_default()))
+                .collect(),
+            system_fallback: self.system_fallback.clone(),
+            revision: self.revision,
+            errors: self.errors.clone(),
+            loaded_file: self.loaded_file.clone(),
+            family_filter: String::new(),
+        }
+    }
+}
+
+impl From<FontDescription> for FontSetting {
+    fn from(description: FontDescription) -> Self {
+        let mut setting = Self {
+            inner: description.make_font(fonts::load_embedded_font(fonts::Font::NotoSansMono)),
+            description,
+            fallback: Vec::new(),
+            system_fallback: Arc::new(Mutex::new(HashMap::new())),
+            revision: 0,
+            errors: Vec::new(),
+            loaded_file: None,
+            family_filter: String::new(),
+        };
 
-        Self { inner: font }
+        setting.rebuild();
+        setting
     }
 }
 
-fn make_default_font() -> Font {
-    Font::from_typeface(
-        fonts::load_embedded_font(fonts::Font::NotoSansMono),
-        Some(32.0),
-    )
+impl From<FontSetting> for FontDescription {
+    fn from(setting: FontSetting) -> Self {
+        setting.description
+    }
 }
 
 impl FontSetting {
     pub fn new() -> Self {
-        Self {
-            inner: make_default_font(),
-        }
+        Self::from(FontDescription::default())
     }
 
     pub fn get_font(&self) -> &Font {
         &self.inner
     }
+
+    /// Identifies the current font, changes whenever any of the font options do.
+    ///
+    /// Renderers caching laid out text can use this to find out when to lay out again.
+    pub fn cache_key(&self) -> u64 {
+        self.revision
+    }
+
+    /// Measures text like [`Font::measure_str`], but includes the letter spacing and
+    /// fallback fonts.
+    pub fn measure_str(&self, text: &str, paint: Option<&Paint>) -> (scalar, Rect) {
+        if self.is_simple(text) {
+            return self.inner.measure_str(text, paint);
+        }
+
+        let mut width = 0.0;
+        let mut bounds = Rect::new_empty();
+
+        self.layout(text, |font, glyphs, positions| {
+            let mut glyph_bounds = vec![Rect::new_empty(); glyphs.len()];
+            font.get_bounds(glyphs, &mut glyph_bounds, paint);
+
+            for (glyph, x) in glyph_bounds.iter().zip(positions) {
+                bounds.join(glyph.with_offset((*x, 0.0)));
+            }
+
+            if let (Some(last), Some(x)) = (glyphs.last(), positions.last()) {
+                width = x + advance(font, *last);
+            }
+        });
+
+        (width, bounds)
+    }
+
+    /// Shapes text into a blob, applying the letter spacing and fallback fonts.
+    pub fn text_blob(&self, text: &str) -> Option<TextBlob> {
+        if self.is_simple(text) {
+            return TextBlob::from_str(text, &self.inner);
+        }
+
+        let mut builder = TextBlobBuilder::new();
+
+        self.layout(text, |font, glyphs, positions| {
+            let (run_glyphs, run_positions) =
+                builder.alloc_run_pos_h(font, glyphs.len(), 0.0, None);
+
+            run_glyphs.copy_from_slice(glyphs);
+            run_positions.copy_from_slice(positions);
+        });
+
+        builder.make()
+    }
+
+    /// Draws text like [`Canvas::draw_str`], but includes the letter spacing and fallback
+    /// fonts.
+    pub fn draw_str(
+        &self,
+        canvas: &mut Canvas,
+        text: &str,
+        origin: impl Into<Point>,
+        paint: &Paint,
+    ) {
+        if self.is_simple(text) {
+            canvas.draw_str(text, origin, &self.inner, paint);
+        } else if let Some(blob) = self.text_blob(text) {
+            canvas.draw_text_blob(&blob, origin, paint);
+        }
+    }
+
+    /// Creates the font collection paragraphs using this font are laid out with.
+    pub fn font_collection(&self) -> FontCollection {
+        let mut collection = FontCollection::new();
+
+        // System fonts are looked up by their family, so their bold and italic variants
+        // are found as well
+        if !matches!(&self.description.source, FontSource::System { family } if !family.is_empty())
+        {
+            let mut provider = TypefaceFontProvider::new();
+            provider.register_typeface(
+                self.inner.typeface_or_default(),
+                Some(PARAGRAPH_FAMILY_ALIAS),
+            );
+
+            collection.set_asset_font_manager(Some(provider.into()));
+        }
+
+        collection.set_default_font_manager(FontMgr::default(), None);
+
+        if !self.description.system_fallback {
+            collection.disable_font_fallback();
+        }
+
+        collection
+    }
+
+    /// Creates the text style paragraphs using this font start out with.
+    pub fn text_style(&self) -> TextStyle {
+        let main = match &self.description.source {
+            FontSource::System { family } if !family.is_empty() => family.as_str(),
+            _ => PARAGRAPH_FAMILY_ALIAS,
+        };
+
+        let families = std::iter::once(main)
+            .chain(
+                self.description
+                    .fallback
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|family| !family.is_empty()),
+            )
+            .collect::<Vec<_>>();
+
+        let mut style = TextStyle::new();
+        style
+            .set_font_families(&families)
+            .set_font_size(self.description.size)
+            .set_font_style(self.description.style())
+            .set_letter_spacing(self.description.letter_spacing);
+
+        style
+    }
+
+    /// Determines whether the text can be drawn by the main font without any adjustments.
+    fn is_simple(&self, text: &str) -> bool {
+        self.description.letter_spacing == 0.0
+            && text
+                .chars()
+                .all(|c| self.inner.unichar_to_glyph(c as Unichar) != 0)
+    }
+
+    /// Determines whether the main font or one of the fallback fonts covers a character.
+    fn covering_font(&self, c: char) -> Option<&Font> {
+        std::iter::once(&self.inner)
+            .chain(&self.fallback)
+            .find(|font| font.unichar_to_glyph(c as Unichar) != 0)
+    }
+
+    /// Asks the system font manager for the characters no configured font covers.
+    ///
+    /// The answers are cached, including the characters no font covers at all.
+    fn ask_system_fallback(&self, text: &str, cache: &mut HashMap<char, Option<Font>>) {
+        if !self.description.system_fallback {
+            return;
+        }
+
+        // Control characters are never covered, there is no point in asking for them
+        for c in text.chars().filter(|c| !c.is_control()) {
+            if cache.contains_key(&c) || self.covering_font(c).is_some() {
+                continue;
+            }
+
+            let font = FontMgr::default()
+                .match_family_style_character("", self.description.style(), &[], c as Unichar)
+                .map(|typeface| self.description.make_font(typeface));
+
+            cache.insert(c, font);
+        }
+    }
+
+    /// Selects the font to draw a character with.
+    fn font_for<'a>(
+        &'a self,
+        c: char,
+        system_fallback: &'a HashMap<char, Option<Font>>,
+    ) -> &'a Font {
+        self.covering_font(c)
+            .or_else(|| system_fallback.get(&c)?.as_ref())
+            .unwrap_or(&self.inner)
+    }
+
+    /// Splits text into runs sharing a font and positions the glyphs horizontally.
+    ///
+    /// The callback receives the font, glyphs and x positions of every run in order.
+    fn layout<F>(&self, text: &str, mut run: F)
+    where
+        F: FnMut(&Font, &[GlyphId], &[scalar]),
+    {
+        let spacing = self.description.letter_spacing;
+
+        let mut system_fallback = self.system_fallback.lock().unwrap();
+        self.ask_system_fallback(text, &mut system_fallback);
+        let system_fallback = &*system_fallback;
+
+        let mut current: Option<&Font> = None;
+        let mut glyphs = Vec::new();
+        let mut positions = Vec::new();
+        let mut x = 0.0;
+
+        for c in text.chars() {
+            let font = self.font_for(c, system_fallback);
+
+            // Different characters may have been resolved to the same system typeface
+            let same_font = current.map_or(false, |current| {
+                std::ptr::eq(current, font)
+                    || current.typeface_or_default().unique_id()
+                        == font.typeface_or_default().unique_id()
+            });
+
+            if !same_font {
+                if let Some(current) = current {
+                    run(current, &glyphs, &positions);
+                }
+
+                glyphs.clear();
+                positions.clear();
+                current = Some(font);
+            }
+
+            let font = current.unwrap();
+            let glyph = font.unichar_to_glyph(c as Unichar);
+
+            glyphs.push(glyph);
+            positions.push(x);
+            x += advance(font, glyph) + spacing;
+        }
+
+        if let Some(current) = current {
+            run(current, &glyphs, &positions);
+        }
+    }
+
+    /// Loads the typefaces again and recreates the fonts from the description.
+    fn rebuild(&mut self) {
+        self.errors.clear();
+
+        let typeface = match self.description.load_typeface(&mut self.loaded_file) {
+            Ok(typeface) => typeface,
+            Err(err) => {
+                self.errors.push(err);
+                fonts::load_embedded_font(fonts::Font::NotoSansMono)
+            }
+        };
+
+        let (fallback, errors) = self.description.load_fallback();
+        self.errors.extend(errors);
+
+        self.inner = self.description.make_font(typeface);
+        self.fallback = fallback;
+        self.system_fallback = Arc::new(Mutex::new(HashMap::new()));
+        self.revision = NEXT_REVISION.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    fn represent_fallback(&mut self, ui: &Ui) {
+        ui.checkbox(
+            "Ask the system for missing characters",
+            &mut self.description.system_fallback,
+        );
+
+        let mut to_remove = None;
+
+        for (i, family) in self.description.fallback.iter_mut().enumerate() {
+            let _id = ui.push_id(i as i32);
+
+            family_combo(ui, "Fallback family", family, &mut self.family_filter);
+            ui.same_line();
+
+            if ui.small_button("Remove") {
+                to_remove = Some(i);
+            }
+        }
+
+        if let Some(i) = to_remove {
+            self.description.fallback.remove(i);
+        }
+
+        if ui.button("Add fallback font") {
+            self.description.fallback.push(String::new());
+        }
+    }
+
+    fn represent_file(&mut self, ui: &Ui) {
+        let path = match &mut self.description.source {
+            FontSource::File { path } => path,
+            _ => return,
+        };
+
+        InputText::new(ui, "Font file", path).build();
+        ui.same_line();
+
+        if ui.small_button("...") {
+            match FileDialog::new()
+                .add_filter("Font", &["ttf", "otf", "ttc"])
+                .show_open_single_file()
+            {
+                Ok(None) => {}
+                Ok(Some(p)) => *path = p.to_string_lossy().into(),
+                Err(err) => {
+                    log::error!("Failed to show a file dialog: {}", err)
+                }
+            };
+        }
+    }
 }
 
 impl AsRef<Font> for FontSetting {
@@ -57,5 +631,127 @@ impl Default for FontSetting {
 }
 
 impl ModuleConfig for FontSetting {
-    fn represent(&mut self, _ui: &Ui, _ctx: &Context<'_>) {}
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let before = self.description.clone();
+
+        let mut current_type_ordinal = self.description.source.ordinal();
+        if ui.combo_simple_string(
+            "Font source",
+            &mut current_type_ordinal,
+            FontSource::type_names().as_slice(),
+        ) {
+            self.description.source = FontSource::from_ordinal(current_type_ordinal);
+        }
+
+        if let FontSource::System { family } = &mut self.description.source {
+            family_combo(ui, "Family", family, &mut self.family_filter);
+        }
+
+        self.represent_file(ui);
+
+        if matches!(self.description.source, FontSource::System { .. }) {
+            Drag::new("Weight")
+                .range(100, 1000)
+                .speed(10.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.description.weight);
+
+            Drag::new("Width")
+                .range(1, 9)
+                .speed(0.05)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.description.width);
+
+            let mut current = FontSlant::VALUES
+                .iter()
+                .position(|v| *v == self.description.slant)
+                .unwrap();
+
+            ui.combo("Slant", &mut current, &FontSlant::VALUES, |v| {
+                match v {
+                    FontSlant::Upright => "Upright",
+                    FontSlant::Italic => "Italic",
+                    FontSlant::Oblique => "Oblique",
+                }
+                .into()
+            });
+
+            self.description.slant = FontSlant::VALUES[current];
+        }
+
+        Drag::new("Size")
+            .range(1.0, 1000.0)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut self.description.size);
+
+        Drag::new("Letter spacing")
+            .range(-100.0, 100.0)
+            .speed(0.1)
+            .build(ui, &mut self.description.letter_spacing);
+
+        let mut current = FontHintingSetting::VALUES
+            .iter()
+            .position(|v| *v == self.description.hinting)
+            .unwrap();
+
+        ui.combo("Hinting", &mut current, &FontHintingSetting::VALUES, |v| {
+            match v {
+                FontHintingSetting::None => "None",
+                FontHintingSetting::Slight => "Slight",
+                FontHintingSetting::Normal => "Normal",
+                FontHintingSetting::Full => "Full",
+            }
+            .into()
+        });
+
+        self.description.hinting = FontHintingSetting::VALUES[current];
+
+        self.represent_fallback(ui);
+
+        if self.description != before {
+            self.rebuild();
+        }
+
+        for err in &self.errors {
+            ui.text_colored(ERROR_COLOR, err);
+        }
+    }
+}
+
+/// Retrieves how far the pen moves after drawing a glyph.
+fn advance(font: &Font, glyph: GlyphId) -> scalar {
+    let mut width = [0.0];
+    font.get_widths(&[glyph], &mut width);
+
+    width[0]
+}
+
+/// Renders a combo box selecting one of the system font families.
+fn family_combo(ui: &Ui, label: &str, family: &mut String, filter: &mut String) {
+    let preview = if family.is_empty() {
+        "<none>"
+    } else {
+        family.as_str()
+    };
+
+    if let Some(_tok) = ui.begin_combo(label, preview) {
+        InputText::new(ui, "Filter", filter).build();
+
+        let filter = filter.to_lowercase();
+
+        for candidate in SYSTEM_FAMILIES
+            .iter()
+            .filter(|candidate| candidate.to_lowercase().contains(&filter))
+        {
+            let is_selected = candidate == family;
+
+            if Selectable::new(candidate).selected(is_selected).build(ui) {
+                *family = candidate.clone();
+            }
+
+            if is_selected {
+                ui.set_item_default_focus();
+            }
+        }
+    }
 }
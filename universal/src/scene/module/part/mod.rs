@@ -1,10 +1,16 @@
 //! Modules which contains partial modules, such as positioning helpers.
+pub use cursor::*;
 pub use display::*;
 pub use font::*;
 pub use paint::*;
 pub use position::*;
+pub use range::*;
+pub use scale::*;
 
+mod cursor;
 mod display;
 mod font;
 mod paint;
 mod position;
+mod range;
+mod scale;
@@ -87,6 +87,12 @@ impl Serialize for ColorSetting {
     }
 }
 
+impl From<Color4f> for ColorSetting {
+    fn from(color: Color4f) -> Self {
+        Self(color)
+    }
+}
+
 impl From<ColorSetting> for Color4f {
     fn from(setting: ColorSetting) -> Self {
         setting.0
@@ -97,6 +103,11 @@ impl From<ColorSetting> for Color4f {
 pub struct PaintSetting(Paint);
 
 impl PaintSetting {
+    /// Creates a paint with a solid color and otherwise default settings.
+    pub fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self(Paint::new(Color4f::new(r, g, b, a), None))
+    }
+
     pub fn get_paint(&self) -> &Paint {
         &self.0
     }
@@ -288,6 +299,12 @@ impl Serialize for PaintSetting {
     }
 }
 
+impl From<Paint> for PaintSetting {
+    fn from(paint: Paint) -> Self {
+        Self(paint)
+    }
+}
+
 impl From<PaintSetting> for Paint {
     fn from(setting: PaintSetting) -> Self {
         setting.0
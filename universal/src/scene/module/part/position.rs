@@ -110,6 +110,15 @@ pub struct ModulePosition {
 }
 
 impl ModulePosition {
+    pub fn display(&self) -> &DisplaySelection {
+        &self.display
+    }
+
+    /// Retrieves the size of the area the module is positioned in.
+    pub fn available_size(&self, data: &SceneData) -> Option<(i32, i32)> {
+        self.display.available_size(data)
+    }
+
     pub fn compute_position(
         &self,
         data: &SceneData,
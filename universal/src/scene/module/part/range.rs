@@ -0,0 +1,53 @@
+use imgui::{DragRange, SliderFlags, Ui};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A range of values a random value is picked from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RangeSetting {
+    min: f32,
+    max: f32,
+}
+
+impl RangeSetting {
+    pub const fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
+    }
+
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// Picks a random value out of the range.
+    pub fn sample(&self, random: &mut impl Rng) -> f32 {
+        if self.min < self.max {
+            random.gen_range(self.min..=self.max)
+        } else {
+            self.min
+        }
+    }
+
+    /// Renders a drag control for the range, clamped to the given limits.
+    ///
+    /// Ranges don't know their label and limits themselves, so unlike other settings they
+    /// are not represented through [`crate::scene::module::ModuleConfig`].
+    pub fn represent(&mut self, ui: &Ui, label: &str, limits: (f32, f32)) -> bool {
+        let (min, max) = limits;
+
+        DragRange::new(label)
+            .range(min, max)
+            .speed(((max - min) / 1000.0).clamp(0.001, 1.0))
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut self.min, &mut self.max)
+    }
+}
+
+impl Default for RangeSetting {
+    fn default() -> Self {
+        Self::new(0.0, 1.0)
+    }
+}
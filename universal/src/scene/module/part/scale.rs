@@ -0,0 +1,165 @@
+use imgui::{Drag, SliderFlags, Ui};
+use serde::{Deserialize, Serialize};
+
+use crate::scene::module::ModuleConfig;
+use crate::ui::context::Context;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ScaleMode {
+    /// Keeps the intrinsic size of the content.
+    Original,
+
+    /// Multiplies the intrinsic size by a factor.
+    Factor,
+
+    /// Scales the content uniformly until it fits into the available area.
+    Fit,
+
+    /// Scales the content uniformly until it covers the available area.
+    Fill,
+
+    /// Stretches the content to the available area, ignoring the aspect ratio.
+    Stretch,
+
+    /// Scales the content to a fixed size, ignoring the aspect ratio.
+    Fixed,
+}
+
+impl ScaleMode {
+    const VALUES: [Self; 6] = [
+        Self::Original,
+        Self::Factor,
+        Self::Fit,
+        Self::Fill,
+        Self::Stretch,
+        Self::Fixed,
+    ];
+}
+
+impl Default for ScaleMode {
+    fn default() -> Self {
+        Self::Original
+    }
+}
+
+impl ModuleConfig for ScaleMode {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Scaling", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Original => "Original",
+                Self::Factor => "Factor",
+                Self::Fit => "Fit",
+                Self::Fill => "Fill",
+                Self::Stretch => "Stretch",
+                Self::Fixed => "Fixed size",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current].clone();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScaleSetting {
+    mode: ScaleMode,
+    factor: f32,
+    width: i32,
+    height: i32,
+}
+
+impl ScaleSetting {
+    /// Computes the scaled size of content with the given intrinsic size.
+    ///
+    /// The available size is the size of the area the content is placed in, usually the
+    /// display the module is positioned on.
+    pub fn compute_size(
+        &self,
+        intrinsic_width: f32,
+        intrinsic_height: f32,
+        available_width: i32,
+        available_height: i32,
+    ) -> (f32, f32) {
+        let available_width = available_width as f32;
+        let available_height = available_height as f32;
+
+        match self.mode {
+            ScaleMode::Original => (intrinsic_width, intrinsic_height),
+            ScaleMode::Factor => (
+                intrinsic_width * self.factor,
+                intrinsic_height * self.factor,
+            ),
+            ScaleMode::Fit | ScaleMode::Fill => {
+                if intrinsic_width <= 0.0 || intrinsic_height <= 0.0 {
+                    return (0.0, 0.0);
+                }
+
+                let horizontal = available_width / intrinsic_width;
+                let vertical = available_height / intrinsic_height;
+
+                let factor = if self.mode == ScaleMode::Fit {
+                    f32::min(horizontal, vertical)
+                } else {
+                    f32::max(horizontal, vertical)
+                };
+
+                (intrinsic_width * factor, intrinsic_height * factor)
+            }
+            ScaleMode::Stretch => (available_width, available_height),
+            ScaleMode::Fixed => (self.width as f32, self.height as f32),
+        }
+    }
+}
+
+impl Default for ScaleSetting {
+    fn default() -> Self {
+        Self {
+            mode: ScaleMode::default(),
+            factor: 1.0,
+            width: 256,
+            height: 256,
+        }
+    }
+}
+
+impl ModuleConfig for ScaleSetting {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if let Some(_tab) = ui.begin_table("Scale", 2) {
+            ui.table_next_row();
+            ui.table_next_column();
+
+            self.mode.represent(ui, ctx);
+
+            match self.mode {
+                ScaleMode::Factor => {
+                    ui.table_next_column();
+
+                    Drag::new("Factor")
+                        .range(0.01, 100.0)
+                        .speed(0.01)
+                        .flags(SliderFlags::ALWAYS_CLAMP)
+                        .build(ui, &mut self.factor);
+                }
+                ScaleMode::Fixed => {
+                    ui.table_next_row();
+                    ui.table_next_column();
+
+                    Drag::new("Width")
+                        .range(1, i32::MAX)
+                        .flags(SliderFlags::ALWAYS_CLAMP)
+                        .build(ui, &mut self.width);
+
+                    ui.table_next_column();
+
+                    Drag::new("Height")
+                        .range(1, i32::MAX)
+                        .flags(SliderFlags::ALWAYS_CLAMP)
+                        .build(ui, &mut self.height);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,809 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use rand::rngs::ThreadRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use skia_safe::{scalar, Color4f, Font, Image, Paint, Path, Point, Rect, TextBlob};
+
+use crate::rendering::fonts;
+use crate::scene::module::part::{ColorSetting, CursorSetting, DisplaySelection, RangeSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::util::load_image;
+
+/// Size glyph particles are laid out at before being scaled to the particle size.
+const GLYPH_SIZE: scalar = 64.0;
+
+/// Upper bound for the time step, avoids particles jumping after a stall.
+const MAX_TIME_STEP: f32 = 0.1;
+
+/// Particles this far outside of the scene are removed.
+const OUTSIDE_MARGIN: f32 = 100.0;
+
+pub(super) struct ParticleModule;
+
+impl Module for ParticleModule {
+    type Config = ParticleModuleConfig;
+    type Renderer = ParticleModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        ParticleModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Particles".into()
+    }
+}
+
+/// The area particles are spawned in, relative to the upper left corner of the display.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EmitterShape {
+    Point {
+        x: f32,
+        y: f32,
+    },
+    Line {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+    },
+    Rectangle {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    DisplayEdges {
+        top: bool,
+        bottom: bool,
+        left: bool,
+        right: bool,
+    },
+}
+
+impl EmitterShape {
+    pub const fn ordinal(&self) -> usize {
+        match self {
+            EmitterShape::Point { .. } => 0,
+            EmitterShape::Line { .. } => 1,
+            EmitterShape::Rectangle { .. } => 2,
+            EmitterShape::DisplayEdges { .. } => 3,
+        }
+    }
+
+    pub fn from_ordinal(ordinal: usize) -> Self {
+        match ordinal {
+            0 => EmitterShape::Point { x: 0.0, y: 0.0 },
+            1 => EmitterShape::Line {
+                x1: 0.0,
+                y1: 0.0,
+                x2: 100.0,
+                y2: 0.0,
+            },
+            2 => EmitterShape::Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 100.0,
+            },
+            3 => EmitterShape::DisplayEdges {
+                top: true,
+                bottom: false,
+                left: false,
+                right: false,
+            },
+            _ => panic!("Invalid emitter shape ordinal"),
+        }
+    }
+
+    pub const fn type_names() -> [&'static str; 4] {
+        ["Point", "Line", "Rectangle", "Display edges"]
+    }
+
+    /// Picks a random spawn position within the shape.
+    fn sample(&self, area: Rect, random: &mut impl Rng) -> Option<Point> {
+        let point = match self {
+            EmitterShape::Point { x, y } => Point::new(*x, *y),
+            EmitterShape::Line { x1, y1, x2, y2 } => {
+                let t = random.gen::<f32>();
+                Point::new(x1 + (x2 - x1) * t, y1 + (y2 - y1) * t)
+            }
+            EmitterShape::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => Point::new(
+                x + random.gen::<f32>() * width,
+                y + random.gen::<f32>() * height,
+            ),
+            EmitterShape::DisplayEdges {
+                top,
+                bottom,
+                left,
+                right,
+            } => {
+                let (width, height) = (area.width(), area.height());
+                let edges = [
+                    (*top, width),
+                    (*bottom, width),
+                    (*left, height),
+                    (*right, height),
+                ];
+
+                // Pick an edge weighted by its length, then a position along it
+                let total = edges
+                    .iter()
+                    .filter(|(enabled, _)| *enabled)
+                    .map(|(_, length)| length)
+                    .sum::<f32>();
+
+                if total <= 0.0 {
+                    return None;
+                }
+
+                let mut position = random.gen::<f32>() * total;
+                let mut edge = 0;
+
+                for (i, (enabled, length)) in edges.iter().enumerate() {
+                    if !enabled {
+                        continue;
+                    }
+
+                    edge = i;
+                    if position < *length {
+                        break;
+                    }
+
+                    position -= length;
+                }
+
+                match edge {
+                    0 => Point::new(position, 0.0),
+                    1 => Point::new(position, height),
+                    2 => Point::new(0.0, position),
+                    _ => Point::new(width, position),
+                }
+            }
+        };
+
+        Some(point + area.tl())
+    }
+}
+
+impl Default for EmitterShape {
+    fn default() -> Self {
+        Self::from_ordinal(3)
+    }
+}
+
+impl ModuleConfig for EmitterShape {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current_type_ordinal = self.ordinal();
+        if ui.combo_simple_string(
+            "Emitter",
+            &mut current_type_ordinal,
+            EmitterShape::type_names().as_slice(),
+        ) {
+            *self = EmitterShape::from_ordinal(current_type_ordinal);
+        }
+
+        match self {
+            EmitterShape::Point { x, y } => {
+                Drag::new("X").build(ui, x);
+                Drag::new("Y").build(ui, y);
+            }
+            EmitterShape::Line { x1, y1, x2, y2 } => {
+                Drag::new("Start X").build(ui, x1);
+                Drag::new("Start Y").build(ui, y1);
+                Drag::new("End X").build(ui, x2);
+                Drag::new("End Y").build(ui, y2);
+            }
+            EmitterShape::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                Drag::new("X").build(ui, x);
+                Drag::new("Y").build(ui, y);
+                Drag::new("Width").range(0.0, f32::MAX).build(ui, width);
+                Drag::new("Height").range(0.0, f32::MAX).build(ui, height);
+            }
+            EmitterShape::DisplayEdges {
+                top,
+                bottom,
+                left,
+                right,
+            } => {
+                ui.checkbox("Top", top);
+                ui.same_line();
+                ui.checkbox("Bottom", bottom);
+                ui.same_line();
+                ui.checkbox("Left", left);
+                ui.same_line();
+                ui.checkbox("Right", right);
+            }
+        }
+    }
+}
+
+/// What a single particle looks like.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParticleShape {
+    Circle,
+
+    /// A regular polygon with the given amount of sides.
+    Polygon {
+        sides: i32,
+    },
+
+    /// An image, its opacity follows the particle color.
+    Image {
+        path: String,
+    },
+
+    /// One or more characters of text.
+    Glyph {
+        text: String,
+    },
+}
+
+impl ParticleShape {
+    pub const fn ordinal(&self) -> usize {
+        match self {
+            ParticleShape::Circle => 0,
+            ParticleShape::Polygon { .. } => 1,
+            ParticleShape::Image { .. } => 2,
+            ParticleShape::Glyph { .. } => 3,
+        }
+    }
+
+    pub fn from_ordinal(ordinal: usize) -> Self {
+        match ordinal {
+            0 => ParticleShape::Circle,
+            1 => ParticleShape::Polygon { sides: 4 },
+            2 => ParticleShape::Image {
+                path: String::new(),
+            },
+            3 => ParticleShape::Glyph {
+                text: String::from("*"),
+            },
+            _ => panic!("Invalid particle shape ordinal"),
+        }
+    }
+
+    pub const fn type_names() -> [&'static str; 4] {
+        ["Circle", "Polygon", "Image", "Glyph"]
+    }
+}
+
+impl Default for ParticleShape {
+    fn default() -> Self {
+        Self::Circle
+    }
+}
+
+impl ModuleConfig for ParticleShape {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current_type_ordinal = self.ordinal();
+        if ui.combo_simple_string(
+            "Shape",
+            &mut current_type_ordinal,
+            ParticleShape::type_names().as_slice(),
+        ) {
+            *self = ParticleShape::from_ordinal(current_type_ordinal);
+        }
+
+        match self {
+            ParticleShape::Circle => {}
+            ParticleShape::Polygon { sides } => {
+                Drag::new("Sides")
+                    .range(3, 64)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, sides);
+            }
+            ParticleShape::Image { path } => {
+                InputText::new(ui, "Image", path).build();
+                ui.same_line();
+
+                if ui.small_button("...") {
+                    match FileDialog::new().show_open_single_file() {
+                        Ok(None) => {}
+                        Ok(Some(p)) => *path = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+            ParticleShape::Glyph { text } => {
+                InputText::new(ui, "Text", text).build();
+            }
+        }
+    }
+}
+
+/// Source of the ids identifying emitters while the application runs.
+static NEXT_EMITTER_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_emitter_id() -> u64 {
+    NEXT_EMITTER_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleEmitter {
+    /// Associates the emitter with its particles, which stay with it when other emitters
+    /// are removed.
+    #[serde(skip, default = "next_emitter_id")]
+    id: u64,
+
+    name: String,
+    enabled: bool,
+    display: DisplaySelection,
+    shape: EmitterShape,
+    particle: ParticleShape,
+
+    /// Particles spawned per second.
+    spawn_rate: f32,
+    max_particles: i32,
+
+    /// Lifetime of a particle in seconds.
+    lifetime: RangeSetting,
+
+    /// Initial velocity in pixels per second.
+    velocity_x: RangeSetting,
+    velocity_y: RangeSetting,
+
+    /// Acceleration in pixels per second squared.
+    acceleration_x: RangeSetting,
+    acceleration_y: RangeSetting,
+
+    /// Downwards acceleration applied to all particles, in pixels per second squared.
+    gravity: f32,
+
+    /// Horizontal movement applied to all particles, in pixels per second.
+    wind: f32,
+
+    size_start: RangeSetting,
+    size_end: RangeSetting,
+    color_start: ColorSetting,
+    color_end: ColorSetting,
+
+    /// Initial rotation in degrees.
+    rotation: RangeSetting,
+
+    /// Rotation speed in degrees per second.
+    spin: RangeSetting,
+
+    anti_alias: bool,
+
+    #[serde(default)]
+    cursor: CursorSetting,
+}
+
+impl ParticleEmitter {
+    fn represent_particles(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        Drag::new("Spawn rate (per second)")
+            .range(0.0, 100000.0)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut self.spawn_rate);
+
+        Drag::new("Maximum particles")
+            .range(0, 1000000)
+            .flags(SliderFlags::ALWAYS_CLAMP)
+            .build(ui, &mut self.max_particles);
+
+        self.lifetime.represent(ui, "Lifetime (s)", (0.01, 3600.0));
+
+        ui.separator();
+
+        self.particle.represent(ui, ctx);
+        ui.checkbox("Anti alias", &mut self.anti_alias);
+
+        self.size_start.represent(ui, "Start size", (0.0, 1000.0));
+        self.size_end.represent(ui, "End size", (0.0, 1000.0));
+
+        ui.text("Start color");
+        {
+            let _id = ui.push_id("start");
+            self.color_start.represent(ui, ctx);
+        }
+
+        ui.text("End color");
+        {
+            let _id = ui.push_id("end");
+            self.color_end.represent(ui, ctx);
+        }
+    }
+
+    fn represent_motion(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        self.velocity_x
+            .represent(ui, "Velocity X", (-10000.0, 10000.0));
+        self.velocity_y
+            .represent(ui, "Velocity Y", (-10000.0, 10000.0));
+        self.acceleration_x
+            .represent(ui, "Acceleration X", (-10000.0, 10000.0));
+        self.acceleration_y
+            .represent(ui, "Acceleration Y", (-10000.0, 10000.0));
+
+        Drag::new("Gravity").build(ui, &mut self.gravity);
+        Drag::new("Wind").build(ui, &mut self.wind);
+        self.cursor.represent(ui, ctx);
+
+        self.rotation.represent(ui, "Rotation", (-360.0, 360.0));
+        self.spin
+            .represent(ui, "Spin (degrees per second)", (-3600.0, 3600.0));
+    }
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            id: next_emitter_id(),
+            name: String::from("Emitter"),
+            enabled: true,
+            display: DisplaySelection::default(),
+            shape: EmitterShape::default(),
+            particle: ParticleShape::default(),
+            spawn_rate: 50.0,
+            max_particles: 1000,
+            lifetime: RangeSetting::new(8.0, 16.0),
+            velocity_x: RangeSetting::new(-20.0, 20.0),
+            velocity_y: RangeSetting::new(40.0, 80.0),
+            acceleration_x: RangeSetting::new(0.0, 0.0),
+            acceleration_y: RangeSetting::new(0.0, 0.0),
+            gravity: 5.0,
+            wind: 0.0,
+            size_start: RangeSetting::new(4.0, 6.0),
+            size_end: RangeSetting::new(2.0, 4.0),
+            color_start: ColorSetting::default(),
+            color_end: ColorSetting::from(Color4f::new(1.0, 1.0, 1.0, 0.0)),
+            rotation: RangeSetting::new(0.0, 360.0),
+            spin: RangeSetting::new(-90.0, 90.0),
+            anti_alias: true,
+            cursor: CursorSetting::default(),
+        }
+    }
+}
+
+impl ModuleConfig for ParticleEmitter {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        InputText::new(ui, "Name", &mut self.name).build();
+        ui.checkbox("Enabled", &mut self.enabled);
+
+        if ui.collapsing_header("Emitter", TreeNodeFlags::empty()) {
+            self.display.represent(ui, ctx);
+            self.shape.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Particles", TreeNodeFlags::empty()) {
+            self.represent_particles(ui, ctx);
+        }
+
+        if ui.collapsing_header("Motion", TreeNodeFlags::empty()) {
+            self.represent_motion(ui, ctx);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticleModuleConfig {
+    emitters: Vec<ParticleEmitter>,
+}
+
+impl Default for ParticleModuleConfig {
+    fn default() -> Self {
+        Self {
+            emitters: vec![ParticleEmitter::default()],
+        }
+    }
+}
+
+impl ModuleConfig for ParticleModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        let mut to_remove = None;
+
+        for (i, emitter) in self.emitters.iter_mut().enumerate() {
+            let _id = ui.push_id(i as i32);
+
+            if ui.collapsing_header(format!("{}###emitter", emitter.name), TreeNodeFlags::FRAMED) {
+                emitter.represent(ui, ctx);
+
+                if ui.button("Remove emitter") {
+                    to_remove = Some(i);
+                }
+            }
+        }
+
+        if let Some(i) = to_remove {
+            self.emitters.remove(i);
+        }
+
+        if ui.button("Add emitter") {
+            self.emitters.push(ParticleEmitter::default());
+        }
+    }
+}
+
+struct Particle {
+    x: f32,
+    y: f32,
+    velocity_x: f32,
+    velocity_y: f32,
+    acceleration_x: f32,
+    acceleration_y: f32,
+    age: f32,
+    lifetime: f32,
+    size_start: f32,
+    size_end: f32,
+    rotation: f32,
+    spin: f32,
+}
+
+impl Particle {
+    fn spawn(emitter: &ParticleEmitter, position: Point, random: &mut ThreadRng) -> Self {
+        Self {
+            x: position.x,
+            y: position.y,
+            velocity_x: emitter.velocity_x.sample(random),
+            velocity_y: emitter.velocity_y.sample(random),
+            acceleration_x: emitter.acceleration_x.sample(random),
+            acceleration_y: emitter.acceleration_y.sample(random),
+            age: 0.0,
+            lifetime: emitter.lifetime.sample(random).max(0.01),
+            size_start: emitter.size_start.sample(random),
+            size_end: emitter.size_end.sample(random),
+            rotation: emitter.rotation.sample(random),
+            spin: emitter.spin.sample(random),
+        }
+    }
+
+    /// Advances the particle, returns `false` once it has died.
+    fn tick(
+        &mut self,
+        emitter: &ParticleEmitter,
+        delta: f32,
+        bounds: &Rect,
+        pointer: Option<Point>,
+    ) -> bool {
+        self.age += delta;
+
+        self.velocity_x += self.acceleration_x * delta;
+        self.velocity_y += (self.acceleration_y + emitter.gravity) * delta;
+
+        self.x += (self.velocity_x + emitter.wind) * delta;
+        self.y += self.velocity_y * delta;
+
+        let push = emitter.cursor.velocity(pointer, Point::new(self.x, self.y));
+        self.x += push.x * delta;
+        self.y += push.y * delta;
+        self.rotation += self.spin * delta;
+
+        self.age < self.lifetime && bounds.contains(Point::new(self.x, self.y))
+    }
+}
+
+/// Resources shared by all particles of an emitter, recreated when the shape changes.
+#[derive(Default)]
+struct ShapeCache {
+    polygon: Option<(i32, Path)>,
+    image: Option<(String, Option<Image>)>,
+    glyph: Option<(String, Option<TextBlob>, Point)>,
+}
+
+impl ShapeCache {
+    /// Creates a regular polygon with a radius of 1 around the origin.
+    fn polygon(&mut self, sides: i32) -> &Path {
+        let sides = sides.max(3);
+
+        if !matches!(&self.polygon, Some((cached, _)) if *cached == sides) {
+            let points = (0..sides)
+                .map(|i| {
+                    let angle = (i as f32 / sides as f32) * std::f32::consts::TAU;
+                    Point::new(angle.cos(), angle.sin())
+                })
+                .collect::<Vec<_>>();
+
+            self.polygon = Some((sides, Path::polygon(&points, true, None, None)));
+        }
+
+        &self.polygon.as_ref().unwrap().1
+    }
+
+    fn image(&mut self, path: &str) -> Option<&Image> {
+        if !matches!(&self.image, Some((cached, _)) if cached == path) {
+            let image = match load_image(path) {
+                Ok(image) => Some(image),
+                Err(err) => {
+                    log::error!("Failed to load particle image from \"{}\": {}", path, err);
+                    None
+                }
+            };
+
+            self.image = Some((path.to_string(), image));
+        }
+
+        self.image.as_ref().unwrap().1.as_ref()
+    }
+
+    /// Retrieves the text blob and its center.
+    fn glyph(&mut self, font: &Font, text: &str) -> Option<(&TextBlob, Point)> {
+        if !matches!(&self.glyph, Some((cached, _, _)) if cached == text) {
+            let (_, bounds) = font.measure_str(text, None);
+
+            self.glyph = Some((
+                text.to_string(),
+                TextBlob::from_str(text, font),
+                bounds.center(),
+            ));
+        }
+
+        match &self.glyph {
+            Some((_, Some(blob), center)) => Some((blob, *center)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct EmitterState {
+    particles: Vec<Particle>,
+
+    /// Fractional particles which have not been spawned yet.
+    pending: f32,
+
+    cache: ShapeCache,
+}
+
+impl EmitterState {
+    fn tick(
+        &mut self,
+        emitter: &ParticleEmitter,
+        data: &SceneData,
+        delta: f32,
+        random: &mut ThreadRng,
+    ) {
+        let bounds = Rect::from_xywh(
+            -OUTSIDE_MARGIN,
+            -OUTSIDE_MARGIN,
+            data.width() as f32 + OUTSIDE_MARGIN * 2.0,
+            data.height() as f32 + OUTSIDE_MARGIN * 2.0,
+        );
+
+        let pointer = data.pointer_position();
+
+        self.particles
+            .drain_filter(|particle| !particle.tick(emitter, delta, &bounds, pointer))
+            .for_each(drop);
+
+        let area = match emitter.display.area(data) {
+            Some(area) if emitter.enabled => Rect::from(area),
+            _ => {
+                self.pending = 0.0;
+                return;
+            }
+        };
+
+        self.pending += emitter.spawn_rate * delta;
+
+        let available =
+            (emitter.max_particles.max(0) as usize).saturating_sub(self.particles.len());
+        let count = (self.pending.floor() as usize).min(available);
+        self.pending = self.pending.fract();
+
+        for _ in 0..count {
+            if let Some(position) = emitter.shape.sample(area, random) {
+                self.particles
+                    .push(Particle::spawn(emitter, position, random));
+            }
+        }
+    }
+
+    fn draw(&mut self, emitter: &ParticleEmitter, data: &mut SceneData, font: &Font) {
+        let canvas = data.canvas();
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(emitter.anti_alias);
+
+        let start = emitter.color_start.get_color();
+        let end = emitter.color_end.get_color();
+
+        for particle in &self.particles {
+            let t = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+            let size = lerp(particle.size_start, particle.size_end, t);
+
+            paint.set_color4f(
+                Color4f::new(
+                    lerp(start.r, end.r, t),
+                    lerp(start.g, end.g, t),
+                    lerp(start.b, end.b, t),
+                    lerp(start.a, end.a, t),
+                ),
+                None,
+            );
+
+            if let ParticleShape::Circle = emitter.particle {
+                canvas.draw_circle((particle.x, particle.y), size / 2.0, &paint);
+                continue;
+            }
+
+            canvas.save();
+            canvas.translate((particle.x, particle.y));
+            canvas.rotate(particle.rotation, None);
+
+            match &emitter.particle {
+                ParticleShape::Circle => {}
+                ParticleShape::Polygon { sides } => {
+                    canvas.scale((size / 2.0, size / 2.0));
+                    canvas.draw_path(self.cache.polygon(*sides), &paint);
+                }
+                ParticleShape::Image { path } => {
+                    if let Some(image) = self.cache.image(path) {
+                        let height = size * (image.height() as f32 / image.width().max(1) as f32);
+
+                        canvas.draw_image_rect(
+                            image,
+                            None,
+                            Rect::from_xywh(-size / 2.0, -height / 2.0, size, height),
+                            &paint,
+                        );
+                    }
+                }
+                ParticleShape::Glyph { text } => {
+                    if let Some((blob, center)) = self.cache.glyph(font, text) {
+                        canvas.scale((size / GLYPH_SIZE, size / GLYPH_SIZE));
+                        canvas.draw_text_blob(blob, -center, &paint);
+                    }
+                }
+            }
+
+            canvas.restore();
+        }
+    }
+}
+
+fn lerp(start: f32, end: f32, t: f32) -> f32 {
+    start + (end - start) * t
+}
+
+pub struct ParticleModuleRenderer {
+    /// State of the emitters by their id.
+    emitters: HashMap<u64, EmitterState>,
+    font: Font,
+}
+
+impl ParticleModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            emitters: HashMap::new(),
+            font: Font::from_typeface(
+                fonts::load_embedded_font(fonts::Font::NotoSansMono),
+                Some(GLYPH_SIZE),
+            ),
+        }
+    }
+}
+
+impl ModuleRenderer for ParticleModuleRenderer {
+    type Config = ParticleModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let delta = data.delta().as_secs_f32().min(MAX_TIME_STEP);
+        let mut rng = ThreadRng::default();
+
+        self.emitters
+            .retain(|id, _| config.emitters.iter().any(|emitter| emitter.id == *id));
+
+        for emitter in &config.emitters {
+            let state = self.emitters.entry(emitter.id).or_default();
+            state.tick(emitter, data, delta, &mut rng);
+            state.draw(emitter, data, &self.font);
+        }
+    }
+}
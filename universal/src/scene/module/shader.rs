@@ -0,0 +1,567 @@
+use std::path::Path;
+use std::time::{Instant, SystemTime};
+
+use chrono::{Datelike, Local, Timelike};
+use imgui::{
+    ColorEdit, Drag, EditableColor, InputText, InputTextMultiline, SliderFlags, TreeNodeFlags, Ui,
+};
+use native_dialog::FileDialog;
+use serde::{Deserialize, Serialize};
+use skia_safe::runtime_effect::uniform::Type as UniformType;
+use skia_safe::runtime_effect::Uniform;
+use skia_safe::{Data, IRect, Matrix, Paint, Rect, RuntimeEffect};
+
+use crate::scene::module::part::DisplaySelection;
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::{FileWatcher, SendCell};
+
+pub(super) struct ShaderModule;
+
+impl Module for ShaderModule {
+    type Config = ShaderModuleConfig;
+    type Renderer = ShaderModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        ShaderModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Shader".into()
+    }
+}
+
+const DEFAULT_SOURCE: &str = "uniform float time;
+uniform float2 resolution;
+
+half4 main(float2 position) {
+    float2 uv = position / resolution;
+    return half4(uv.x, uv.y, 0.5 + 0.5 * sin(time), 1.0);
+}
+";
+
+const UNIFORM_HELP: &str = "Uniforms provided by Snowland, declare them to use them:
+uniform float time - seconds since the shader has been loaded
+uniform float2 resolution - size of the filled area in pixels
+uniform float2 mouse - pointer position in the area, negative if unknown
+uniform float4 display - x, y, width and height of the display
+uniform float4 date - year, month, day and seconds since midnight
+
+All other uniforms can be edited below, declare them with
+layout(color) to edit a float3 or float4 as a color.";
+
+/// Names of the uniforms which are filled in by the renderer.
+const STANDARD_UNIFORMS: [&str; 5] = ["time", "resolution", "mouse", "display", "date"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ShaderSource {
+    Inline { code: String },
+    File { path: String },
+}
+
+impl ShaderSource {
+    pub const fn ordinal(&self) -> usize {
+        match self {
+            ShaderSource::Inline { .. } => 0,
+            ShaderSource::File { .. } => 1,
+        }
+    }
+
+    pub fn from_ordinal(ordinal: usize) -> Self {
+        match ordinal {
+            0 => ShaderSource::Inline {
+                code: DEFAULT_SOURCE.into(),
+            },
+            1 => ShaderSource::File {
+                path: String::new(),
+            },
+            _ => panic!("Invalid shader source ordinal"),
+        }
+    }
+
+    pub const fn type_names() -> [&'static str; 2] {
+        ["Inline", "File"]
+    }
+
+    /// Reads the SkSL code.
+    fn read(&self) -> Result<String, String> {
+        match self {
+            ShaderSource::Inline { code } => Ok(code.clone()),
+            ShaderSource::File { path } => std::fs::read_to_string(path)
+                .map_err(|err| format!("Failed to read {}: {}", path, err)),
+        }
+    }
+
+    /// Identifies the current state of the source, changes whenever the code does.
+    fn version(&self) -> (String, Option<SystemTime>) {
+        match self {
+            ShaderSource::Inline { code } => (code.clone(), None),
+            ShaderSource::File { path } => (
+                path.clone(),
+                std::fs::metadata(path).and_then(|m| m.modified()).ok(),
+            ),
+        }
+    }
+}
+
+impl Default for ShaderSource {
+    fn default() -> Self {
+        Self::from_ordinal(0)
+    }
+}
+
+impl ModuleConfig for ShaderSource {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current_type_ordinal = self.ordinal();
+        if ui.combo_simple_string(
+            "Source",
+            &mut current_type_ordinal,
+            ShaderSource::type_names().as_slice(),
+        ) {
+            *self = ShaderSource::from_ordinal(current_type_ordinal);
+        }
+
+        match self {
+            ShaderSource::Inline { code } => {
+                InputTextMultiline::new(ui, "SkSL", code, [-1.0, 300.0]).build();
+            }
+            ShaderSource::File { path } => {
+                InputText::new(ui, "File", path).build();
+                ui.same_line();
+
+                if ui.small_button("...") {
+                    match FileDialog::new()
+                        .add_filter("SkSL shader", &["sksl", "glsl", "frag"])
+                        .show_open_single_file()
+                    {
+                        Ok(None) => {}
+                        Ok(Some(p)) => *path = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// How a uniform declared by the shader is edited.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum UniformKind {
+    Float,
+    Int,
+    Color,
+}
+
+/// A uniform declared by the shader which is not provided by the renderer.
+#[derive(Debug, Clone)]
+struct UniformDescription {
+    name: String,
+    kind: UniformKind,
+
+    /// Amount of scalar values, including all array elements.
+    components: usize,
+}
+
+impl UniformDescription {
+    fn of(uniform: &Uniform) -> Self {
+        let components = uniform_components(uniform.ty()) * uniform.count().max(1) as usize;
+
+        let kind = if is_int(uniform.ty()) {
+            UniformKind::Int
+        } else if uniform.is_color() && (components == 3 || components == 4) {
+            UniformKind::Color
+        } else {
+            UniformKind::Float
+        };
+
+        Self {
+            name: uniform.name().to_string(),
+            kind,
+            components,
+        }
+    }
+}
+
+fn uniform_components(ty: UniformType) -> usize {
+    match ty {
+        UniformType::Float | UniformType::Int => 1,
+        UniformType::Float2 | UniformType::Int2 => 2,
+        UniformType::Float3 | UniformType::Int3 => 3,
+        UniformType::Float4 | UniformType::Int4 | UniformType::Float2x2 => 4,
+        UniformType::Float3x3 => 9,
+        UniformType::Float4x4 => 16,
+    }
+}
+
+fn is_int(ty: UniformType) -> bool {
+    matches!(
+        ty,
+        UniformType::Int | UniformType::Int2 | UniformType::Int3 | UniformType::Int4
+    )
+}
+
+/// Outcome of compiling the shader for the user interface.
+#[derive(Debug, Clone, Default)]
+struct CompileStatus {
+    /// The source version this status has been created for.
+    version: Option<(String, Option<SystemTime>)>,
+
+    /// The editable uniforms or the compile error.
+    result: Option<Result<Vec<UniformDescription>, String>>,
+}
+
+/// The value of a user declared uniform, ints and colors are stored as floats too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UniformValue {
+    name: String,
+    values: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderModuleConfig {
+    display: DisplaySelection,
+
+    /// Whether the entire display is filled, otherwise only the region below is.
+    fill_display: bool,
+
+    /// Region relative to the display.
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+
+    source: ShaderSource,
+    uniforms: Vec<UniformValue>,
+
+    #[serde(skip)]
+    status: CompileStatus,
+}
+
+impl ShaderModuleConfig {
+    /// Compiles the shader again if the source changed since the last compilation.
+    fn update_status(&mut self) {
+        let version = self.source.version();
+        if self.status.version.as_ref() == Some(&version) {
+            return;
+        }
+
+        let result = self.source.read().and_then(|code| {
+            RuntimeEffect::make_for_shader(code, None).map(|effect| {
+                effect
+                    .uniforms()
+                    .iter()
+                    .filter(|uniform| !STANDARD_UNIFORMS.contains(&uniform.name()))
+                    .map(UniformDescription::of)
+                    .collect()
+            })
+        });
+
+        self.status = CompileStatus {
+            version: Some(version),
+            result: Some(result),
+        };
+    }
+
+    /// Resolves the filled area.
+    fn area(&self, data: &SceneData) -> Option<(IRect, IRect)> {
+        let display = self.display.area(data)?;
+
+        let area = if self.fill_display {
+            display
+        } else {
+            IRect::from_xywh(
+                display.left + self.x,
+                display.top + self.y,
+                self.width,
+                self.height,
+            )
+        };
+
+        Some((display, area))
+    }
+
+    fn represent_uniforms(&mut self, ui: &Ui) {
+        let descriptions = match &self.status.result {
+            Some(Ok(descriptions)) => descriptions,
+            _ => return,
+        };
+
+        if descriptions.is_empty() {
+            ui.text_disabled("The shader declares no editable uniforms");
+        }
+
+        for description in descriptions {
+            let value = match self
+                .uniforms
+                .iter()
+                .position(|value| value.name == description.name)
+            {
+                Some(i) => &mut self.uniforms[i],
+                None => {
+                    self.uniforms.push(UniformValue {
+                        name: description.name.clone(),
+                        values: Vec::new(),
+                    });
+
+                    self.uniforms.last_mut().unwrap()
+                }
+            };
+
+            let default = match description.kind {
+                UniformKind::Color => 1.0,
+                _ => 0.0,
+            };
+            value.values.resize(description.components, default);
+
+            match description.kind {
+                UniformKind::Color if description.components == 3 => {
+                    let color: &mut [f32; 3] = value.values.as_mut_slice().try_into().unwrap();
+                    ColorEdit::new(&description.name, EditableColor::Float3(color)).build(ui);
+                }
+                UniformKind::Color => {
+                    let color: &mut [f32; 4] = value.values.as_mut_slice().try_into().unwrap();
+                    ColorEdit::new(&description.name, EditableColor::Float4(color)).build(ui);
+                }
+                UniformKind::Int => {
+                    Drag::new(&description.name)
+                        .speed(1.0)
+                        .display_format("%.0f")
+                        .build_array(ui, &mut value.values);
+
+                    for v in &mut value.values {
+                        *v = v.round();
+                    }
+                }
+                UniformKind::Float => {
+                    Drag::new(&description.name)
+                        .speed(0.01)
+                        .build_array(ui, &mut value.values);
+                }
+            }
+        }
+    }
+}
+
+impl Default for ShaderModuleConfig {
+    fn default() -> Self {
+        Self {
+            display: DisplaySelection::default(),
+            fill_display: true,
+            x: 0,
+            y: 0,
+            width: 400,
+            height: 300,
+            source: ShaderSource::default(),
+            uniforms: Vec::new(),
+            status: CompileStatus::default(),
+        }
+    }
+}
+
+impl ModuleConfig for ShaderModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.display.represent(ui, ctx);
+            ui.checkbox("Fill display", &mut self.fill_display);
+
+            if !self.fill_display {
+                Drag::new("X").build(ui, &mut self.x);
+                Drag::new("Y").build(ui, &mut self.y);
+                Drag::new("Width")
+                    .range(1, i32::MAX)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.width);
+                Drag::new("Height")
+                    .range(1, i32::MAX)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.height);
+            }
+        }
+
+        self.update_status();
+
+        if ui.collapsing_header("Shader", TreeNodeFlags::FRAMED) {
+            self.source.represent(ui, ctx);
+
+            ui.text_disabled("Available uniforms (?)");
+            if ui.is_item_hovered() {
+                ui.tooltip_text(UNIFORM_HELP);
+            }
+
+            if let Some(Err(err)) = &self.status.result {
+                ui.text_colored(ERROR_COLOR, err);
+            }
+        }
+
+        if ui.collapsing_header("Uniforms", TreeNodeFlags::FRAMED) {
+            self.represent_uniforms(ui);
+        }
+    }
+}
+
+/// The shader as currently loaded by the renderer.
+struct LoadedShader {
+    source: ShaderSource,
+    watcher: Option<FileWatcher>,
+    effect: Option<SendCell<RuntimeEffect>>,
+}
+
+pub struct ShaderModuleRenderer {
+    start: Instant,
+    loaded: Option<LoadedShader>,
+
+    /// Buffer the uniform values are assembled in.
+    uniforms: Vec<u8>,
+}
+
+impl ShaderModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            loaded: None,
+            uniforms: Vec::new(),
+        }
+    }
+
+    /// Compiles the shader if the source changed.
+    fn effect(&mut self, source: &ShaderSource) -> Option<&RuntimeEffect> {
+        let outdated = match &self.loaded {
+            None => true,
+            Some(loaded) => {
+                loaded.source != *source
+                    || loaded
+                        .watcher
+                        .as_ref()
+                        .map_or(false, FileWatcher::has_changed)
+            }
+        };
+
+        if outdated {
+            let watcher = match source {
+                ShaderSource::File { path } if !path.is_empty() => {
+                    match FileWatcher::new(Path::new(path)) {
+                        Ok(watcher) => Some(watcher),
+                        Err(err) => {
+                            log::warn!("Failed to watch shader file {}: {}", path, err);
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            };
+
+            // Errors are displayed in the module panel
+            let effect = source
+                .read()
+                .and_then(|code| RuntimeEffect::make_for_shader(code, None))
+                .ok()
+                // Runtime effects are immutable and reference counted atomically
+                .map(|effect| unsafe { SendCell::new(effect) });
+
+            self.loaded = Some(LoadedShader {
+                source: source.clone(),
+                watcher,
+                effect,
+            });
+        }
+
+        self.loaded
+            .as_ref()
+            .and_then(|loaded| loaded.effect.as_ref())
+            .map(|effect| &**effect)
+    }
+}
+
+/// Writes values into the uniform buffer at the location of the given uniform.
+fn write_uniform(buffer: &mut [u8], uniform: &Uniform, values: &[f32]) {
+    let start = uniform.offset().min(buffer.len());
+    let end = (uniform.offset() + uniform.size_in_bytes()).min(buffer.len());
+    let int = is_int(uniform.ty());
+
+    for (target, value) in buffer[start..end].chunks_exact_mut(4).zip(values) {
+        let bytes = if int {
+            (*value as i32).to_ne_bytes()
+        } else {
+            value.to_ne_bytes()
+        };
+
+        target.copy_from_slice(&bytes);
+    }
+}
+
+impl ModuleRenderer for ShaderModuleRenderer {
+    type Config = ShaderModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let (display, area) = match config.area(data) {
+            Some(v) if !v.1.is_empty() => v,
+            _ => return,
+        };
+
+        let time = self.start.elapsed().as_secs_f32();
+        let mut uniforms = std::mem::take(&mut self.uniforms);
+
+        let effect = match self.effect(&config.source) {
+            None => return,
+            Some(v) => v,
+        };
+
+        let mouse = match data.pointer_position() {
+            Some(pointer) => [pointer.x - area.left as f32, pointer.y - area.top as f32],
+            None => [-1.0, -1.0],
+        };
+
+        let now = Local::now();
+        let date = [
+            now.year() as f32,
+            now.month() as f32,
+            now.day() as f32,
+            now.num_seconds_from_midnight() as f32 + now.nanosecond() as f32 / 1e9,
+        ];
+
+        let time = [time];
+        let resolution = [area.width() as f32, area.height() as f32];
+        let display = [
+            display.left as f32,
+            display.top as f32,
+            display.width() as f32,
+            display.height() as f32,
+        ];
+
+        uniforms.clear();
+        uniforms.resize(effect.uniform_size(), 0);
+
+        for uniform in effect.uniforms() {
+            let values: &[f32] = match uniform.name() {
+                "time" => &time,
+                "resolution" => &resolution,
+                "mouse" => &mouse,
+                "display" => &display,
+                "date" => &date,
+                name => config
+                    .uniforms
+                    .iter()
+                    .find(|value| value.name == name)
+                    .map_or(&[], |value| value.values.as_slice()),
+            };
+
+            write_uniform(&mut uniforms, uniform, values);
+        }
+
+        // Shader coordinates are relative to the filled area
+        let matrix = Matrix::translate((area.left as f32, area.top as f32));
+        let shader = effect.make_shader(Data::new_copy(&uniforms), &[], &matrix, false);
+        self.uniforms = uniforms;
+
+        if let Some(shader) = shader {
+            let mut paint = Paint::default();
+            paint.set_shader(shader);
+
+            data.canvas().draw_rect(Rect::from(area), &paint);
+        }
+    }
+}
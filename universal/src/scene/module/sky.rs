@@ -0,0 +1,471 @@
+use std::time::Instant;
+
+use chrono::Utc;
+use imgui::{Drag, SliderFlags, TreeNodeFlags, Ui};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use skia_safe::{Color, IRect, Paint, Path, Point, Rect, Shader, TileMode};
+
+use crate::scene::module::part::DisplaySelection;
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::util::{moon_phase, moon_position, sun_position, HorizontalPosition, Observer};
+
+pub(super) struct SkyModule;
+
+impl Module for SkyModule {
+    type Config = SkyModuleConfig;
+    type Renderer = SkyModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        SkyModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Sky".into()
+    }
+}
+
+/// Sky colors at the zenith and the horizon for a given sun altitude in degrees.
+///
+/// Altitudes in between are interpolated, the list has to be sorted by altitude.
+const SKY_COLORS: [(f32, [u8; 3], [u8; 3]); 7] = [
+    (-18.0, [4, 6, 18], [12, 16, 36]),
+    (-10.0, [12, 18, 48], [44, 38, 78]),
+    (-4.0, [36, 48, 108], [196, 102, 84]),
+    (0.0, [64, 96, 166], [248, 150, 82]),
+    (6.0, [84, 136, 206], [246, 198, 148]),
+    (20.0, [58, 128, 218], [168, 208, 238]),
+    (90.0, [38, 108, 218], [148, 198, 240]),
+];
+
+const GLOW_COLOR: [u8; 3] = [255, 132, 56];
+const SUN_COLOR: [u8; 3] = [255, 244, 214];
+const MOON_COLOR: [u8; 3] = [236, 236, 226];
+
+/// Amount of points used per half circle when drawing the moon.
+const MOON_SEGMENTS: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkyModuleConfig {
+    display: DisplaySelection,
+
+    /// Location of the observer in degrees.
+    latitude: f64,
+    longitude: f64,
+
+    /// Compass direction in degrees the center of the display looks at.
+    facing: f32,
+
+    /// Horizontal field of view in degrees.
+    field_of_view: f32,
+
+    sun: bool,
+    glow: bool,
+    stars: bool,
+    star_count: u32,
+    twinkle: bool,
+    moon: bool,
+    moon_size: f32,
+}
+
+impl SkyModuleConfig {
+    fn observer(&self) -> Observer {
+        Observer {
+            latitude: self.latitude,
+            longitude: self.longitude,
+        }
+    }
+
+    /// Projects a position on the sky onto the area, the horizon is the bottom edge.
+    fn project(&self, area: &IRect, position: &HorizontalPosition) -> Point {
+        let offset = ((position.azimuth as f32 - self.facing) + 540.0).rem_euclid(360.0) - 180.0;
+        let width = area.width() as f32;
+        let height = area.height() as f32;
+
+        // Keep the aspect ratio of the sky identical in both directions
+        let vertical_field_of_view = self.field_of_view * height / width;
+
+        Point::new(
+            area.center_x() as f32 + offset / self.field_of_view * width,
+            area.bottom as f32 - position.altitude as f32 / vertical_field_of_view * height,
+        )
+    }
+}
+
+impl Default for SkyModuleConfig {
+    fn default() -> Self {
+        Self {
+            display: DisplaySelection::default(),
+            latitude: 52.52,
+            longitude: 13.405,
+            facing: 180.0,
+            field_of_view: 180.0,
+            sun: true,
+            glow: true,
+            stars: true,
+            star_count: 400,
+            twinkle: true,
+            moon: true,
+            moon_size: 40.0,
+        }
+    }
+}
+
+impl ModuleConfig for SkyModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.display.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Location", TreeNodeFlags::FRAMED) {
+            Drag::new("Latitude")
+                .range(-90.0, 90.0)
+                .speed(0.01)
+                .display_format("%.4f")
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.latitude);
+            Drag::new("Longitude")
+                .range(-180.0, 180.0)
+                .speed(0.01)
+                .display_format("%.4f")
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.longitude);
+
+            ui.text_disabled("North and east are positive");
+        }
+
+        if ui.collapsing_header("View", TreeNodeFlags::FRAMED) {
+            Drag::new("Facing (degrees from north)")
+                .range(0.0, 360.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.facing);
+            Drag::new("Field of view")
+                .range(10.0, 360.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.field_of_view);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            ui.checkbox("Sun", &mut self.sun);
+            ui.checkbox("Sunrise and sunset glow", &mut self.glow);
+
+            ui.checkbox("Stars", &mut self.stars);
+            if self.stars {
+                Drag::new("Star count")
+                    .range(0, 10000)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.star_count);
+                ui.checkbox("Twinkle", &mut self.twinkle);
+            }
+
+            ui.checkbox("Moon", &mut self.moon);
+            if self.moon {
+                Drag::new("Moon size")
+                    .range(1.0, 1000.0)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.moon_size);
+            }
+        }
+    }
+}
+
+/// A star, positioned relative to the area so it survives resizes.
+struct Star {
+    x: f32,
+    y: f32,
+    radius: f32,
+    brightness: f32,
+    phase: f32,
+}
+
+impl Star {
+    fn new_random(random: &mut impl Rng) -> Self {
+        // Stars close to the horizon are drowned out by the atmosphere
+        Self {
+            x: random.gen(),
+            y: random.gen::<f32>().powf(1.5) * 0.9,
+            radius: random.gen_range(0.4..1.4),
+            brightness: random.gen_range(0.3..1.0),
+            phase: random.gen_range(0.0..std::f32::consts::TAU),
+        }
+    }
+}
+
+pub struct SkyModuleRenderer {
+    start: Instant,
+    stars: Vec<Star>,
+    moon_path: Path,
+}
+
+impl SkyModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            stars: Vec::new(),
+            moon_path: Path::new(),
+        }
+    }
+
+    fn draw_stars(
+        &mut self,
+        config: &SkyModuleConfig,
+        area: &IRect,
+        visibility: f32,
+        data: &mut SceneData,
+    ) {
+        let count = config.star_count as usize;
+        if self.stars.len() != count {
+            let mut random = rand::thread_rng();
+            self.stars
+                .resize_with(count, || Star::new_random(&mut random));
+        }
+
+        let time = self.start.elapsed().as_secs_f32();
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+
+        for star in &self.stars {
+            let twinkle = if config.twinkle {
+                0.75 + 0.25 * (time * 2.0 + star.phase).sin()
+            } else {
+                1.0
+            };
+
+            paint.set_color(with_alpha(
+                [255, 255, 255],
+                star.brightness * twinkle * visibility,
+            ));
+
+            data.canvas().draw_circle(
+                (
+                    area.left as f32 + star.x * area.width() as f32,
+                    area.top as f32 + star.y * area.height() as f32,
+                ),
+                star.radius,
+                &paint,
+            );
+        }
+    }
+
+    /// Draws the moon with the lit part following the phase.
+    fn draw_moon(
+        &mut self,
+        config: &SkyModuleConfig,
+        center: Point,
+        phase: f64,
+        zenith: Color,
+        daylight: f32,
+        data: &mut SceneData,
+    ) {
+        let radius = config.moon_size / 2.0;
+
+        // The unlit part still hides the stars behind it
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_color(zenith);
+        data.canvas().draw_circle(center, radius, &paint);
+
+        build_moon_path(
+            &mut self.moon_path,
+            center,
+            radius,
+            phase,
+            config.latitude < 0.0,
+        );
+
+        // The moon fades into the bright day sky
+        paint.set_color(with_alpha(MOON_COLOR, 1.0 - daylight * 0.5));
+        data.canvas().draw_path(&self.moon_path, &paint);
+    }
+}
+
+/// Builds the outline of the lit part of the moon for the given phase.
+pub(super) fn build_moon_path(
+    path: &mut Path,
+    center: Point,
+    radius: f32,
+    phase: f64,
+    southern_hemisphere: bool,
+) {
+    // Waxing moons are lit on the right on the northern hemisphere and on the left
+    // on the southern hemisphere, waning moons the other way around
+    let mut side = if phase < 0.5 { 1.0 } else { -1.0 };
+    if southern_hemisphere {
+        side = -side;
+    }
+
+    let terminator = (phase * std::f64::consts::TAU).cos() as f32;
+
+    path.reset();
+    for i in 0..=MOON_SEGMENTS {
+        let angle = std::f32::consts::PI * (i as f32 / MOON_SEGMENTS as f32 - 0.5);
+        let point = (
+            center.x + side * radius * angle.cos(),
+            center.y + radius * angle.sin(),
+        );
+
+        if i == 0 {
+            path.move_to(point);
+        } else {
+            path.line_to(point);
+        }
+    }
+
+    for i in (0..=MOON_SEGMENTS).rev() {
+        let angle = std::f32::consts::PI * (i as f32 / MOON_SEGMENTS as f32 - 0.5);
+        path.line_to((
+            center.x + side * radius * angle.cos() * terminator,
+            center.y + radius * angle.sin(),
+        ));
+    }
+
+    path.close();
+}
+
+fn with_alpha(color: [u8; 3], alpha: f32) -> Color {
+    Color::from_argb(
+        (alpha.clamp(0.0, 1.0) * 255.0) as u8,
+        color[0],
+        color[1],
+        color[2],
+    )
+}
+
+fn rgb(color: [u8; 3]) -> Color {
+    Color::from_rgb(color[0], color[1], color[2])
+}
+
+fn mix(a: [u8; 3], b: [u8; 3], t: f32) -> Color {
+    let channel = |i: usize| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8;
+    Color::from_rgb(channel(0), channel(1), channel(2))
+}
+
+/// Looks up the zenith and horizon colors for a sun altitude.
+fn sky_colors(altitude: f32) -> (Color, Color) {
+    let (first_altitude, first_zenith, first_horizon) = SKY_COLORS[0];
+    if altitude <= first_altitude {
+        return (rgb(first_zenith), rgb(first_horizon));
+    }
+
+    for pair in SKY_COLORS.windows(2) {
+        let (low, low_zenith, low_horizon) = pair[0];
+        let (high, high_zenith, high_horizon) = pair[1];
+
+        if altitude <= high {
+            let t = (altitude - low) / (high - low);
+            return (
+                mix(low_zenith, high_zenith, t),
+                mix(low_horizon, high_horizon, t),
+            );
+        }
+    }
+
+    let (_, zenith, horizon) = SKY_COLORS[SKY_COLORS.len() - 1];
+    (rgb(zenith), rgb(horizon))
+}
+
+/// Maps a value linearly from one range to 0.0 to 1.0, clamping it.
+fn ramp(value: f32, from: f32, to: f32) -> f32 {
+    ((value - from) / (to - from)).clamp(0.0, 1.0)
+}
+
+impl ModuleRenderer for SkyModuleRenderer {
+    type Config = SkyModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        let area = match config.display.area(data) {
+            Some(v) if !v.is_empty() => v,
+            _ => return,
+        };
+
+        let now = Utc::now();
+        let observer = config.observer();
+        let sun = sun_position(observer, now);
+        let altitude = sun.altitude as f32;
+
+        let (zenith, horizon) = sky_colors(altitude);
+        let rect = Rect::from(area);
+
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_shader(Shader::linear_gradient(
+            (
+                Point::new(rect.left, rect.top),
+                Point::new(rect.left, rect.bottom),
+            ),
+            [zenith, horizon].as_ref(),
+            None,
+            TileMode::Clamp,
+            None,
+            None,
+        ));
+        data.canvas().draw_rect(rect, &paint);
+
+        // Stars start to show once the sun is well below the horizon
+        let darkness = ramp(altitude, -4.0, -14.0);
+        if config.stars && darkness > 0.0 {
+            self.draw_stars(config, &area, darkness, data);
+        }
+
+        let sun_center = config.project(&area, &sun);
+
+        if config.glow {
+            let strength = 1.0 - ((altitude + 1.0).abs() / 9.0).min(1.0);
+
+            if strength > 0.0 {
+                let center = Point::new(sun_center.x, rect.bottom);
+                let radius = rect.width().max(rect.height()) * 0.6;
+
+                let mut paint = Paint::default();
+                paint.set_shader(Shader::radial_gradient(
+                    center,
+                    radius,
+                    [
+                        with_alpha(GLOW_COLOR, strength * 0.8),
+                        with_alpha(GLOW_COLOR, 0.0),
+                    ]
+                    .as_ref(),
+                    None,
+                    TileMode::Clamp,
+                    None,
+                    None,
+                ));
+                data.canvas().draw_rect(rect, &paint);
+            }
+        }
+
+        let daylight = ramp(altitude, -6.0, 6.0);
+
+        if config.moon {
+            let moon = moon_position(observer, now);
+            if moon.altitude > -1.0 {
+                let center = config.project(&area, &moon);
+                self.draw_moon(config, center, moon_phase(now), zenith, daylight, data);
+            }
+        }
+
+        if config.sun && altitude > -1.0 {
+            let radius = rect.width().min(rect.height()) * 0.03;
+
+            let mut paint = Paint::default();
+            paint.set_anti_alias(true);
+            paint.set_shader(Shader::radial_gradient(
+                sun_center,
+                radius * 3.0,
+                [
+                    with_alpha(SUN_COLOR, 1.0),
+                    with_alpha(SUN_COLOR, 1.0),
+                    with_alpha(SUN_COLOR, 0.0),
+                ]
+                .as_ref(),
+                [0.0, 0.33, 1.0].as_ref(),
+                TileMode::Clamp,
+                None,
+                None,
+            ));
+            data.canvas().draw_circle(sun_center, radius * 3.0, &paint);
+        }
+    }
+}
@@ -1,12 +1,20 @@
-use imgui::{Drag, DragRange, SliderFlags, TreeNodeFlags, Ui};
+use imgui::{Drag, DragRange, InputText, SliderFlags, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
 use rand::rngs::ThreadRng;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use skia_safe::{Color4f, Paint};
+use skia_safe::canvas::PointMode;
+use skia_safe::vertices::VertexMode;
+use skia_safe::{
+    BlendMode, BlurStyle, Cap, Color, Color4f, IRect, Image, MaskFilter, Paint, Path, Point, Rect,
+    SamplingOptions, Vertices,
+};
 
+use crate::scene::module::part::{ColorSetting, CursorSetting};
 use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
 use crate::scene::SceneData;
 use crate::ui::context::Context;
+use crate::util::load_image;
 
 pub(super) struct SnowModule;
 
@@ -36,6 +44,70 @@ const DEFAULT_TUMBLING_MAX: f32 = 1.0;
 const DEFAULT_FALLING_SPEED_MIN: f32 = 1.0;
 const DEFAULT_FALLING_SPEED_MAX: f32 = 3.0;
 
+const DEFAULT_SIZE_MIN: f32 = 2.5;
+const DEFAULT_SIZE_MAX: f32 = 2.5;
+
+const DEFAULT_OPACITY_MIN: f32 = 1.0;
+const DEFAULT_OPACITY_MAX: f32 = 1.0;
+
+const DEFAULT_GUST_INTERVAL: f32 = 10.0;
+const DEFAULT_DEPTH_SCALE: f32 = 0.4;
+const DEFAULT_DEPTH_BLUR: f32 = 1.5;
+
+const DEFAULT_ACCUMULATION_RATE: f32 = 1.0;
+const DEFAULT_MAX_ACCUMULATION: f32 = 40.0;
+const DEFAULT_MELTING_SPEED: f32 = 1.0;
+
+/// Width of a single column of the accumulated snow height maps.
+const COVER_COLUMN_WIDTH: f32 = 4.0;
+
+/// Maximum height difference between two neighbouring columns before snow slides down.
+const COVER_MAX_SLOPE: f32 = 3.0;
+
+fn default_size_min() -> f32 {
+    DEFAULT_SIZE_MIN
+}
+
+fn default_size_max() -> f32 {
+    DEFAULT_SIZE_MAX
+}
+
+fn default_opacity_min() -> f32 {
+    DEFAULT_OPACITY_MIN
+}
+
+fn default_opacity_max() -> f32 {
+    DEFAULT_OPACITY_MAX
+}
+
+fn default_gust_interval() -> f32 {
+    DEFAULT_GUST_INTERVAL
+}
+
+fn default_depth_layers() -> i32 {
+    1
+}
+
+fn default_depth_scale() -> f32 {
+    DEFAULT_DEPTH_SCALE
+}
+
+fn default_depth_blur() -> f32 {
+    DEFAULT_DEPTH_BLUR
+}
+
+fn default_accumulation_rate() -> f32 {
+    DEFAULT_ACCUMULATION_RATE
+}
+
+fn default_max_accumulation() -> f32 {
+    DEFAULT_MAX_ACCUMULATION
+}
+
+fn default_melting_speed() -> f32 {
+    DEFAULT_MELTING_SPEED
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnowModuleConfig {
     pixel_flake_ratio: i32,
@@ -46,6 +118,71 @@ pub struct SnowModuleConfig {
     tumbling_max: f32,
     falling_speed_min: f32,
     falling_speed_max: f32,
+
+    /// Radius of the flakes.
+    #[serde(default = "default_size_min")]
+    size_min: f32,
+    #[serde(default = "default_size_max")]
+    size_max: f32,
+
+    #[serde(default = "default_opacity_min")]
+    opacity_min: f32,
+    #[serde(default = "default_opacity_max")]
+    opacity_max: f32,
+
+    /// Constant horizontal movement, in the same unit as the falling speed.
+    #[serde(default)]
+    wind: f32,
+
+    /// Additional horizontal movement at the peak of a gust.
+    #[serde(default)]
+    gust_strength: f32,
+
+    /// Average time between two gusts in seconds.
+    #[serde(default = "default_gust_interval")]
+    gust_interval: f32,
+
+    /// Amount of parallax layers, 1 disables the parallax effect.
+    #[serde(default = "default_depth_layers")]
+    depth_layers: i32,
+
+    /// Size and speed factor of the most distant layer.
+    #[serde(default = "default_depth_scale")]
+    depth_scale: f32,
+
+    /// Blur radius of the most distant layer.
+    #[serde(default = "default_depth_blur")]
+    depth_blur: f32,
+
+    #[serde(default)]
+    color: ColorSetting,
+
+    /// Images used instead of circles, picked randomly per flake.
+    #[serde(default)]
+    sprites: Vec<String>,
+
+    /// Whether flakes settle at the bottom of the displays.
+    #[serde(default)]
+    accumulate: bool,
+
+    /// Whether flakes settle on top of windows, if the host can determine them.
+    #[serde(default)]
+    accumulate_on_windows: bool,
+
+    /// Multiplier for the amount of snow a single flake adds.
+    #[serde(default = "default_accumulation_rate")]
+    accumulation_rate: f32,
+
+    /// Maximum height of accumulated snow in pixels.
+    #[serde(default = "default_max_accumulation")]
+    max_accumulation: f32,
+
+    /// Pixels of accumulated snow melting per second.
+    #[serde(default = "default_melting_speed")]
+    melting_speed: f32,
+
+    #[serde(default)]
+    cursor: CursorSetting,
 }
 
 impl SnowModuleConfig {
@@ -74,6 +211,35 @@ impl SnowModuleConfig {
             *current_max = default_max;
         }
     }
+
+    /// Computes the horizontal movement caused by wind at the given time in seconds.
+    ///
+    /// Gusts are the product of two sines with unrelated frequencies, which makes them
+    /// appear irregular while still rising and falling smoothly.
+    fn wind_at(&self, time: f32) -> f32 {
+        if self.gust_strength == 0.0 || self.gust_interval <= 0.0 {
+            return self.wind;
+        }
+
+        let frequency = std::f32::consts::TAU / self.gust_interval;
+        let gust = ((time * frequency).sin() * (time * frequency * 0.37 + 1.3).sin()).max(0.0);
+
+        self.wind + gust * self.gust_strength
+    }
+
+    /// Computes the size and speed factor and the blur radius of a depth layer.
+    fn layer_properties(&self, layer: usize) -> (f32, f32) {
+        if self.depth_layers <= 1 {
+            return (1.0, 0.0);
+        }
+
+        let depth = layer as f32 / (self.depth_layers - 1) as f32;
+
+        (
+            1.0 + (self.depth_scale - 1.0) * depth,
+            self.depth_blur * depth,
+        )
+    }
 }
 
 impl Default for SnowModuleConfig {
@@ -87,12 +253,30 @@ impl Default for SnowModuleConfig {
             tumbling_max: DEFAULT_TUMBLING_MAX,
             falling_speed_min: DEFAULT_FALLING_SPEED_MIN,
             falling_speed_max: DEFAULT_FALLING_SPEED_MAX,
+            size_min: DEFAULT_SIZE_MIN,
+            size_max: DEFAULT_SIZE_MAX,
+            opacity_min: DEFAULT_OPACITY_MIN,
+            opacity_max: DEFAULT_OPACITY_MAX,
+            wind: 0.0,
+            gust_strength: 0.0,
+            gust_interval: DEFAULT_GUST_INTERVAL,
+            depth_layers: default_depth_layers(),
+            depth_scale: DEFAULT_DEPTH_SCALE,
+            depth_blur: DEFAULT_DEPTH_BLUR,
+            color: ColorSetting::default(),
+            sprites: Vec::new(),
+            accumulate: false,
+            accumulate_on_windows: false,
+            accumulation_rate: DEFAULT_ACCUMULATION_RATE,
+            max_accumulation: DEFAULT_MAX_ACCUMULATION,
+            melting_speed: DEFAULT_MELTING_SPEED,
+            cursor: CursorSetting::default(),
         }
     }
 }
 
 impl ModuleConfig for SnowModuleConfig {
-    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
         if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
             if let Some(_tab) = ui.begin_table("Values", 3) {
                 ui.table_next_row();
@@ -153,6 +337,24 @@ impl ModuleConfig for SnowModuleConfig {
                         DEFAULT_FALLING_SPEED_MIN,
                         DEFAULT_FALLING_SPEED_MAX,
                     ),
+                    (
+                        "Size",
+                        0.1,
+                        100.0,
+                        &mut self.size_min,
+                        &mut self.size_max,
+                        DEFAULT_SIZE_MIN,
+                        DEFAULT_SIZE_MAX,
+                    ),
+                    (
+                        "Opacity",
+                        0.0,
+                        1.0,
+                        &mut self.opacity_min,
+                        &mut self.opacity_max,
+                        DEFAULT_OPACITY_MIN,
+                        DEFAULT_OPACITY_MAX,
+                    ),
                 ];
 
                 for range in ranges {
@@ -165,16 +367,180 @@ impl ModuleConfig for SnowModuleConfig {
                 }
             }
         }
+
+        if ui.collapsing_header("Wind", TreeNodeFlags::FRAMED) {
+            Drag::new("Wind")
+                .range(-100.0, 100.0)
+                .speed(0.01)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.wind);
+
+            Drag::new("Gust strength")
+                .range(-100.0, 100.0)
+                .speed(0.01)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.gust_strength);
+
+            Drag::new("Gust interval (s)")
+                .range(0.1, 3600.0)
+                .speed(0.1)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.gust_interval);
+        }
+
+        if ui.collapsing_header("Depth", TreeNodeFlags::FRAMED) {
+            Drag::new("Layers")
+                .range(1, 16)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.depth_layers);
+
+            if self.depth_layers > 1 {
+                Drag::new("Distant scale")
+                    .range(0.05, 1.0)
+                    .speed(0.01)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.depth_scale);
+
+                Drag::new("Distant blur")
+                    .range(0.0, 20.0)
+                    .speed(0.01)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.depth_blur);
+            }
+        }
+
+        if ui.collapsing_header("Appearance", TreeNodeFlags::FRAMED) {
+            self.color.represent(ui, ctx);
+
+            ui.separator();
+            ui.text("Sprites");
+
+            let mut to_remove = None;
+
+            for (i, sprite) in self.sprites.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                InputText::new(ui, "Path", sprite).build();
+                ui.same_line();
+
+                if ui.small_button("...") {
+                    match FileDialog::new().show_open_single_file() {
+                        Ok(None) => {}
+                        Ok(Some(p)) => *sprite = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+
+                ui.same_line();
+                if ui.small_button("Remove") {
+                    to_remove = Some(i);
+                }
+            }
+
+            if let Some(i) = to_remove {
+                self.sprites.remove(i);
+            }
+
+            if ui.button("Add sprite") {
+                self.sprites.push(String::new());
+            }
+        }
+
+        if ui.collapsing_header("Cursor", TreeNodeFlags::FRAMED) {
+            self.cursor.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Accumulation", TreeNodeFlags::FRAMED) {
+            ui.checkbox("Accumulate at the bottom", &mut self.accumulate);
+            ui.checkbox("Accumulate on windows", &mut self.accumulate_on_windows);
+
+            if ui.is_item_hovered() {
+                ui.tooltip_text(
+                    "Requires a window manager supporting _NET_CLIENT_LIST_STACKING, \
+                    currently only available on X11",
+                );
+            }
+
+            if self.accumulate || self.accumulate_on_windows {
+                Drag::new("Rate")
+                    .range(0.0, 100.0)
+                    .speed(0.01)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.accumulation_rate);
+
+                Drag::new("Maximum height")
+                    .range(1.0, 1000.0)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.max_accumulation);
+
+                Drag::new("Melting speed")
+                    .range(0.0, 1000.0)
+                    .speed(0.01)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.melting_speed);
+            }
+        }
     }
 }
 
 pub struct SnowModuleRenderer {
-    flakes: Vec<Snowflake>,
+    flakes: Snowflakes,
+    sprites: Vec<(String, Option<Image>)>,
+
+    /// Seconds since the renderer has been created, drives the gusts.
+    time: f32,
+
+    cover: SnowCover,
+    batches: SnowBatches,
 }
 
 impl SnowModuleRenderer {
     pub fn new() -> SnowModuleRenderer {
-        Self { flakes: Vec::new() }
+        Self {
+            flakes: Snowflakes::default(),
+            sprites: Vec::new(),
+            time: 0.0,
+            cover: SnowCover::new(),
+            batches: SnowBatches::new(),
+        }
+    }
+
+    /// Loads the sprite images if the configured paths changed.
+    fn update_sprites(&mut self, config: &SnowModuleConfig) {
+        let unchanged = self.sprites.len() == config.sprites.len()
+            && self
+                .sprites
+                .iter()
+                .zip(config.sprites.iter())
+                .all(|((loaded, _), configured)| loaded == configured);
+
+        if unchanged {
+            return;
+        }
+
+        self.sprites = config
+            .sprites
+            .iter()
+            .map(|path| {
+                let image = match load_image(path) {
+                    Ok(image) => Some(image),
+                    Err(err) if !path.is_empty() => {
+                        log::error!("Failed to load snow sprite from \"{}\": {}", path, err);
+                        None
+                    }
+                    Err(_) => None,
+                };
+
+                (path.clone(), image)
+            })
+            .collect();
+
+        let sprite_count = self.sprites.len();
+        for sprite in &mut self.flakes.sprite {
+            *sprite = sprite.filter(|sprite| *sprite < sprite_count);
+        }
     }
 }
 
@@ -186,100 +552,774 @@ impl ModuleRenderer for SnowModuleRenderer {
 
         let mut rng = ThreadRng::default();
 
-        if (target_flake_count as usize) != self.flakes.len() {
-            self.flakes.resize_with(target_flake_count as _, || {
-                Snowflake::new_random(data, config, &mut rng)
-            });
-        }
+        self.update_sprites(config);
 
-        for flake in self.flakes.iter_mut() {
-            flake.tick(data, config, &mut rng);
-        }
+        let sprite_count = self.sprites.len();
+        self.flakes.resize(target_flake_count.max(0) as usize, || {
+            Snowflake::new_random(data, config, sprite_count, &mut rng)
+        });
+
+        self.time += data.delta().as_secs_f32();
+        let wind = config.wind_at(self.time);
+
+        self.cover.update(config, data);
+        self.flakes
+            .update(data, config, wind, sprite_count, &mut self.cover, &mut rng);
+
+        self.batches.prepare(config);
+        self.cover.draw(data, &self.batches.cover_paint);
+        self.batches.draw(&self.flakes, &self.sprites, config, data);
     }
 }
 
+/// The properties of a single snowflake.
 struct Snowflake {
     x: f32,
     y: f32,
-    time_alive: f32,
 
     tumbling_multiplier: f32,
     time_to_live: f32,
     falling_speed: f32,
+
+    /// Offset into the tumble so flakes don't move in lockstep.
+    phase: f32,
+    size: f32,
+    opacity: f32,
+    layer: usize,
+    sprite: Option<usize>,
 }
 
 impl Snowflake {
     pub fn new_random<'a>(
         data: &SceneData<'a>,
         config: &SnowModuleConfig,
+        sprite_count: usize,
         random: &mut ThreadRng,
     ) -> Self {
-        let x = random.gen_range(0..data.width()) as f32;
-        let y = random.gen_range(0..data.height()) as f32;
+        let x = random.gen_range(0..data.width().max(1)) as f32;
+        let y = random.gen_range(0..data.height().max(1)) as f32;
 
         let tumbling_multiplier = random.gen_range(config.tumbling_min..=config.tumbling_max);
         let time_to_live = random.gen_range(config.time_to_live_min..=config.time_to_live_max);
         let falling_speed = random.gen_range(config.falling_speed_min..=config.falling_speed_max);
 
+        let phase = random.gen_range(0.0..std::f32::consts::TAU);
+        let size = random.gen_range(config.size_min..=config.size_max);
+        let opacity = random.gen_range(config.opacity_min..=config.opacity_max);
+        let layer = random.gen_range(0..config.depth_layers.max(1)) as usize;
+        let sprite = (sprite_count > 0).then(|| random.gen_range(0..sprite_count));
+
         Self {
             x,
             y,
-            time_alive: 0.0,
             tumbling_multiplier,
             time_to_live,
             falling_speed,
+            phase,
+            size,
+            opacity,
+            layer,
+            sprite,
         }
     }
+}
 
-    pub fn tick<'a>(
+/// All snowflakes of a renderer, stored as one array per property.
+///
+/// Updating and batching the flakes walks over tightly packed arrays, which keeps tens of
+/// thousands of flakes cheap.
+#[derive(Default)]
+struct Snowflakes {
+    x: Vec<f32>,
+    y: Vec<f32>,
+    time_alive: Vec<f32>,
+    tumbling_multiplier: Vec<f32>,
+    time_to_live: Vec<f32>,
+    falling_speed: Vec<f32>,
+    phase: Vec<f32>,
+    size: Vec<f32>,
+    opacity: Vec<f32>,
+    layer: Vec<u8>,
+    sprite: Vec<Option<usize>>,
+}
+
+impl Snowflakes {
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Adds or removes flakes until the given count is reached.
+    pub fn resize(&mut self, count: usize, mut create: impl FnMut() -> Snowflake) {
+        if count < self.len() {
+            self.x.truncate(count);
+            self.y.truncate(count);
+            self.time_alive.truncate(count);
+            self.tumbling_multiplier.truncate(count);
+            self.time_to_live.truncate(count);
+            self.falling_speed.truncate(count);
+            self.phase.truncate(count);
+            self.size.truncate(count);
+            self.opacity.truncate(count);
+            self.layer.truncate(count);
+            self.sprite.truncate(count);
+        }
+
+        while self.len() < count {
+            let flake = create();
+
+            self.x.push(flake.x);
+            self.y.push(flake.y);
+            self.time_alive.push(0.0);
+            self.tumbling_multiplier.push(flake.tumbling_multiplier);
+            self.time_to_live.push(flake.time_to_live);
+            self.falling_speed.push(flake.falling_speed);
+            self.phase.push(flake.phase);
+            self.size.push(flake.size);
+            self.opacity.push(flake.opacity);
+            self.layer.push(flake.layer as u8);
+            self.sprite.push(flake.sprite);
+        }
+    }
+
+    /// Replaces the flake at the given index.
+    fn replace(&mut self, i: usize, flake: Snowflake) {
+        self.x[i] = flake.x;
+        self.y[i] = flake.y;
+        self.time_alive[i] = 0.0;
+        self.tumbling_multiplier[i] = flake.tumbling_multiplier;
+        self.time_to_live[i] = flake.time_to_live;
+        self.falling_speed[i] = flake.falling_speed;
+        self.phase[i] = flake.phase;
+        self.size[i] = flake.size;
+        self.opacity[i] = flake.opacity;
+        self.layer[i] = flake.layer as u8;
+        self.sprite[i] = flake.sprite;
+    }
+
+    /// Moves all flakes and replaces the ones which died, left the scene or settled.
+    pub fn update<'a>(
         &mut self,
-        data: &mut SceneData<'a>,
+        data: &SceneData<'a>,
         config: &SnowModuleConfig,
+        wind: f32,
+        sprite_count: usize,
+        cover: &mut SnowCover,
         random: &mut ThreadRng,
     ) {
-        let mut paint = Paint::new(
-            Color4f::new(1.0, 1.0, 1.0, self.calculate_opacity(config)),
-            None,
-        );
-        paint.set_anti_alias(true);
-
         let delta = data.delta().as_millis() as f32;
+        let steps = delta / 20.0;
 
-        let tumble = (self.time_alive / 1000.0).sin() * self.tumbling_multiplier * (delta / 20.0);
-        let fall = self.falling_speed * (delta / 20.0);
+        let layers = config.depth_layers.max(1) as usize;
+        let right = (data.width() + 10) as f32;
+        let bottom = (data.height() + 10) as f32;
+        let pointer = data.pointer_position();
 
-        self.x += tumble;
-        self.y += fall;
+        for i in 0..self.len() {
+            let layer = self.layer[i] as usize;
+            let (scale, _) = config.layer_properties(layer);
 
-        data.canvas().draw_circle((self.x, self.y), 2.5, &paint);
+            let tumble = (self.time_alive[i] / 1000.0 + self.phase[i]).sin()
+                * self.tumbling_multiplier[i]
+                * steps;
+            let fall = self.falling_speed[i] * steps;
 
-        self.time_alive += delta;
+            let push = config
+                .cursor
+                .velocity(pointer, Point::new(self.x[i], self.y[i]));
 
-        if self.time_alive > self.time_to_live
-            || self.x < -10.0
-            || self.x > (data.width() + 10) as f32
-            || self.y > (data.height() + 10) as f32
-        {
-            self.reset(data, config, random);
+            let previous_y = self.y[i];
+            self.x[i] += (tumble + wind * steps) * scale + push.x * delta / 1000.0;
+            self.y[i] += fall * scale + push.y * delta / 1000.0;
+
+            self.time_alive[i] += delta;
+
+            // Distant flakes fall behind the covered surfaces
+            let settled = layer == 0 && {
+                let amount = std::f32::consts::PI * self.size[i] * self.size[i]
+                    / COVER_COLUMN_WIDTH
+                    * config.accumulation_rate;
+
+                cover.settle(config, self.x[i], previous_y, self.y[i], amount)
+            };
+
+            if settled
+                || self.time_alive[i] > self.time_to_live[i]
+                || layer >= layers
+                || self.x[i] < -10.0
+                || self.x[i] > right
+                || self.y[i] > bottom
+            {
+                self.replace(i, Snowflake::new_random(data, config, sprite_count, random));
+            }
         }
     }
 
-    fn calculate_opacity(&self, config: &SnowModuleConfig) -> f32 {
-        f32::max(
+    /// Calculates the opacity of a flake including fading in and out.
+    fn opacity(&self, i: usize, fade_time: f32) -> f32 {
+        let fade = f32::max(
             0.0,
             f32::min(
-                f32::min(self.time_alive, self.time_to_live - self.time_alive),
-                config.fade_time,
+                f32::min(
+                    self.time_alive[i],
+                    self.time_to_live[i] - self.time_alive[i],
+                ),
+                fade_time,
             ),
-        ) / config.fade_time
+        ) / fade_time;
+
+        self.opacity[i] * fade
+    }
+}
+
+/// Amount of distinct flake sizes per layer, each drawn in a separate batch.
+const SIZE_BUCKETS: usize = 4;
+
+/// Amount of distinct opacity levels flakes are rounded to, each drawn in a separate batch.
+const OPACITY_BUCKETS: usize = 16;
+
+/// The configuration values the batch paints have been created for.
+#[derive(PartialEq)]
+struct BatchKey {
+    color: Color4f,
+    layers: usize,
+    depth_scale: f32,
+    depth_blur: f32,
+}
+
+/// Groups flakes by layer, size and opacity, so every group is drawn in a single call.
+///
+/// The buffers and paints are kept between frames, rendering does not allocate unless the
+/// configuration changes.
+struct SnowBatches {
+    key: Option<BatchKey>,
+
+    /// One paint per layer with the blur of the layer applied.
+    paints: Vec<Paint>,
+
+    /// Paint for sprites, which are not blurred.
+    sprite_paint: Paint,
+
+    cover_paint: Paint,
+
+    /// Flake positions indexed by layer, size and opacity bucket.
+    points: Vec<Vec<Point>>,
+
+    /// Triangles of the sprite flakes indexed by layer and sprite.
+    sprite_triangles: Vec<SpriteTriangles>,
+}
+
+/// Two triangles per flake, textured with the whole sprite image.
+#[derive(Default)]
+struct SpriteTriangles {
+    positions: Vec<Point>,
+    texs: Vec<Point>,
+    colors: Vec<Color>,
+}
+
+impl SpriteTriangles {
+    fn clear(&mut self) {
+        self.positions.clear();
+        self.texs.clear();
+        self.colors.clear();
+    }
+
+    fn push(&mut self, bounds: Rect, image: &Image, opacity: f32) {
+        let corners = [
+            (bounds.left, bounds.top, 0.0, 0.0),
+            (bounds.right, bounds.top, 1.0, 0.0),
+            (bounds.right, bounds.bottom, 1.0, 1.0),
+            (bounds.left, bounds.bottom, 0.0, 1.0),
+        ];
+
+        let color = Color::from_argb((opacity * 255.0).round() as u8, 255, 255, 255);
+        for corner in [0, 1, 2, 0, 2, 3] {
+            let (x, y, u, v) = corners[corner];
+
+            self.positions.push(Point::new(x, y));
+            self.texs.push(Point::new(
+                u * image.width() as f32,
+                v * image.height() as f32,
+            ));
+            self.colors.push(color);
+        }
+    }
+}
+
+impl SnowBatches {
+    pub fn new() -> Self {
+        Self {
+            key: None,
+            paints: Vec::new(),
+            sprite_paint: Paint::default(),
+            cover_paint: Paint::default(),
+            points: Vec::new(),
+            sprite_triangles: Vec::new(),
+        }
+    }
+
+    fn index(layer: usize, size: usize, opacity: usize) -> usize {
+        (layer * SIZE_BUCKETS + size) * OPACITY_BUCKETS + opacity
+    }
+
+    /// Recreates the paints if the configuration changed.
+    pub fn prepare(&mut self, config: &SnowModuleConfig) {
+        let key = BatchKey {
+            color: config.color.get_color(),
+            layers: config.depth_layers.max(1) as usize,
+            depth_scale: config.depth_scale,
+            depth_blur: config.depth_blur,
+        };
+
+        if self.key.as_ref() == Some(&key) {
+            return;
+        }
+
+        self.paints = (0..key.layers)
+            .map(|layer| {
+                let (_, blur) = config.layer_properties(layer);
+
+                let mut paint = Paint::new(key.color, None);
+                paint.set_anti_alias(true);
+                paint.set_stroke_cap(Cap::Round);
+
+                if blur > 0.0 {
+                    paint.set_mask_filter(MaskFilter::blur(BlurStyle::Normal, blur, None));
+                }
+
+                paint
+            })
+            .collect();
+
+        self.sprite_paint = Paint::new(key.color, None);
+        self.sprite_paint.set_anti_alias(true);
+
+        self.cover_paint = Paint::new(key.color, None);
+        self.cover_paint.set_anti_alias(true);
+
+        self.points
+            .resize_with(Self::index(key.layers, 0, 0), Vec::new);
+        self.key = Some(key);
     }
 
-    fn reset<'a>(
+    pub fn draw<'a>(
         &mut self,
-        data: &SceneData<'a>,
+        flakes: &Snowflakes,
+        sprites: &[(String, Option<Image>)],
         config: &SnowModuleConfig,
-        random: &mut ThreadRng,
+        data: &mut SceneData<'a>,
     ) {
-        *self = Self::new_random(data, config, random);
+        let layers = self.paints.len();
+        let alpha = config.color.get_color().a;
+        let size_range = config.size_max - config.size_min;
+
+        let sprite_image = |i: usize| {
+            let sprite = flakes.sprite[i]?;
+            sprites.get(sprite)?.1.as_ref().map(|image| (sprite, image))
+        };
+
+        for points in &mut self.points {
+            points.clear();
+        }
+
+        self.sprite_triangles
+            .resize_with(layers * sprites.len(), SpriteTriangles::default);
+        for triangles in &mut self.sprite_triangles {
+            triangles.clear();
+        }
+
+        for i in 0..flakes.len() {
+            let layer = flakes.layer[i] as usize;
+            let opacity = flakes.opacity(i, config.fade_time);
+            let level = (opacity * (OPACITY_BUCKETS - 1) as f32).round();
+
+            if layer >= layers || level <= 0.0 {
+                continue;
+            }
+
+            if let Some((sprite, image)) = sprite_image(i) {
+                let (scale, _) = config.layer_properties(layer);
+                let radius = flakes.size[i] * scale;
+                let bounds = Rect::from_xywh(
+                    flakes.x[i] - radius,
+                    flakes.y[i] - radius,
+                    radius * 2.0,
+                    radius * 2.0,
+                );
+
+                self.sprite_triangles[layer * sprites.len() + sprite].push(bounds, image, opacity);
+                continue;
+            }
+
+            let size = if size_range > 0.0 {
+                let relative = (flakes.size[i] - config.size_min) / size_range;
+                ((relative * SIZE_BUCKETS as f32) as usize).min(SIZE_BUCKETS - 1)
+            } else {
+                0
+            };
+
+            let opacity = (level as usize).min(OPACITY_BUCKETS - 1);
+            self.points[Self::index(layer, size, opacity)]
+                .push(Point::new(flakes.x[i], flakes.y[i]));
+        }
+
+        // Draw the distant layers first so closer flakes cover them
+        for layer in (0..layers).rev() {
+            let (scale, _) = config.layer_properties(layer);
+            let paint = &mut self.paints[layer];
+
+            for size in 0..SIZE_BUCKETS {
+                let radius =
+                    config.size_min + size_range * (size as f32 + 0.5) / SIZE_BUCKETS as f32;
+                paint.set_stroke_width(radius * 2.0 * scale);
+
+                for opacity in 1..OPACITY_BUCKETS {
+                    let points = &self.points[Self::index(layer, size, opacity)];
+
+                    if points.is_empty() {
+                        continue;
+                    }
+
+                    paint.set_alpha_f(alpha * opacity as f32 / (OPACITY_BUCKETS - 1) as f32);
+                    data.canvas().draw_points(PointMode::Points, points, paint);
+                }
+            }
+
+            for (sprite, (_, image)) in sprites.iter().enumerate() {
+                let triangles = &self.sprite_triangles[layer * sprites.len() + sprite];
+                let image = match image {
+                    Some(v) if !triangles.positions.is_empty() => v,
+                    _ => continue,
+                };
+
+                let vertices = Vertices::new_copy(
+                    VertexMode::Triangles,
+                    &triangles.positions,
+                    &triangles.texs,
+                    &triangles.colors,
+                    None,
+                );
+
+                // The vertex colors carry the opacity of every flake, modulating the sprite
+                self.sprite_paint.set_alpha_f(alpha);
+                self.sprite_paint.set_shader(image.to_shader(
+                    None,
+                    SamplingOptions::default(),
+                    None,
+                ));
+                data.canvas()
+                    .draw_vertices(&vertices, BlendMode::Modulate, &self.sprite_paint);
+            }
+        }
+
+        self.sprite_paint.set_shader(None);
+    }
+}
+
+/// A height map of snow resting on a surface.
+struct SnowPile {
+    left: f32,
+
+    /// The y coordinate of the surface below every column.
+    base: Vec<f32>,
+    heights: Vec<f32>,
+
+    /// Whether snow slides off at the left and right end.
+    open_edges: bool,
+
+    /// The outline of the snow, kept to reuse its memory.
+    path: Path,
+}
+
+impl SnowPile {
+    pub fn new(left: f32, base: Vec<f32>, open_edges: bool) -> Self {
+        Self {
+            left,
+            heights: vec![0.0; base.len()],
+            base,
+            open_edges,
+            path: Path::new(),
+        }
+    }
+
+    /// Creates a pile resting on a flat surface.
+    pub fn flat(left: f32, y: f32, width: f32, open_edges: bool) -> Self {
+        let columns = (width / COVER_COLUMN_WIDTH).ceil().max(1.0) as usize;
+
+        Self::new(left, vec![y; columns], open_edges)
+    }
+
+    fn column(&self, x: f32) -> Option<usize> {
+        if x < self.left {
+            return None;
+        }
+
+        let column = ((x - self.left) / COVER_COLUMN_WIDTH) as usize;
+        (column < self.heights.len()).then(|| column)
+    }
+
+    /// The y coordinate of the top of the snow in the column containing x.
+    pub fn surface_at(&self, x: f32) -> Option<f32> {
+        self.column(x).map(|i| self.base[i] - self.heights[i])
+    }
+
+    /// Adds snow around x, spread over the neighbouring columns.
+    pub fn deposit(&mut self, x: f32, amount: f32, max_height: f32) {
+        let column = match self.column(x) {
+            None => return,
+            Some(v) => v,
+        };
+
+        let start = column.saturating_sub(1);
+        let end = (column + 1).min(self.heights.len() - 1);
+
+        for i in start..=end {
+            let share = if i == column { 0.5 } else { 0.25 };
+            self.heights[i] = (self.heights[i] + amount * share).min(max_height);
+        }
+    }
+
+    /// Melts the snow and lets it slide down where it is piled up too steeply.
+    pub fn update(&mut self, melted: f32) {
+        for height in &mut self.heights {
+            *height = (*height - melted).max(0.0);
+        }
+
+        for i in 1..self.heights.len() {
+            // Columns resting on different surfaces are separated by a wall
+            if self.base[i - 1] != self.base[i] {
+                continue;
+            }
+
+            let difference = self.heights[i - 1] - self.heights[i];
+
+            if difference.abs() > COVER_MAX_SLOPE {
+                let transfer = (difference.abs() - COVER_MAX_SLOPE) / 2.0 * difference.signum();
+
+                self.heights[i - 1] -= transfer;
+                self.heights[i] += transfer;
+            }
+        }
+
+        if self.open_edges {
+            for i in [0, self.heights.len() - 1] {
+                self.heights[i] = self.heights[i].min(COVER_MAX_SLOPE);
+            }
+        }
+    }
+
+    pub fn draw<'a>(&mut self, data: &mut SceneData<'a>, paint: &Paint) {
+        let path = &mut self.path;
+        path.reset();
+
+        let mut in_run = false;
+
+        for (i, (base, height)) in self.base.iter().zip(self.heights.iter()).enumerate() {
+            let x = self.left + i as f32 * COVER_COLUMN_WIDTH;
+            let continues = in_run && self.base[i - 1] == *base;
+
+            if *height < 0.5 {
+                if in_run {
+                    path.line_to((x, self.base[i - 1]));
+                    path.close();
+                    in_run = false;
+                }
+
+                continue;
+            }
+
+            if !continues {
+                if in_run {
+                    path.line_to((x, self.base[i - 1]));
+                    path.close();
+                }
+
+                path.move_to((x, *base));
+                in_run = true;
+            }
+
+            path.line_to((x + COVER_COLUMN_WIDTH / 2.0, base - height));
+        }
+
+        if in_run {
+            let right = self.left + self.heights.len() as f32 * COVER_COLUMN_WIDTH;
+
+            path.line_to((right, self.base[self.base.len() - 1]));
+            path.close();
+        }
+
+        if !path.is_empty() {
+            data.canvas().draw_path(path, paint);
+        }
+    }
+}
+
+/// Snow accumulated at the bottom of the displays and on top of windows.
+struct SnowCover {
+    /// The pile at the bottom of the scene.
+    ground: Option<SnowPile>,
+
+    /// The display areas and scene size the ground has been created for.
+    ground_areas: Vec<IRect>,
+    ground_size: (i32, i32),
+
+    /// Buffer for collecting the current display areas.
+    display_areas: Vec<IRect>,
+
+    /// Piles on windows, ordered like the windows from bottom to top.
+    windows: Vec<(IRect, SnowPile)>,
+}
+
+impl SnowCover {
+    pub fn new() -> Self {
+        Self {
+            ground: None,
+            ground_areas: Vec::new(),
+            ground_size: (0, 0),
+            display_areas: Vec::new(),
+            windows: Vec::new(),
+        }
+    }
+
+    /// Adjusts the piles to the current displays and windows and melts them.
+    pub fn update<'a>(&mut self, config: &SnowModuleConfig, data: &mut SceneData<'a>) {
+        if config.accumulate {
+            self.display_areas.clear();
+            self.display_areas.extend(
+                data.displays()
+                    .map(|display| display.rect().round())
+                    .filter(|area| !area.is_empty()),
+            );
+            self.display_areas.sort_by_key(|area| (area.left, area.top));
+
+            let size = (data.width(), data.height());
+            let outdated = self.ground.is_none()
+                || self.ground_size != size
+                || self.ground_areas != self.display_areas;
+
+            if outdated {
+                self.ground = Some(Self::create_ground(&self.display_areas, size.0, size.1));
+                self.ground_areas.clone_from(&self.display_areas);
+                self.ground_size = size;
+            }
+        } else {
+            self.ground = None;
+        }
+
+        if config.accumulate_on_windows {
+            data.request_windows();
+        }
+
+        let windows = data.windows().iter().filter(|area| !area.is_empty());
+        let windows_changed = !self
+            .windows
+            .iter()
+            .map(|(area, _)| area)
+            .eq(windows.clone());
+
+        if config.accumulate_on_windows && windows_changed {
+            // Piles disappear once their window moves or is resized
+            let mut previous = std::mem::take(&mut self.windows);
+
+            self.windows = windows
+                .map(|area| {
+                    let pile = match previous.iter().position(|(other, _)| other == area) {
+                        Some(i) => previous.swap_remove(i).1,
+                        None => {
+                            SnowPile::flat(area.left as _, area.top as _, area.width() as _, true)
+                        }
+                    };
+
+                    (*area, pile)
+                })
+                .collect();
+        } else if !config.accumulate_on_windows {
+            self.windows.clear();
+        }
+
+        let melted = config.melting_speed * data.delta().as_secs_f32();
+
+        let windows = self.windows.iter_mut().map(|(_, pile)| pile);
+
+        for pile in self.ground.iter_mut().chain(windows) {
+            pile.update(melted);
+        }
+    }
+
+    /// Creates the ground pile, every column rests on the lowest display edge below it.
+    fn create_ground(displays: &[IRect], width: i32, height: i32) -> SnowPile {
+        let columns = (width as f32 / COVER_COLUMN_WIDTH).ceil() as usize;
+
+        let base = (0..columns)
+            .map(|i| {
+                let x = ((i as f32 + 0.5) * COVER_COLUMN_WIDTH) as i32;
+
+                displays
+                    .iter()
+                    .filter(|area| area.left <= x && x < area.right)
+                    .map(|area| area.bottom)
+                    .max()
+                    .unwrap_or(height) as f32
+            })
+            .collect();
+
+        SnowPile::new(0.0, base, false)
+    }
+
+    /// Lets a flake which moved from previous_y to y settle on a surface it hit.
+    ///
+    /// Returns whether the flake has settled.
+    pub fn settle(
+        &mut self,
+        config: &SnowModuleConfig,
+        x: f32,
+        previous_y: f32,
+        y: f32,
+        amount: f32,
+    ) -> bool {
+        // The top most window is hit first
+        for i in (0..self.windows.len()).rev() {
+            let (lower, higher) = self.windows.split_at_mut(i + 1);
+            let pile = &mut lower[i].1;
+
+            let surface = match pile.surface_at(x) {
+                Some(v) if previous_y < v && y >= v => v,
+                _ => continue,
+            };
+
+            // Windows above hide the surface
+            let hidden = higher.iter().any(|(area, _)| {
+                x >= area.left as f32
+                    && x < area.right as f32
+                    && surface >= area.top as f32
+                    && surface < area.bottom as f32
+            });
+
+            if !hidden {
+                pile.deposit(x, amount, config.max_accumulation);
+                return true;
+            }
+        }
+
+        if let Some(ground) = &mut self.ground {
+            // Like on windows, only flakes crossing the surface land on it
+            let crossed = ground
+                .surface_at(x)
+                .map_or(false, |surface| previous_y < surface && y >= surface);
+
+            if crossed {
+                ground.deposit(x, amount, config.max_accumulation);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn draw<'a>(&mut self, data: &mut SceneData<'a>, paint: &Paint) {
+        let windows = self.windows.iter_mut().map(|(_, pile)| pile);
+
+        for pile in self.ground.iter_mut().chain(windows) {
+            pile.draw(data, paint);
+        }
     }
 }
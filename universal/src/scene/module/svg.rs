@@ -0,0 +1,429 @@
+use imgui::{InputText, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use serde::{Deserialize, Serialize};
+use skia_safe::canvas::SaveLayerRec;
+use skia_safe::svg::Dom;
+use skia_safe::{color_filters, BlendMode, Color, Paint, Size};
+
+use crate::scene::module::part::{ColorSetting, ModulePosition, ScaleSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+
+/// Size used for documents which neither specify a size nor a view box.
+const DEFAULT_DOCUMENT_SIZE: f32 = 256.0;
+
+pub(super) struct SvgModule;
+
+impl Module for SvgModule {
+    type Config = SvgModuleConfig;
+    type Renderer = SvgModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        SvgModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "SVG".into()
+    }
+}
+
+/// Override for a CSS-like `var(--name)` reference in the document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SvgVariable {
+    name: String,
+    color: ColorSetting,
+}
+
+impl Default for SvgVariable {
+    fn default() -> Self {
+        Self {
+            name: String::from("fill"),
+            color: ColorSetting::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SvgModuleConfig {
+    position: ModulePosition,
+    scale: ScaleSetting,
+    path: String,
+    tint_enabled: bool,
+    tint: ColorSetting,
+    variables: Vec<SvgVariable>,
+}
+
+impl Default for SvgModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            scale: Default::default(),
+            path: "".into(),
+            tint_enabled: false,
+            tint: ColorSetting::default(),
+            variables: Vec::new(),
+        }
+    }
+}
+
+impl ModuleConfig for SvgModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+            self.scale.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            if let Some(_tok) = ui.begin_table("Module Options", 2) {
+                ui.table_next_row();
+                ui.table_next_column();
+
+                InputText::new(ui, "Path", &mut self.path).build();
+                ui.table_next_column();
+                if ui.small_button("...") {
+                    match FileDialog::new()
+                        .add_filter("SVG document", &["svg"])
+                        .show_open_single_file()
+                    {
+                        Ok(None) => {}
+                        Ok(Some(p)) => self.path = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+        }
+
+        if ui.collapsing_header("Tint", TreeNodeFlags::FRAMED) {
+            ui.checkbox("Enable tint", &mut self.tint_enabled);
+
+            if self.tint_enabled {
+                self.tint.represent(ui, ctx);
+            }
+        }
+
+        if ui.collapsing_header("Variables", TreeNodeFlags::FRAMED) {
+            let mut to_remove = None;
+
+            if let Some(_tok) = ui.begin_table("Variables", 3) {
+                for (i, variable) in self.variables.iter_mut().enumerate() {
+                    let _id = ui.push_id(i as i32);
+
+                    ui.table_next_row();
+                    ui.table_next_column();
+
+                    InputText::new(ui, "Name", &mut variable.name)
+                        .hint("Variable name without --")
+                        .build();
+
+                    ui.table_next_column();
+                    variable.color.represent(ui, ctx);
+
+                    ui.table_next_column();
+                    if ui.small_button("-") {
+                        to_remove = Some(i);
+                    }
+                }
+            }
+
+            if let Some(i) = to_remove {
+                self.variables.remove(i);
+            }
+
+            if ui.button("Add variable") {
+                self.variables.push(SvgVariable::default());
+            }
+        }
+    }
+}
+
+pub struct SvgModuleRenderer {
+    current_path: String,
+    current_source: Option<String>,
+    current_variables: Vec<(String, Color)>,
+    current_document: Option<(Dom, Size)>,
+}
+
+impl SvgModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            current_path: "".into(),
+            current_source: None,
+            current_variables: Vec::new(),
+            current_document: None,
+        }
+    }
+
+    /// Determines whether the variables of the configuration differ from the ones used to
+    /// build the current document.
+    fn variables_changed(&self, config: &SvgModuleConfig) -> bool {
+        self.current_variables.len() != config.variables.len()
+            || self
+                .current_variables
+                .iter()
+                .zip(config.variables.iter())
+                .any(|((name, color), variable)| {
+                    *name != variable.name || *color != variable.color.get_color().to_color()
+                })
+    }
+
+    /// Re-reads the source file if the configured path has changed.
+    fn reload_source(&mut self, config: &SvgModuleConfig) -> bool {
+        if self.current_path == config.path {
+            return false;
+        }
+
+        self.current_path = config.path.clone();
+        self.current_source = None;
+
+        if self.current_path.is_empty() {
+            return true;
+        }
+
+        match std::fs::read_to_string(&self.current_path) {
+            Ok(source) => self.current_source = Some(source),
+            Err(err) => log::error!(
+                "Failed to read SVG document from \"{}\": {}",
+                self.current_path,
+                err
+            ),
+        }
+
+        true
+    }
+
+    /// Parses the current source into a document with the variables substituted.
+    fn rebuild_document(&mut self, config: &SvgModuleConfig) {
+        self.current_variables = config
+            .variables
+            .iter()
+            .map(|v| (v.name.clone(), v.color.get_color().to_color()))
+            .collect();
+
+        self.current_document = None;
+
+        let source = match &self.current_source {
+            None => return,
+            Some(v) => substitute_variables(v, &self.current_variables),
+        };
+
+        let size = intrinsic_size(&source);
+
+        match Dom::from_bytes(source.as_bytes()) {
+            Ok(mut dom) => {
+                dom.set_container_size(size);
+                self.current_document = Some((dom, size));
+            }
+            Err(err) => log::error!(
+                "Failed to parse SVG document from \"{}\": {:?}",
+                self.current_path,
+                err
+            ),
+        }
+    }
+}
+
+impl ModuleRenderer for SvgModuleRenderer {
+    type Config = SvgModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        if self.reload_source(config) || self.variables_changed(config) {
+            self.rebuild_document(config);
+        }
+
+        let (dom, size) = match &self.current_document {
+            None => return,
+            Some(v) => v,
+        };
+
+        let (available_width, available_height) = match config.position.available_size(data) {
+            None => return,
+            Some(v) => v,
+        };
+
+        let (width, height) =
+            config
+                .scale
+                .compute_size(size.width, size.height, available_width, available_height);
+
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        if let Some((x, y)) = config
+            .position
+            .compute_position(data, width as i32, height as i32)
+        {
+            let canvas = data.canvas();
+            canvas.save();
+            canvas.translate((x as f32, y as f32));
+            canvas.scale((width / size.width, height / size.height));
+
+            if config.tint_enabled {
+                let mut paint = Paint::default();
+                paint.set_color_filter(color_filters::blend(
+                    config.tint.get_color().to_color(),
+                    BlendMode::SrcIn,
+                ));
+
+                canvas.save_layer(&SaveLayerRec::default().paint(&paint));
+                dom.render(canvas);
+                canvas.restore();
+            } else {
+                dom.render(canvas);
+            }
+
+            canvas.restore();
+        }
+    }
+}
+
+/// Replaces all `var(--name)` and `var(--name, fallback)` references in the source.
+///
+/// Variables without an override are replaced with their fallback, or if none is given,
+/// with the value of a `--name: value` declaration found in the document.
+fn substitute_variables(source: &str, overrides: &[(String, Color)]) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find("var(") {
+        out.push_str(&rest[..start]);
+
+        let after = &rest[start + 4..];
+        let end = match closing_parenthesis(after) {
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+            Some(v) => v,
+        };
+
+        let (name, fallback) = match after[..end].split_once(',') {
+            None => (after[..end].trim(), None),
+            Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+        };
+        let name = name.trim_start_matches("--");
+
+        let value = overrides
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, color)| format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b()))
+            .or_else(|| fallback.map(String::from))
+            .or_else(|| find_declaration(source, name));
+
+        match value {
+            None => out.push_str(&rest[start..start + 4 + end + 1]),
+            Some(value) => out.push_str(&value),
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Finds the parenthesis closing the one opened right before the text, skipping nested
+/// pairs such as the ones of a `rgb(...)` fallback.
+fn closing_parenthesis(text: &str) -> Option<usize> {
+    let mut depth = 0;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Looks up the value of a `--name: value` declaration.
+fn find_declaration(source: &str, name: &str) -> Option<String> {
+    let needle = format!("--{}:", name);
+    let start = source.find(&needle)? + needle.len();
+    let value = &source[start..];
+    let end = value.find(|c| c == ';' || c == '}' || c == '"' || c == '\'')?;
+
+    Some(value[..end].trim().to_string())
+}
+
+/// Determines the size of the document from the attributes of its root element.
+fn intrinsic_size(source: &str) -> Size {
+    let root = source
+        .find("<svg")
+        .and_then(|start| {
+            let element = &source[start + 4..];
+            element.find('>').map(|end| &element[..end])
+        })
+        .unwrap_or("");
+
+    let width = find_attribute(root, "width").and_then(parse_length);
+    let height = find_attribute(root, "height").and_then(parse_length);
+
+    let view_box = find_attribute(root, "viewBox").and_then(|v| {
+        let values = v
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(str::parse::<f32>)
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+
+        match values.as_slice() {
+            [_, _, w, h] => Some((*w, *h)),
+            _ => None,
+        }
+    });
+
+    match (width, height, view_box) {
+        (Some(w), Some(h), _) => Size::new(w, h),
+        (Some(w), None, Some((vw, vh))) if vw > 0.0 => Size::new(w, w * vh / vw),
+        (None, Some(h), Some((vw, vh))) if vh > 0.0 => Size::new(h * vw / vh, h),
+        (_, _, Some((vw, vh))) => Size::new(vw, vh),
+        _ => Size::new(DEFAULT_DOCUMENT_SIZE, DEFAULT_DOCUMENT_SIZE),
+    }
+}
+
+/// Finds the value of an attribute in the attribute list of an element.
+fn find_attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = element;
+
+    while let Some(index) = rest.find(name) {
+        let preceded_by_space = rest[..index]
+            .chars()
+            .last()
+            .map_or(true, char::is_whitespace);
+        let after = rest[index + name.len()..].trim_start();
+
+        if preceded_by_space {
+            if let Some(after) = after.strip_prefix('=') {
+                let after = after.trim_start();
+                let quote = after.chars().next()?;
+
+                if quote == '"' || quote == '\'' {
+                    let value = &after[1..];
+                    return value.find(quote).map(|end| &value[..end]);
+                }
+            }
+        }
+
+        rest = &rest[index + name.len()..];
+    }
+
+    None
+}
+
+/// Parses an absolute length, percentages are not considered absolute.
+fn parse_length(value: &str) -> Option<f32> {
+    let value = value.trim();
+
+    if value.ends_with('%') {
+        return None;
+    }
+
+    value.trim_end_matches("px").trim().parse().ok()
+}
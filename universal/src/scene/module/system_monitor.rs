@@ -0,0 +1,648 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::time::Duration;
+
+use imgui::{Drag, InputText, SliderFlags, TreeNodeFlags, Ui};
+use serde::{Deserialize, Serialize};
+use skia_safe::{canvas::PointMode, scalar, Point, Rect};
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::util::{Sampler, SystemProbe, SystemSnapshot};
+
+pub(super) struct SystemMonitorModule;
+
+impl Module for SystemMonitorModule {
+    type Config = SystemMonitorModuleConfig;
+    type Renderer = SystemMonitorModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        SystemMonitorModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "System monitor".into()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Metric {
+    Cpu,
+    CpuCore(i32),
+    Memory,
+    Swap,
+    LoadAverage,
+
+    /// Usage of the file system mounted at the given path.
+    Disk(String),
+
+    /// Received bytes per second on the given interface.
+    NetworkReceive(String),
+
+    /// Transmitted bytes per second on the given interface.
+    NetworkTransmit(String),
+
+    /// Temperature of the given sensor, or the first sensor if empty.
+    Temperature(String),
+}
+
+impl Metric {
+    pub const fn ordinal(&self) -> usize {
+        match self {
+            Metric::Cpu => 0,
+            Metric::CpuCore(_) => 1,
+            Metric::Memory => 2,
+            Metric::Swap => 3,
+            Metric::LoadAverage => 4,
+            Metric::Disk(_) => 5,
+            Metric::NetworkReceive(_) => 6,
+            Metric::NetworkTransmit(_) => 7,
+            Metric::Temperature(_) => 8,
+        }
+    }
+
+    pub fn from_ordinal(ordinal: usize) -> Self {
+        match ordinal {
+            0 => Metric::Cpu,
+            1 => Metric::CpuCore(0),
+            2 => Metric::Memory,
+            3 => Metric::Swap,
+            4 => Metric::LoadAverage,
+            5 => Metric::Disk(String::from("/")),
+            6 => Metric::NetworkReceive(String::new()),
+            7 => Metric::NetworkTransmit(String::new()),
+            8 => Metric::Temperature(String::new()),
+            _ => panic!("Invalid metric ordinal"),
+        }
+    }
+
+    pub const fn type_names() -> [&'static str; 9] {
+        [
+            "CPU",
+            "CPU core",
+            "Memory",
+            "Swap",
+            "Load average",
+            "Disk",
+            "Network receive",
+            "Network transmit",
+            "Temperature",
+        ]
+    }
+
+    /// Writes the label used when no custom label is set.
+    fn default_label(&self, out: &mut String) {
+        let _ = match self {
+            Metric::Cpu => write!(out, "CPU"),
+            Metric::CpuCore(core) => write!(out, "CPU {}", core),
+            Metric::Memory => write!(out, "Memory"),
+            Metric::Swap => write!(out, "Swap"),
+            Metric::LoadAverage => write!(out, "Load"),
+            Metric::Disk(mount_point) => write!(out, "{}", mount_point),
+            Metric::NetworkReceive(interface) => write!(out, "{} down", interface),
+            Metric::NetworkTransmit(interface) => write!(out, "{} up", interface),
+            Metric::Temperature(sensor) if sensor.is_empty() => write!(out, "Temperature"),
+            Metric::Temperature(sensor) => write!(out, "{}", sensor),
+        };
+    }
+
+    /// Looks up the value of this metric in a snapshot and formats it as text.
+    ///
+    /// Returns the value and, if the metric has a natural maximum, the maximum. Metrics
+    /// which are not available yield [`None`].
+    fn evaluate(&self, snapshot: &SystemSnapshot, out: &mut String) -> Option<(f32, Option<f32>)> {
+        let (value, maximum) = match self {
+            Metric::Cpu => {
+                write_percentage(out, snapshot.cpu_total);
+                (snapshot.cpu_total, Some(1.0))
+            }
+            Metric::CpuCore(core) => {
+                let usage = *snapshot.cpu_cores.get(*core as usize)?;
+                write_percentage(out, usage);
+                (usage, Some(1.0))
+            }
+            Metric::Memory => {
+                write_usage(out, snapshot.memory.used, snapshot.memory.total);
+                (snapshot.memory.fraction(), Some(1.0))
+            }
+            Metric::Swap => {
+                write_usage(out, snapshot.swap.used, snapshot.swap.total);
+                (snapshot.swap.fraction(), Some(1.0))
+            }
+            Metric::LoadAverage => {
+                let [one, five, fifteen] = snapshot.load_average;
+                let _ = write!(out, "{:.2} {:.2} {:.2}", one, five, fifteen);
+
+                let cores = snapshot.cpu_cores.len().max(1) as f32;
+                (one, Some(cores))
+            }
+            Metric::Disk(mount_point) => {
+                let disk = snapshot
+                    .disks
+                    .iter()
+                    .find(|disk| &disk.mount_point == mount_point)?;
+
+                write_usage(out, disk.usage.used, disk.usage.total);
+                (disk.usage.fraction(), Some(1.0))
+            }
+            Metric::NetworkReceive(interface) | Metric::NetworkTransmit(interface) => {
+                let network = snapshot
+                    .networks
+                    .iter()
+                    .find(|network| &network.interface == interface)?;
+
+                let rate = match self {
+                    Metric::NetworkReceive(_) => network.receive,
+                    _ => network.transmit,
+                };
+
+                write_bytes(out, rate);
+                out.push_str("/s");
+                (rate as f32, None)
+            }
+            Metric::Temperature(sensor) => {
+                let temperature = snapshot
+                    .temperatures
+                    .iter()
+                    .find(|temperature| sensor.is_empty() || &temperature.sensor == sensor)?;
+
+                let _ = write!(out, "{:.1} °C", temperature.celsius);
+                (temperature.celsius, Some(100.0))
+            }
+        };
+
+        Some((value, maximum))
+    }
+}
+
+impl Default for Metric {
+    fn default() -> Self {
+        Self::Cpu
+    }
+}
+
+impl ModuleConfig for Metric {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current_type_ordinal = self.ordinal();
+        if ui.combo_simple_string(
+            "Metric",
+            &mut current_type_ordinal,
+            Metric::type_names().as_slice(),
+        ) {
+            *self = Metric::from_ordinal(current_type_ordinal);
+        }
+
+        match self {
+            Metric::CpuCore(core) => {
+                Drag::new("Core")
+                    .range(0, 1024)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, core);
+            }
+            Metric::Disk(mount_point) => {
+                InputText::new(ui, "Mount point", mount_point)
+                    .hint("/")
+                    .build();
+            }
+            Metric::NetworkReceive(interface) | Metric::NetworkTransmit(interface) => {
+                InputText::new(ui, "Interface", interface)
+                    .hint("For example eth0 or wlan0")
+                    .build();
+            }
+            Metric::Temperature(sensor) => {
+                InputText::new(ui, "Sensor", sensor)
+                    .hint("First sensor, or for example coretemp Package id 0")
+                    .build();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_percentage(out: &mut String, fraction: f32) {
+    let _ = write!(out, "{:.0}%", fraction * 100.0);
+}
+
+/// Writes an amount of bytes using binary prefixes.
+fn write_bytes(out: &mut String, bytes: f64) {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    let mut value = bytes;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    let _ = if unit == 0 {
+        write!(out, "{:.0} {}", value, UNITS[unit])
+    } else {
+        write!(out, "{:.1} {}", value, UNITS[unit])
+    };
+}
+
+fn write_usage(out: &mut String, used: u64, total: u64) {
+    write_bytes(out, used as f64);
+    out.push_str(" / ");
+    write_bytes(out, total as f64);
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MetricDisplay {
+    Text,
+    Bar,
+    Sparkline,
+}
+
+impl MetricDisplay {
+    const VALUES: [Self; 3] = [Self::Text, Self::Bar, Self::Sparkline];
+}
+
+impl Default for MetricDisplay {
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+impl ModuleConfig for MetricDisplay {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Display", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Text => "Text",
+                Self::Bar => "Bar",
+                Self::Sparkline => "Sparkline",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current].clone();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricEntry {
+    metric: Metric,
+    display: MetricDisplay,
+
+    /// Custom label, the default label of the metric is used if empty.
+    label: String,
+
+    /// Paint used for bars and sparklines.
+    paint: PaintSetting,
+}
+
+impl MetricEntry {
+    fn new(metric: Metric, display: MetricDisplay) -> Self {
+        Self {
+            metric,
+            display,
+            label: String::new(),
+            paint: PaintSetting::default(),
+        }
+    }
+}
+
+impl Default for MetricEntry {
+    fn default() -> Self {
+        Self::new(Metric::default(), MetricDisplay::default())
+    }
+}
+
+impl ModuleConfig for MetricEntry {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        self.metric.represent(ui, ctx);
+        self.display.represent(ui, ctx);
+
+        InputText::new(ui, "Label", &mut self.label)
+            .hint("Default label")
+            .build();
+
+        if self.display != MetricDisplay::Text {
+            self.paint.represent(ui, ctx);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemMonitorModuleConfig {
+    position: ModulePosition,
+
+    /// Time between two samples, in milliseconds.
+    interval: i32,
+
+    /// Amount of samples displayed by sparklines.
+    history_length: i32,
+
+    /// Width of bars and sparklines.
+    width: f32,
+    bar_height: f32,
+    sparkline_height: f32,
+    spacing: f32,
+
+    metrics: Vec<MetricEntry>,
+
+    font: FontSetting,
+    paint: PaintSetting,
+
+    /// Paint used for the background of bars and sparklines.
+    track_paint: PaintSetting,
+}
+
+impl Default for SystemMonitorModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            interval: 1000,
+            history_length: 60,
+            width: 300.0,
+            bar_height: 8.0,
+            sparkline_height: 40.0,
+            spacing: 8.0,
+            metrics: vec![
+                MetricEntry::new(Metric::Cpu, MetricDisplay::Sparkline),
+                MetricEntry::new(Metric::Memory, MetricDisplay::Bar),
+                MetricEntry::new(Metric::Swap, MetricDisplay::Bar),
+                MetricEntry::new(Metric::LoadAverage, MetricDisplay::Text),
+                MetricEntry::new(Metric::Disk(String::from("/")), MetricDisplay::Bar),
+            ],
+            font: FontSetting::default(),
+            paint: PaintSetting::default(),
+            track_paint: PaintSetting::from_rgba(1.0, 1.0, 1.0, 0.2),
+        }
+    }
+}
+
+impl ModuleConfig for SystemMonitorModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Color", TreeNodeFlags::FRAMED) {
+            self.paint.represent(ui, ctx);
+
+            ui.separator();
+            ui.text("Bar and sparkline background");
+
+            let _id = ui.push_id("track");
+            self.track_paint.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            Drag::new("Interval (ms)")
+                .range(100, 60 * 60 * 1000)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.interval);
+
+            Drag::new("History length")
+                .range(2, 1000)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.history_length);
+
+            Drag::new("Width")
+                .range(1.0, 10000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.width);
+
+            Drag::new("Bar height")
+                .range(1.0, 1000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.bar_height);
+
+            Drag::new("Sparkline height")
+                .range(1.0, 1000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.sparkline_height);
+
+            Drag::new("Spacing")
+                .range(0.0, 1000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.spacing);
+        }
+
+        if ui.collapsing_header("Metrics", TreeNodeFlags::FRAMED) {
+            let mut to_remove = None;
+
+            for (i, entry) in self.metrics.iter_mut().enumerate() {
+                let _id = ui.push_id(i as i32);
+
+                entry.represent(ui, ctx);
+
+                if ui.button("Remove metric") {
+                    to_remove = Some(i);
+                }
+
+                ui.separator();
+            }
+
+            if let Some(i) = to_remove {
+                self.metrics.remove(i);
+            }
+
+            if ui.button("Add metric") {
+                self.metrics.push(MetricEntry::default());
+            }
+        }
+    }
+}
+
+pub struct SystemMonitorModuleRenderer {
+    sampler: Sampler<SystemSnapshot>,
+    snapshot: SystemSnapshot,
+    histories: HashMap<Metric, VecDeque<f32>>,
+    text: String,
+    points: Vec<Point>,
+}
+
+impl SystemMonitorModuleRenderer {
+    pub fn new() -> Self {
+        let mut probe = SystemProbe::new();
+
+        Self {
+            sampler: Sampler::spawn("System monitor", Duration::from_secs(1), move || {
+                probe.sample()
+            }),
+            snapshot: SystemSnapshot::default(),
+            histories: HashMap::new(),
+            text: String::new(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Records the values of a new snapshot in the histories.
+    fn record(&mut self, config: &SystemMonitorModuleConfig, snapshot: SystemSnapshot) {
+        let length = config.history_length.max(2) as usize;
+
+        self.histories
+            .retain(|metric, _| config.metrics.iter().any(|entry| &entry.metric == metric));
+
+        for (index, entry) in config.metrics.iter().enumerate() {
+            // Entries displaying the same metric share a history, it only advances once
+            let duplicate = config.metrics[..index]
+                .iter()
+                .any(|other| other.metric == entry.metric);
+            if duplicate {
+                continue;
+            }
+
+            self.text.clear();
+
+            if let Some((value, _)) = entry.metric.evaluate(&snapshot, &mut self.text) {
+                let history = self.histories.entry(entry.metric.clone()).or_default();
+
+                history.push_back(value);
+                while history.len() > length {
+                    history.pop_front();
+                }
+            }
+        }
+
+        self.snapshot = snapshot;
+    }
+
+    fn entry_height(
+        config: &SystemMonitorModuleConfig,
+        entry: &MetricEntry,
+        line_height: scalar,
+    ) -> scalar {
+        match entry.display {
+            MetricDisplay::Text => line_height,
+            MetricDisplay::Bar => line_height + config.bar_height,
+            MetricDisplay::Sparkline => line_height + config.sparkline_height,
+        }
+    }
+}
+
+impl ModuleRenderer for SystemMonitorModuleRenderer {
+    type Config = SystemMonitorModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        self.sampler
+            .set_interval(Duration::from_millis(config.interval.max(100) as u64));
+
+        if let Some(snapshot) = self.sampler.take() {
+            self.record(config, snapshot);
+        }
+
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+
+        let height = config
+            .metrics
+            .iter()
+            .map(|entry| Self::entry_height(config, entry, line_height) + config.spacing)
+            .sum::<scalar>()
+            - config.spacing;
+
+        let (x, mut y) = match config.position.compute_position(
+            data,
+            config.width as i32,
+            height.max(0.0) as i32,
+        ) {
+            None => return,
+            Some((x, y)) => (x as scalar, y as scalar),
+        };
+
+        let canvas = data.canvas();
+
+        for entry in &config.metrics {
+            self.text.clear();
+
+            if entry.label.is_empty() {
+                entry.metric.default_label(&mut self.text);
+            } else {
+                self.text.push_str(&entry.label);
+            }
+            self.text.push_str(": ");
+
+            let evaluated = entry.metric.evaluate(&self.snapshot, &mut self.text);
+            if evaluated.is_none() {
+                self.text.push_str("n/a");
+            }
+
+            font.draw_str(
+                canvas,
+                &self.text,
+                (x, y - metrics.ascent),
+                config.paint.get_paint(),
+            );
+
+            let graph_top = y + line_height;
+            let history = self.histories.get(&entry.metric);
+
+            // Metrics without a natural maximum are scaled to the largest recorded value
+            let maximum = match evaluated {
+                Some((_, Some(maximum))) => maximum,
+                _ => history
+                    .map(|history| history.iter().fold(0.0, |a: f32, b| a.max(*b)))
+                    .unwrap_or(0.0),
+            };
+
+            let fraction = |value: f32| {
+                if maximum > 0.0 {
+                    (value / maximum).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            };
+
+            match entry.display {
+                MetricDisplay::Text => {}
+                MetricDisplay::Bar => {
+                    let track = Rect::from_xywh(x, graph_top, config.width, config.bar_height);
+                    canvas.draw_rect(track, config.track_paint.get_paint());
+
+                    if let Some((value, _)) = evaluated {
+                        let filled = Rect::from_xywh(
+                            x,
+                            graph_top,
+                            config.width * fraction(value),
+                            config.bar_height,
+                        );
+                        canvas.draw_rect(filled, entry.paint.get_paint());
+                    }
+                }
+                MetricDisplay::Sparkline => {
+                    let track =
+                        Rect::from_xywh(x, graph_top, config.width, config.sparkline_height);
+                    canvas.draw_rect(track, config.track_paint.get_paint());
+
+                    if let Some(history) = history.filter(|history| history.len() >= 2) {
+                        let length = config.history_length.max(2) as usize;
+                        let step = config.width / (length - 1) as scalar;
+
+                        // The history is only trimmed once the next sample is recorded
+                        let skipped = history.len().saturating_sub(length);
+                        let offset = length.saturating_sub(history.len());
+
+                        self.points.clear();
+                        self.points
+                            .extend(history.iter().skip(skipped).enumerate().map(|(i, value)| {
+                                Point::new(
+                                    x + (offset + i) as scalar * step,
+                                    track.bottom - fraction(*value) * config.sparkline_height,
+                                )
+                            }));
+
+                        canvas.draw_points(
+                            PointMode::Polygon,
+                            &self.points,
+                            entry.paint.get_paint(),
+                        );
+                    }
+                }
+            }
+
+            y += Self::entry_height(config, entry, line_height) + config.spacing;
+        }
+    }
+}
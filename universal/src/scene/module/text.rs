@@ -1,11 +1,22 @@
-use imgui::{InputText, TreeNodeFlags};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use imgui::{Drag, InputTextMultiline, SliderFlags, TreeNodeFlags};
 use serde::{Deserialize, Serialize};
-use skia_safe::Point;
+use skia_safe::font_style::{Slant, Weight};
+use skia_safe::textlayout::{
+    Paragraph, ParagraphBuilder, ParagraphStyle, TextAlign, TextDecoration, TextDirection,
+    TextStyle,
+};
+use skia_safe::{scalar, Color, FontStyle, Paint};
 
 use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
 use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
 use crate::scene::SceneData;
 use crate::ui::context::Context;
+use crate::util::{
+    expand_template, has_placeholders, Sampler, SendCell, TemplateContext, TEMPLATE_HELP,
+};
 
 pub(super) struct TextModule;
 
@@ -14,7 +25,7 @@ impl Module for TextModule {
     type Renderer = TextModuleRenderer;
 
     fn create_renderer() -> Self::Renderer {
-        TextModuleRenderer
+        TextModuleRenderer::new()
     }
 
     fn name() -> String {
@@ -22,10 +33,98 @@ impl Module for TextModule {
     }
 }
 
+const MARKUP_HELP: &str = "Markup:
+[b]bold[/b] [i]italic[/i] [u]underlined[/u]
+[color=#ff8800]colored[/color], optionally with alpha as #rrggbbaa
+Tags can be nested, write [[ for a literal [
+Bold and italic need a font with those variants, such as a system font.";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TextAlignment {
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+impl TextAlignment {
+    const VALUES: [Self; 4] = [Self::Left, Self::Center, Self::Right, Self::Justify];
+
+    fn to_skia(self) -> TextAlign {
+        match self {
+            TextAlignment::Left => TextAlign::Left,
+            TextAlignment::Center => TextAlign::Center,
+            TextAlignment::Right => TextAlign::Right,
+            TextAlignment::Justify => TextAlign::Justify,
+        }
+    }
+}
+
+impl Default for TextAlignment {
+    fn default() -> Self {
+        Self::Left
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum BaseDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl BaseDirection {
+    const VALUES: [Self; 2] = [Self::LeftToRight, Self::RightToLeft];
+
+    fn to_skia(self) -> TextDirection {
+        match self {
+            BaseDirection::LeftToRight => TextDirection::LTR,
+            BaseDirection::RightToLeft => TextDirection::RTL,
+        }
+    }
+}
+
+impl Default for BaseDirection {
+    fn default() -> Self {
+        Self::LeftToRight
+    }
+}
+
+fn default_line_spacing() -> f32 {
+    1.0
+}
+
+fn default_template_interval() -> f32 {
+    1.0
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextModuleConfig {
     position: ModulePosition,
     value: String,
+
+    /// Time between two evaluations of the placeholders in the value, in seconds.
+    #[serde(default = "default_template_interval")]
+    template_interval: f32,
+
+    /// Whether the value is parsed as markup, see [`MARKUP_HELP`].
+    #[serde(default)]
+    markup: bool,
+
+    /// Width at which lines are wrapped, 0 disables wrapping.
+    #[serde(default)]
+    max_width: f32,
+
+    #[serde(default)]
+    alignment: TextAlignment,
+
+    /// Direction of paragraphs, mixed scripts are still laid out bidirectionally.
+    #[serde(default)]
+    direction: BaseDirection,
+
+    /// Line height as a multiple of the font size.
+    #[serde(default = "default_line_spacing")]
+    line_spacing: f32,
+
     paint: PaintSetting,
     font: FontSetting,
 }
@@ -35,6 +134,12 @@ impl Default for TextModuleConfig {
         Self {
             position: Default::default(),
             value: String::from("Custom text"),
+            template_interval: default_template_interval(),
+            markup: false,
+            max_width: 0.0,
+            alignment: TextAlignment::default(),
+            direction: BaseDirection::default(),
+            line_spacing: default_line_spacing(),
             paint: PaintSetting::default(),
             font: FontSetting::default(),
         }
@@ -51,36 +156,490 @@ impl ModuleConfig for TextModuleConfig {
             self.paint.represent(ui, ctx);
         }
 
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
         if ui.collapsing_header("Text", TreeNodeFlags::FRAMED) {
-            InputText::new(ui, "Value", &mut self.value).build();
+            InputTextMultiline::new(ui, "Value", &mut self.value, [0.0, 120.0]).build();
+            if ui.is_item_hovered() {
+                ui.tooltip_text(TEMPLATE_HELP);
+            }
+
+            if has_placeholders(&self.value) {
+                Drag::new("Update interval (s)")
+                    .range(0.1, 60.0 * 60.0)
+                    .speed(0.1)
+                    .flags(SliderFlags::ALWAYS_CLAMP)
+                    .build(ui, &mut self.template_interval);
+            }
+
+            ui.checkbox("Markup", &mut self.markup);
+            if ui.is_item_hovered() {
+                ui.tooltip_text(MARKUP_HELP);
+            }
+        }
+
+        if ui.collapsing_header("Layout", TreeNodeFlags::FRAMED) {
+            Drag::new("Maximum width")
+                .range(0.0, 100000.0)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.max_width);
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Lines longer than this are wrapped, 0 disables wrapping");
+            }
+
+            let mut current = TextAlignment::VALUES
+                .iter()
+                .position(|v| *v == self.alignment)
+                .unwrap();
+
+            ui.combo("Alignment", &mut current, &TextAlignment::VALUES, |v| {
+                match v {
+                    TextAlignment::Left => "Left",
+                    TextAlignment::Center => "Center",
+                    TextAlignment::Right => "Right",
+                    TextAlignment::Justify => "Justify",
+                }
+                .into()
+            });
+
+            self.alignment = TextAlignment::VALUES[current];
+
+            let mut current = BaseDirection::VALUES
+                .iter()
+                .position(|v| *v == self.direction)
+                .unwrap();
+
+            ui.combo("Direction", &mut current, &BaseDirection::VALUES, |v| {
+                match v {
+                    BaseDirection::LeftToRight => "Left to right",
+                    BaseDirection::RightToLeft => "Right to left",
+                }
+                .into()
+            });
+
+            self.direction = BaseDirection::VALUES[current];
+
+            Drag::new("Line spacing")
+                .range(0.1, 10.0)
+                .speed(0.01)
+                .flags(SliderFlags::ALWAYS_CLAMP)
+                .build(ui, &mut self.line_spacing);
+        }
+    }
+}
+
+/// Style of a piece of marked up text.
+#[derive(Debug, Clone, Default)]
+struct SpanStyle {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    color: Option<Color>,
+}
+
+impl SpanStyle {
+    /// Applies a markup tag such as `b` or `color=#ffffff`, unknown tags yield [`None`].
+    fn with_tag(&self, tag: &str) -> Option<Self> {
+        let mut style = self.clone();
+
+        match tag {
+            "b" => style.bold = true,
+            "i" => style.italic = true,
+            "u" => style.underline = true,
+            _ => style.color = Some(parse_color(tag.strip_prefix("color=")?)?),
+        }
+
+        Some(style)
+    }
+
+    fn apply(&self, base: &TextStyle, paint: &Paint) -> TextStyle {
+        let mut style = base.clone();
+        let font_style = base.font_style();
+
+        if self.bold || self.italic {
+            let weight = if self.bold {
+                Weight::BOLD
+            } else {
+                font_style.weight()
+            };
+
+            let slant = if self.italic {
+                Slant::Italic
+            } else {
+                font_style.slant()
+            };
+
+            style.set_font_style(FontStyle::new(weight, font_style.width(), slant));
+        }
+
+        if self.underline {
+            style.set_decoration_type(TextDecoration::UNDERLINE);
+        }
+
+        if let Some(color) = self.color {
+            let mut paint = paint.clone();
+            paint.set_color(color);
+            style.set_foreground_color(paint);
         }
+
+        style
+    }
+}
+
+/// Parses `#rrggbb` or `#rrggbbaa`.
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok();
+
+    match hex.len() {
+        6 => Some(Color::from_rgb(channel(0)?, channel(1)?, channel(2)?)),
+        8 => Some(Color::from_argb(
+            channel(3)?,
+            channel(0)?,
+            channel(1)?,
+            channel(2)?,
+        )),
+        _ => None,
     }
 }
 
-pub struct TextModuleRenderer;
+/// Splits marked up text into pieces of equal style.
+///
+/// Malformed tags, such as closing tags which don't match the innermost open tag, are kept
+/// as literal text.
+fn parse_markup(source: &str) -> Vec<(SpanStyle, String)> {
+    let mut spans = Vec::new();
+    let mut stack: Vec<(&str, SpanStyle)> = Vec::new();
+    let mut text = String::new();
+    let mut rest = source;
+
+    let current = |stack: &[(&str, SpanStyle)]| {
+        stack
+            .last()
+            .map_or_else(SpanStyle::default, |(_, style)| style.clone())
+    };
+
+    while let Some(start) = rest.find('[') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(escaped) = rest.strip_prefix("[[") {
+            text.push('[');
+            rest = escaped;
+            continue;
+        }
+
+        let end = match rest.find(']') {
+            None => break,
+            Some(v) => v,
+        };
+
+        let tag = &rest[1..end];
+        let name = tag.split('=').next().unwrap_or_default();
+
+        let changed = if let Some(closing) = tag.strip_prefix('/') {
+            match stack.last() {
+                Some((open, _)) if *open == closing => {
+                    let style = current(&stack);
+                    stack.pop();
+                    Some(style)
+                }
+                _ => None,
+            }
+        } else {
+            match current(&stack).with_tag(tag) {
+                Some(style) => {
+                    let previous = current(&stack);
+                    stack.push((name, style));
+                    Some(previous)
+                }
+                None => None,
+            }
+        };
+
+        match changed {
+            Some(style) => {
+                if !text.is_empty() {
+                    spans.push((style, std::mem::take(&mut text)));
+                }
+            }
+            None => text.push_str(&rest[..=end]),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        spans.push((current(&stack), text));
+    }
+
+    spans
+}
+
+/// Everything the layout depends on, the paragraph is built again when any of this changes.
+#[derive(Debug)]
+struct LayoutKey {
+    value: String,
+    markup: bool,
+    max_width: f32,
+    alignment: TextAlignment,
+    direction: BaseDirection,
+    line_spacing: f32,
+    font: u64,
+    paint: Paint,
+}
+
+impl LayoutKey {
+    fn new(config: &TextModuleConfig, text: &str) -> Self {
+        Self {
+            value: text.to_string(),
+            markup: config.markup,
+            max_width: config.max_width,
+            alignment: config.alignment,
+            direction: config.direction,
+            line_spacing: config.line_spacing,
+            font: config.font.cache_key(),
+            paint: config.paint.get_paint().clone(),
+        }
+    }
+
+    fn matches(&self, config: &TextModuleConfig, text: &str) -> bool {
+        self.value == text
+            && self.markup == config.markup
+            && self.max_width == config.max_width
+            && self.alignment == config.alignment
+            && self.direction == config.direction
+            && self.line_spacing == config.line_spacing
+            && self.font == config.font.cache_key()
+            && self.paint == *config.paint.get_paint()
+    }
+}
+
+/// A paragraph which has been laid out.
+struct LaidOutText {
+    key: LayoutKey,
+    paragraph: Paragraph,
+    width: scalar,
+
+    /// Distance from the top of the paragraph to the baseline of its last line.
+    baseline: scalar,
+}
+
+/// Expands the placeholders of the value, on a background thread while it has any.
+struct TemplateExpansion {
+    template: String,
+    has_placeholders: bool,
+    context: TemplateContext,
+    expanded: String,
+
+    /// Keeps the values of the placeholders up to date, the template and context are shared
+    /// with its thread.
+    sampler: Option<(Arc<Mutex<(String, TemplateContext)>>, Sampler<String>)>,
+}
+
+impl TemplateExpansion {
+    fn new() -> Self {
+        Self {
+            template: String::new(),
+            has_placeholders: false,
+            context: TemplateContext::default(),
+            expanded: String::new(),
+            sampler: None,
+        }
+    }
+
+    /// Picks up the latest expansion of the value.
+    ///
+    /// Whenever the value or context changes, it is expanded right away so that the text is
+    /// never blank or outdated until the sampler catches up.
+    fn update(
+        &mut self,
+        config: &TextModuleConfig,
+        context: impl FnOnce() -> TemplateContext,
+    ) -> &str {
+        let template_changed = self.template != config.value;
+        if template_changed {
+            self.template.clone_from(&config.value);
+            self.has_placeholders = has_placeholders(&self.template);
+        }
+
+        if !self.has_placeholders {
+            // Stops the sampler thread, escaped braces still need to be expanded once
+            self.sampler = None;
+
+            if template_changed {
+                self.expanded.clear();
+                expand_template(&self.template, &self.context, &mut self.expanded);
+            }
+
+            return &self.expanded;
+        }
+
+        let context = context();
+
+        if template_changed || self.context != context || self.sampler.is_none() {
+            self.context = context;
+            self.expanded.clear();
+            expand_template(&self.template, &self.context, &mut self.expanded);
+
+            let input = (self.template.clone(), self.context.clone());
+
+            match &self.sampler {
+                Some((shared, sampler)) => {
+                    *shared.lock().unwrap() = input;
+
+                    // Drops samples of the previous template
+                    sampler.take();
+                    sampler.trigger();
+                }
+                None => {
+                    let shared = Arc::new(Mutex::new(input));
+                    let sampler_shared = shared.clone();
+
+                    let sampler =
+                        Sampler::spawn("Text template", Duration::from_secs(1), move || {
+                            let (template, context) = sampler_shared.lock().unwrap().clone();
+
+                            let mut expanded = String::new();
+                            expand_template(&template, &context, &mut expanded);
+
+                            expanded
+                        });
+
+                    self.sampler = Some((shared, sampler));
+                }
+            }
+        }
+
+        let (_, sampler) = self.sampler.as_ref().unwrap();
+        sampler.set_interval(Duration::from_secs_f32(config.template_interval.max(0.1)));
+
+        if let Some(expanded) = sampler.take() {
+            self.expanded = expanded;
+        }
+
+        &self.expanded
+    }
+}
+
+pub struct TextModuleRenderer {
+    laid_out: Option<SendCell<LaidOutText>>,
+    template: TemplateExpansion,
+}
+
+impl TextModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            laid_out: None,
+            template: TemplateExpansion::new(),
+        }
+    }
+
+    /// Collects the values of the placeholders only known while rendering.
+    fn template_context(config: &TextModuleConfig, data: &SceneData) -> TemplateContext {
+        let (display_width, display_height) =
+            config.position.available_size(data).unwrap_or_default();
+
+        TemplateContext {
+            display_name: config
+                .position
+                .display()
+                .display(data)
+                .map(|display| display.name().clone())
+                .unwrap_or_default(),
+            display_width,
+            display_height,
+        }
+    }
+
+    fn layout(config: &TextModuleConfig, text: &str) -> LaidOutText {
+        let paint = config.paint.get_paint();
+
+        let mut base = config.font.text_style();
+        base.set_foreground_color(paint.clone())
+            .set_height(config.line_spacing)
+            .set_height_override(true);
+
+        let mut paragraph_style = ParagraphStyle::new();
+        paragraph_style
+            .set_text_style(&base)
+            .set_text_align(config.alignment.to_skia())
+            .set_text_direction(config.direction.to_skia());
+
+        let mut builder = ParagraphBuilder::new(&paragraph_style, config.font.font_collection());
+
+        if config.markup {
+            for (style, span) in parse_markup(text) {
+                builder.push_style(&style.apply(&base, paint));
+                builder.add_text(span);
+                builder.pop();
+            }
+        } else {
+            builder.add_text(text);
+        }
+
+        let mut paragraph = builder.build();
+
+        let width = if config.max_width > 0.0 {
+            config.max_width
+        } else {
+            // Lay out without any limit first to find the width of the longest line
+            paragraph.layout(scalar::INFINITY);
+            paragraph.max_intrinsic_width().ceil()
+        };
+
+        paragraph.layout(width);
+
+        let baseline = paragraph
+            .get_line_metrics()
+            .last()
+            .map_or_else(|| paragraph.height(), |line| line.baseline as scalar);
+
+        LaidOutText {
+            key: LayoutKey::new(config, text),
+            paragraph,
+            width,
+            baseline,
+        }
+    }
+}
 
 impl ModuleRenderer for TextModuleRenderer {
     type Config = TextModuleConfig;
 
     fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
-        let (_, rect) = config
-            .font
-            .get_font()
-            .measure_str(&config.value, Some(config.paint.get_paint()));
+        let text = self
+            .template
+            .update(config, || Self::template_context(config, data));
+
+        let outdated = self
+            .laid_out
+            .as_ref()
+            .map_or(true, |laid_out| !laid_out.key.matches(config, text));
 
+        if outdated {
+            // The paragraph owns all of its data
+            self.laid_out = Some(unsafe { SendCell::new(Self::layout(config, text)) });
+        }
+
+        let laid_out = self.laid_out.as_ref().unwrap();
+
+        // The baseline of the last line is anchored, like single lines of text always were
         if let Some((x, y)) = config.position.compute_position_baselined(
             data,
-            rect.width() as i32,
-            rect.height() as i32,
+            laid_out.width as i32,
+            laid_out.baseline as i32,
         ) {
-            let canvas = data.canvas();
-
-            canvas.draw_str(
-                &config.value,
-                Point::new(x as _, y as _),
-                config.font.get_font(),
-                config.paint.get_paint(),
-            );
+            let top = y as scalar - laid_out.baseline;
+
+            laid_out.paragraph.paint(data.canvas(), (x as scalar, top));
         }
     }
 }
@@ -0,0 +1,566 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chrono::{Local, NaiveDate};
+use imgui::{InputText, TreeNodeFlags, Ui};
+use native_dialog::FileDialog;
+use serde::{Deserialize, Serialize};
+use skia_safe::{scalar, Paint};
+
+use crate::scene::module::part::{FontSetting, ModulePosition, PaintSetting};
+use crate::scene::module::{Module, ModuleConfig, ModuleRenderer};
+use crate::scene::SceneData;
+use crate::ui::context::Context;
+use crate::ui::ERROR_COLOR;
+use crate::util::{complete_task, read_tasks, Task, WatchedFile};
+
+pub(super) struct TodoModule;
+
+impl Module for TodoModule {
+    type Config = TodoModuleConfig;
+    type Renderer = TodoModuleRenderer;
+
+    fn create_renderer() -> Self::Renderer {
+        TodoModuleRenderer::new()
+    }
+
+    fn name() -> String {
+        "Todo".into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TodoSort {
+    Priority,
+    DueDate,
+    FileOrder,
+}
+
+impl TodoSort {
+    const VALUES: [Self; 3] = [Self::Priority, Self::DueDate, Self::FileOrder];
+}
+
+impl Default for TodoSort {
+    fn default() -> Self {
+        Self::Priority
+    }
+}
+
+impl ModuleConfig for TodoSort {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Sort by", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::Priority => "Priority",
+                Self::DueDate => "Due date",
+                Self::FileOrder => "Order in the file",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current];
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TodoGroup {
+    None,
+    Priority,
+    Project,
+    Context,
+}
+
+impl TodoGroup {
+    const VALUES: [Self; 4] = [Self::None, Self::Priority, Self::Project, Self::Context];
+
+    /// Names of the groups a task belongs to, [`None`] stands for the group of tasks
+    /// without any.
+    fn groups_of(&self, task: &Task) -> Vec<Option<String>> {
+        let names = match self {
+            Self::None => return vec![None],
+            Self::Priority => task
+                .priority
+                .map(|priority| format!("Priority {}", priority))
+                .into_iter()
+                .collect(),
+            Self::Project => task.projects.iter().map(|p| format!("+{}", p)).collect(),
+            Self::Context => task.contexts.iter().map(|c| format!("@{}", c)).collect(),
+        };
+
+        if names.is_empty() {
+            vec![None]
+        } else {
+            names.into_iter().map(Some).collect()
+        }
+    }
+
+    /// Name of the group of tasks without a priority, project or context.
+    fn ungrouped_name(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Priority => "No priority",
+            Self::Project => "No project",
+            Self::Context => "No context",
+        }
+    }
+}
+
+impl Default for TodoGroup {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl ModuleConfig for TodoGroup {
+    fn represent(&mut self, ui: &Ui, _ctx: &Context<'_>) {
+        let mut current = Self::VALUES.iter().position(|v| v == self).unwrap();
+
+        ui.combo("Group by", &mut current, &Self::VALUES, |v| {
+            match v {
+                Self::None => "Nothing",
+                Self::Priority => "Priority",
+                Self::Project => "Project",
+                Self::Context => "Context",
+            }
+            .into()
+        });
+
+        *self = Self::VALUES[current];
+    }
+}
+
+/// The tasks as loaded by the renderer, shared with the user interface so tasks can be
+/// checked off.
+#[derive(Debug, Default)]
+struct TodoList {
+    tasks: Vec<Task>,
+    error: Option<String>,
+
+    /// Changes whenever the tasks do.
+    revision: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoModuleConfig {
+    position: ModulePosition,
+
+    /// Path of the todo.txt file.
+    path: String,
+
+    sort: TodoSort,
+    group: TodoGroup,
+
+    /// Whether completed tasks are shown below the open ones.
+    show_done: bool,
+    show_due: bool,
+
+    paint: PaintSetting,
+
+    /// Paints of the tasks with priority A, B and C.
+    priority_paints: [PaintSetting; 3],
+    overdue_paint: PaintSetting,
+    done_paint: PaintSetting,
+    group_paint: PaintSetting,
+
+    font: FontSetting,
+
+    #[serde(skip)]
+    list: Arc<Mutex<TodoList>>,
+
+    /// Why the last task could not be checked off.
+    #[serde(skip)]
+    check_error: Option<String>,
+}
+
+impl TodoModuleConfig {
+    /// Checks off a task from the user interface by writing it back to the file.
+    ///
+    /// The renderer picks up the change when it reloads the file, until then the task is
+    /// only marked as done in the shared list.
+    fn complete(&mut self, task: &Task) {
+        let today = Local::today().naive_local();
+
+        match complete_task(Path::new(&self.path), task, today) {
+            Ok(()) => {
+                self.check_error = None;
+
+                let mut list = self.list.lock().unwrap();
+                if let Some(task) = list.tasks.iter_mut().find(|t| t.line == task.line) {
+                    task.done = true;
+                    task.completion_date = Some(today);
+                }
+
+                list.revision += 1;
+            }
+            Err(err) => {
+                self.check_error = Some(format!("Failed to check off task: {}", err));
+            }
+        }
+    }
+
+    fn represent_tasks(&mut self, ui: &Ui) {
+        let open = self
+            .list
+            .lock()
+            .unwrap()
+            .tasks
+            .iter()
+            .filter(|task| !task.done)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if open.is_empty() {
+            ui.text_disabled("No open tasks");
+        }
+
+        let mut completed = None;
+        for task in &open {
+            let _id = ui.push_id(task.line as i32);
+
+            let mut done = false;
+            if ui.checkbox(&task.description, &mut done) {
+                completed = Some(task);
+            }
+        }
+
+        if let Some(task) = completed {
+            self.complete(task);
+        }
+
+        if let Some(err) = &self.check_error {
+            ui.text_colored(ERROR_COLOR, err);
+        }
+    }
+}
+
+impl Default for TodoModuleConfig {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            path: String::new(),
+            sort: TodoSort::default(),
+            group: TodoGroup::default(),
+            show_done: false,
+            show_due: true,
+            paint: PaintSetting::default(),
+            priority_paints: [
+                PaintSetting::from_rgba(1.0, 0.45, 0.4, 1.0),
+                PaintSetting::from_rgba(1.0, 0.75, 0.35, 1.0),
+                PaintSetting::from_rgba(0.55, 0.8, 1.0, 1.0),
+            ],
+            overdue_paint: PaintSetting::from_rgba(1.0, 0.3, 0.3, 1.0),
+            done_paint: PaintSetting::from_rgba(1.0, 1.0, 1.0, 0.4),
+            group_paint: PaintSetting::from_rgba(1.0, 1.0, 1.0, 0.7),
+            font: FontSetting::default(),
+            list: Arc::new(Mutex::new(TodoList::default())),
+            check_error: None,
+        }
+    }
+}
+
+impl ModuleConfig for TodoModuleConfig {
+    fn represent(&mut self, ui: &Ui, ctx: &Context<'_>) {
+        if ui.collapsing_header("Position", TreeNodeFlags::FRAMED) {
+            self.position.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Font", TreeNodeFlags::FRAMED) {
+            self.font.represent(ui, ctx);
+        }
+
+        if ui.collapsing_header("Module", TreeNodeFlags::FRAMED) {
+            if let Some(_tok) = ui.begin_table("Module Options", 2) {
+                ui.table_next_row();
+                ui.table_next_column();
+
+                InputText::new(ui, "Path", &mut self.path).build();
+                ui.table_next_column();
+                if ui.small_button("...") {
+                    match FileDialog::new()
+                        .add_filter("todo.txt", &["txt"])
+                        .show_open_single_file()
+                    {
+                        Ok(None) => {}
+                        Ok(Some(p)) => self.path = p.to_string_lossy().into(),
+                        Err(err) => {
+                            log::error!("Failed to show a file dialog: {}", err)
+                        }
+                    };
+                }
+            }
+
+            if let Some(err) = &self.list.lock().unwrap().error {
+                ui.text_colored(ERROR_COLOR, err);
+            }
+
+            self.sort.represent(ui, ctx);
+            self.group.represent(ui, ctx);
+
+            ui.checkbox("Show completed tasks", &mut self.show_done);
+            ui.checkbox("Show due dates", &mut self.show_due);
+        }
+
+        if ui.collapsing_header("Colors", TreeNodeFlags::FRAMED) {
+            let [a, b, c] = &mut self.priority_paints;
+            let paints = [
+                ("Tasks", &mut self.paint),
+                ("Priority A", a),
+                ("Priority B", b),
+                ("Priority C", c),
+                ("Overdue tasks", &mut self.overdue_paint),
+                ("Completed tasks", &mut self.done_paint),
+                ("Group names", &mut self.group_paint),
+            ];
+
+            for (name, paint) in paints {
+                let _id = ui.push_id(name);
+
+                ui.text(name);
+                paint.represent(ui, ctx);
+                ui.separator();
+            }
+        }
+
+        if ui.collapsing_header("Tasks", TreeNodeFlags::FRAMED) {
+            self.represent_tasks(ui);
+        }
+    }
+}
+
+/// Which paint a line is drawn with.
+#[derive(Debug, Clone, Copy)]
+enum LineStyle {
+    Group,
+    Task {
+        priority: Option<char>,
+        overdue: bool,
+        done: bool,
+    },
+}
+
+struct TodoLine {
+    text: String,
+    style: LineStyle,
+    indented: bool,
+}
+
+/// Everything the lines depend on, they are created again when any of this changes.
+#[derive(Debug, PartialEq)]
+struct LinesKey {
+    revision: u64,
+    sort: TodoSort,
+    group: TodoGroup,
+    show_done: bool,
+    show_due: bool,
+    today: NaiveDate,
+}
+
+impl LinesKey {
+    fn new(config: &TodoModuleConfig, revision: u64, today: NaiveDate) -> Self {
+        Self {
+            revision,
+            sort: config.sort,
+            group: config.group,
+            show_done: config.show_done,
+            show_due: config.show_due,
+            today,
+        }
+    }
+}
+
+pub struct TodoModuleRenderer {
+    file: WatchedFile,
+    lines: Vec<TodoLine>,
+    lines_for: Option<LinesKey>,
+}
+
+impl TodoModuleRenderer {
+    pub fn new() -> Self {
+        Self {
+            file: WatchedFile::new(),
+            lines: Vec::new(),
+            lines_for: None,
+        }
+    }
+
+    /// Loads the file again if the path changed or the file has been modified.
+    fn update_file(&mut self, config: &TodoModuleConfig) {
+        if !self.file.update(&config.path) {
+            return;
+        }
+
+        let (tasks, error) = if config.path.is_empty() {
+            (Vec::new(), Some(String::from("No file selected")))
+        } else {
+            match read_tasks(Path::new(&config.path)) {
+                Ok(tasks) => (tasks, None),
+                Err(err) => (
+                    Vec::new(),
+                    Some(format!("Failed to read {}: {}", config.path, err)),
+                ),
+            }
+        };
+
+        let mut list = config.list.lock().unwrap();
+        list.tasks = tasks;
+        list.error = error;
+        list.revision += 1;
+    }
+
+    /// Sorts, filters and groups the tasks into the lines to draw.
+    fn build_lines(config: &TodoModuleConfig, tasks: &[Task], today: NaiveDate) -> Vec<TodoLine> {
+        let mut tasks = tasks
+            .iter()
+            .filter(|task| config.show_done || !task.done)
+            .collect::<Vec<_>>();
+
+        // Missing priorities and due dates sort last, completed tasks after all open ones
+        match config.sort {
+            TodoSort::Priority => tasks.sort_by_key(|task| {
+                (
+                    task.done,
+                    task.priority.is_none(),
+                    task.priority,
+                    task.due.is_none(),
+                    task.due,
+                    task.line,
+                )
+            }),
+            TodoSort::DueDate => tasks.sort_by_key(|task| {
+                (
+                    task.done,
+                    task.due.is_none(),
+                    task.due,
+                    task.priority.is_none(),
+                    task.priority,
+                    task.line,
+                )
+            }),
+            TodoSort::FileOrder => tasks.sort_by_key(|task| (task.done, task.line)),
+        }
+
+        let mut groups: BTreeMap<(bool, String), Vec<&Task>> = BTreeMap::new();
+        for task in tasks {
+            for name in config.group.groups_of(task) {
+                let key = (name.is_none(), name.unwrap_or_default());
+                groups.entry(key).or_default().push(task);
+            }
+        }
+
+        let grouped = config.group != TodoGroup::None;
+        let mut lines = Vec::new();
+
+        for ((ungrouped, name), tasks) in groups {
+            if grouped {
+                lines.push(TodoLine {
+                    text: if ungrouped {
+                        config.group.ungrouped_name().to_string()
+                    } else {
+                        name
+                    },
+                    style: LineStyle::Group,
+                    indented: false,
+                });
+            }
+
+            for task in tasks {
+                let mut text = String::new();
+
+                if let Some(priority) = task.priority {
+                    if config.group != TodoGroup::Priority {
+                        text.push_str(&format!("({}) ", priority));
+                    }
+                }
+
+                text.push_str(&task.description);
+
+                if let Some(due) = task.due.filter(|_| config.show_due) {
+                    text.push_str(&format!("  due {}", due));
+                }
+
+                lines.push(TodoLine {
+                    text,
+                    style: LineStyle::Task {
+                        priority: task.priority,
+                        overdue: task.is_overdue(today),
+                        done: task.done,
+                    },
+                    indented: grouped,
+                });
+            }
+        }
+
+        lines
+    }
+
+    fn paint_for<'a>(config: &'a TodoModuleConfig, style: LineStyle) -> &'a Paint {
+        let setting = match style {
+            LineStyle::Group => &config.group_paint,
+            LineStyle::Task { done: true, .. } => &config.done_paint,
+            LineStyle::Task { overdue: true, .. } => &config.overdue_paint,
+            LineStyle::Task {
+                priority: Some(priority @ 'A'..='C'),
+                ..
+            } => &config.priority_paints[(priority as u8 - b'A') as usize],
+            LineStyle::Task { .. } => &config.paint,
+        };
+
+        setting.get_paint()
+    }
+}
+
+impl ModuleRenderer for TodoModuleRenderer {
+    type Config = TodoModuleConfig;
+
+    fn render<'a>(&mut self, config: &Self::Config, data: &mut SceneData<'a>) {
+        self.update_file(config);
+
+        let today = Local::today().naive_local();
+        {
+            let list = config.list.lock().unwrap();
+            let key = LinesKey::new(config, list.revision, today);
+
+            if self.lines_for.as_ref() != Some(&key) {
+                self.lines = Self::build_lines(config, &list.tasks, today);
+                self.lines_for = Some(key);
+            }
+        }
+
+        if self.lines.is_empty() {
+            return;
+        }
+
+        let font = &config.font;
+        let (line_height, metrics) = font.get_font().metrics();
+        let indent = line_height;
+
+        let width = self
+            .lines
+            .iter()
+            .map(|line| {
+                let offset = if line.indented { indent } else { 0.0 };
+                offset + font.measure_str(&line.text, None).0
+            })
+            .fold(0.0, scalar::max);
+        let height = line_height * self.lines.len() as scalar;
+
+        let (x, y) = match config
+            .position
+            .compute_position(data, width as i32, height as i32)
+        {
+            None => return,
+            Some((x, y)) => (x as scalar, y as scalar),
+        };
+
+        let mut line_y = y - metrics.ascent;
+        for line in &self.lines {
+            let line_x = if line.indented { x + indent } else { x };
+            let paint = Self::paint_for(config, line.style);
+
+            font.draw_str(data.canvas(), &line.text, (line_x, line_y), paint);
+            line_y += line_height;
+        }
+    }
+}
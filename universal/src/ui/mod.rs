@@ -27,6 +27,9 @@ mod panel;
 /// The window is initially visible in debug builds to ease programming.
 const WINDOW_INITIALLY_VISIBLE: bool = cfg!(debug_assertions);
 
+/// Color used to display invalid settings and other errors in the user interface.
+pub const ERROR_COLOR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+
 /// Contains the contents of the user interface.
 pub struct SnowlandUI {
     panel: MainPanel,
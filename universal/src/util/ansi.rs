@@ -0,0 +1,155 @@
+use skia_safe::Color;
+
+/// Width tabs are expanded to, in spaces.
+const TAB_WIDTH: usize = 4;
+
+/// The 16 standard terminal colors, as used by xterm.
+const STANDARD_COLORS: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+/// A piece of terminal output drawn in a single color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnsiSpan {
+    /// The foreground color, [`None`] for the default color.
+    pub color: Option<Color>,
+    pub text: String,
+}
+
+/// Splits a line of terminal output into colored spans.
+///
+/// Colors are taken from SGR escape sequences, all other escape sequences and control
+/// characters are removed. The color is carried over from and to the next line, just like
+/// a terminal would.
+pub fn parse_ansi(line: &str, color: &mut Option<Color>, out: &mut Vec<AnsiSpan>) {
+    // Carriage returns are used to redraw lines, only the last version is of interest
+    let line = line.rsplit('\r').next().unwrap_or_default();
+
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut parameters = String::new();
+
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            if c == 'm' {
+                                push_span(out, *color, &mut text);
+                                apply_sgr(&parameters, color);
+                            }
+
+                            break;
+                        }
+
+                        parameters.push(c);
+                    }
+                }
+                Some(']') => {
+                    // Operating system commands end with a bell or a string terminator
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\t' => {
+                let column = out
+                    .iter()
+                    .map(|span| span.text.chars().count())
+                    .sum::<usize>()
+                    + text.chars().count();
+
+                text.extend(std::iter::repeat(' ').take(TAB_WIDTH - column % TAB_WIDTH));
+            }
+            c if c.is_control() => {}
+            c => text.push(c),
+        }
+    }
+
+    push_span(out, *color, &mut text);
+}
+
+fn push_span(out: &mut Vec<AnsiSpan>, color: Option<Color>, text: &mut String) {
+    if !text.is_empty() {
+        out.push(AnsiSpan {
+            color,
+            text: std::mem::take(text),
+        });
+    }
+}
+
+/// Applies the parameters of a "select graphic rendition" sequence to the color.
+fn apply_sgr(parameters: &str, color: &mut Option<Color>) {
+    let mut parameters = parameters
+        .split(';')
+        .map(|parameter| parameter.parse::<u8>().unwrap_or(0));
+
+    while let Some(parameter) = parameters.next() {
+        match parameter {
+            0 | 39 => *color = None,
+            30..=37 => *color = Some(standard_color(parameter - 30)),
+            90..=97 => *color = Some(standard_color(parameter - 90 + 8)),
+            38 => *color = extended_color(&mut parameters),
+            48 => {
+                // Background colors are not supported, but their arguments need to be skipped
+                extended_color(&mut parameters);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads the arguments of an extended color, either a 256 color index or RGB values.
+fn extended_color(parameters: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match parameters.next()? {
+        5 => Some(indexed_color(parameters.next()?)),
+        2 => Some(Color::from_rgb(
+            parameters.next()?,
+            parameters.next()?,
+            parameters.next()?,
+        )),
+        _ => None,
+    }
+}
+
+fn standard_color(index: u8) -> Color {
+    let (r, g, b) = STANDARD_COLORS[index as usize];
+    Color::from_rgb(r, g, b)
+}
+
+/// Looks up a color of the 256 color palette.
+fn indexed_color(index: u8) -> Color {
+    match index {
+        0..=15 => standard_color(index),
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+
+            Color::from_rgb(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            Color::from_rgb(gray, gray, gray)
+        }
+    }
+}
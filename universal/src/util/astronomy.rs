@@ -0,0 +1,254 @@
+//! Low precision positions of the sun and the moon.
+//!
+//! The formulas are accurate to roughly a minute of arc for the sun and a fraction of a
+//! degree for the moon, which is plenty for drawing a sky. Everything is computed locally.
+use std::f64::consts::PI;
+
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Julian date of the J2000 epoch.
+const J2000: f64 = 2451545.0;
+
+/// Julian date of the unix epoch.
+const UNIX_EPOCH_JULIAN: f64 = 2440587.5;
+
+/// Obliquity of the earth's axis in radians.
+const OBLIQUITY: f64 = 23.4397 * PI / 180.0;
+
+/// Length of a synodic month in days.
+const SYNODIC_MONTH: f64 = 29.530588853;
+
+/// Days since J2000 of a known new moon (2000-01-06 18:14 UTC).
+const KNOWN_NEW_MOON: f64 = 5.5972;
+
+/// Correction of the days since J2000 for the sun transit calculation.
+const TRANSIT_OFFSET: f64 = 0.0009;
+
+/// Altitude of the sun at sunrise and sunset, accounting for refraction and the sun's radius.
+const SUNRISE_ALTITUDE: f64 = -0.833;
+
+/// Altitude of the sun at the start of the morning and the end of the evening civil twilight.
+const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+
+/// The location of an observer on earth in degrees, north and east are positive.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Observer {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Position of a body on the sky as seen by an observer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HorizontalPosition {
+    /// Degrees above the horizon, negative if below.
+    pub altitude: f64,
+
+    /// Degrees clockwise from north.
+    pub azimuth: f64,
+}
+
+/// Times of the sun events of a day.
+///
+/// Events are [`None`] if they don't happen on that day, such as during the polar night.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SunTimes {
+    pub solar_noon: DateTime<Utc>,
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+    pub dawn: Option<DateTime<Utc>>,
+    pub dusk: Option<DateTime<Utc>>,
+
+    /// Whether the sun is above the horizon at solar noon.
+    pub sun_up_at_noon: bool,
+}
+
+impl SunTimes {
+    /// Calculates how long the sun is above the horizon.
+    pub fn day_length(&self) -> chrono::Duration {
+        match (self.sunrise, self.sunset) {
+            (Some(sunrise), Some(sunset)) => sunset - sunrise,
+            _ if self.sun_up_at_noon => chrono::Duration::days(1),
+            _ => chrono::Duration::zero(),
+        }
+    }
+
+    /// Calculates how much daylight is left at the given time.
+    pub fn daylight_left(&self, now: DateTime<Utc>) -> chrono::Duration {
+        match (self.sunrise, self.sunset) {
+            (Some(sunrise), Some(sunset)) if now < sunrise => sunset - sunrise,
+            (Some(_), Some(sunset)) if now < sunset => sunset - now,
+            (Some(_), Some(_)) => chrono::Duration::zero(),
+            _ => self.day_length(),
+        }
+    }
+}
+
+/// Equatorial coordinates in radians.
+struct Equatorial {
+    right_ascension: f64,
+    declination: f64,
+}
+
+/// Days since J2000 (fractional).
+fn days_since_j2000(time: DateTime<Utc>) -> f64 {
+    let seconds = time.timestamp() as f64 + time.timestamp_subsec_millis() as f64 / 1000.0;
+    seconds / 86400.0 + UNIX_EPOCH_JULIAN - J2000
+}
+
+fn from_days_since_j2000(days: f64) -> DateTime<Utc> {
+    let millis = ((days + J2000 - UNIX_EPOCH_JULIAN) * 86400000.0).round() as i64;
+    Utc.timestamp_millis(millis)
+}
+
+fn to_equatorial(longitude: f64, latitude: f64) -> Equatorial {
+    Equatorial {
+        right_ascension: (longitude.sin() * OBLIQUITY.cos() - latitude.tan() * OBLIQUITY.sin())
+            .atan2(longitude.cos()),
+        declination: (latitude.sin() * OBLIQUITY.cos()
+            + latitude.cos() * OBLIQUITY.sin() * longitude.sin())
+        .asin(),
+    }
+}
+
+fn solar_mean_anomaly(days: f64) -> f64 {
+    (357.5291 + 0.98560028 * days).to_radians()
+}
+
+fn ecliptic_longitude(mean_anomaly: f64) -> f64 {
+    let center = (1.9148 * mean_anomaly.sin()
+        + 0.02 * (2.0 * mean_anomaly).sin()
+        + 0.0003 * (3.0 * mean_anomaly).sin())
+    .to_radians();
+    let perihelion = 102.9372f64.to_radians();
+
+    mean_anomaly + center + perihelion + PI
+}
+
+fn sun_coordinates(days: f64) -> Equatorial {
+    to_equatorial(ecliptic_longitude(solar_mean_anomaly(days)), 0.0)
+}
+
+fn moon_coordinates(days: f64) -> Equatorial {
+    let mean_longitude = (218.316 + 13.176396 * days).to_radians();
+    let mean_anomaly = (134.963 + 13.064993 * days).to_radians();
+    let mean_distance = (93.272 + 13.229350 * days).to_radians();
+
+    let longitude = mean_longitude + 6.289f64.to_radians() * mean_anomaly.sin();
+    let latitude = 5.128f64.to_radians() * mean_distance.sin();
+
+    to_equatorial(longitude, latitude)
+}
+
+fn to_horizontal(observer: Observer, days: f64, coordinates: Equatorial) -> HorizontalPosition {
+    let latitude = observer.latitude.to_radians();
+    let sidereal_time =
+        (280.16 + 360.9856235 * days).to_radians() + observer.longitude.to_radians();
+    let hour_angle = sidereal_time - coordinates.right_ascension;
+    let declination = coordinates.declination;
+
+    let altitude = (latitude.sin() * declination.sin()
+        + latitude.cos() * declination.cos() * hour_angle.cos())
+    .asin();
+
+    // Measured from south, turned around to be measured from north
+    let azimuth = hour_angle
+        .sin()
+        .atan2(hour_angle.cos() * latitude.sin() - declination.tan() * latitude.cos())
+        + PI;
+
+    HorizontalPosition {
+        altitude: altitude.to_degrees(),
+        azimuth: azimuth.to_degrees().rem_euclid(360.0),
+    }
+}
+
+/// Calculates where the sun is at the given time.
+pub fn sun_position(observer: Observer, time: DateTime<Utc>) -> HorizontalPosition {
+    let days = days_since_j2000(time);
+    to_horizontal(observer, days, sun_coordinates(days))
+}
+
+/// Calculates where the moon is at the given time.
+pub fn moon_position(observer: Observer, time: DateTime<Utc>) -> HorizontalPosition {
+    let days = days_since_j2000(time);
+    to_horizontal(observer, days, moon_coordinates(days))
+}
+
+/// Calculates the moon phase at the given time.
+///
+/// The phase runs from 0.0 (new moon) over 0.5 (full moon) back to 1.0.
+pub fn moon_phase(time: DateTime<Utc>) -> f64 {
+    ((days_since_j2000(time) - KNOWN_NEW_MOON) / SYNODIC_MONTH).rem_euclid(1.0)
+}
+
+/// Calculates which fraction of the moon disk is lit for a given phase.
+pub fn moon_illumination(phase: f64) -> f64 {
+    (1.0 - (phase * 2.0 * PI).cos()) / 2.0
+}
+
+/// Calculates the sun events of the day the given time lies in.
+///
+/// The day is determined by the observer's longitude, so passing local noon is the safest
+/// way to get the events of a local day.
+pub fn sun_times(observer: Observer, time: DateTime<Utc>) -> SunTimes {
+    let west = -observer.longitude.to_radians();
+    let latitude = observer.latitude.to_radians();
+
+    let cycle = (days_since_j2000(time) - TRANSIT_OFFSET - west / (2.0 * PI)).round();
+    let approximate_transit = TRANSIT_OFFSET + west / (2.0 * PI) + cycle;
+
+    let mean_anomaly = solar_mean_anomaly(approximate_transit);
+    let longitude = ecliptic_longitude(mean_anomaly);
+    let declination = to_equatorial(longitude, 0.0).declination;
+
+    let equation_of_time = 0.0053 * mean_anomaly.sin() - 0.0069 * (2.0 * longitude).sin();
+    let noon = approximate_transit + equation_of_time;
+
+    // Returns the rise and set time for the sun reaching the given altitude
+    let events = |altitude: f64| {
+        let cos_hour_angle = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+            / (latitude.cos() * declination.cos());
+
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return (None, None);
+        }
+
+        let set =
+            TRANSIT_OFFSET + (cos_hour_angle.acos() + west) / (2.0 * PI) + cycle + equation_of_time;
+        let rise = noon - (set - noon);
+
+        (
+            Some(from_days_since_j2000(rise)),
+            Some(from_days_since_j2000(set)),
+        )
+    };
+
+    let (sunrise, sunset) = events(SUNRISE_ALTITUDE);
+    let (dawn, dusk) = events(CIVIL_TWILIGHT_ALTITUDE);
+
+    SunTimes {
+        solar_noon: from_days_since_j2000(noon),
+        sunrise,
+        sunset,
+        dawn,
+        dusk,
+        sun_up_at_noon: latitude.sin() * declination.sin() + latitude.cos() * declination.cos()
+            > SUNRISE_ALTITUDE.to_radians().sin(),
+    }
+}
+
+/// Names the moon phase, see [`moon_phase`].
+pub fn moon_phase_name(phase: f64) -> &'static str {
+    const NAMES: [&str; 8] = [
+        "New moon",
+        "Waxing crescent",
+        "First quarter",
+        "Waxing gibbous",
+        "Full moon",
+        "Waning gibbous",
+        "Last quarter",
+        "Waning crescent",
+    ];
+
+    NAMES[((phase * 8.0).round() as usize) % NAMES.len()]
+}
@@ -0,0 +1,335 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{JoinHandle, Thread};
+use std::time::{Duration, Instant};
+
+/// How often a running command is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Describes which command a [`CommandRunner`] runs and how.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandOptions {
+    /// Command line passed to the system shell.
+    pub command_line: String,
+
+    /// Whether output is published line by line while the command is running, instead of
+    /// once it has exited.
+    pub continuous: bool,
+
+    /// Time after which a command which has not exited yet is killed, ignored for
+    /// continuous commands.
+    pub timeout: Option<Duration>,
+
+    /// Whether the standard error is captured along with the standard output.
+    pub capture_stderr: bool,
+
+    /// Amount of lines kept, older lines are discarded.
+    pub max_lines: usize,
+}
+
+/// What a command has printed so far.
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    /// The last lines of output, without line terminators.
+    pub lines: Vec<String>,
+
+    /// Why the command failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Runs a command repeatedly on a background thread.
+///
+/// Commands are started again once they have exited and the interval has elapsed. Like the
+/// [`Sampler`](crate::util::Sampler), the renderer only ever picks up the most recent output
+/// and the thread is stopped as soon as the runner is dropped, killing the command if it is
+/// still running.
+#[derive(Debug)]
+pub struct CommandRunner {
+    options: CommandOptions,
+    shared: Arc<RunnerShared>,
+    thread: Thread,
+}
+
+#[derive(Debug)]
+struct RunnerShared {
+    latest: Mutex<Option<CommandOutput>>,
+    child: Mutex<Option<Child>>,
+    interval_millis: AtomicU64,
+    running: AtomicBool,
+}
+
+impl RunnerShared {
+    fn publish(&self, output: CommandOutput) {
+        *self.latest.lock().unwrap() = Some(output);
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    /// Kills the current command, including everything it has started itself.
+    fn kill(&self) {
+        if let Some(child) = self.child.lock().unwrap().as_mut() {
+            #[cfg(unix)]
+            unsafe {
+                // The command has been made the leader of its own process group
+                libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+            }
+
+            let _ = child.kill();
+        }
+    }
+}
+
+impl CommandRunner {
+    /// Starts running the command on a new thread, the first run starts immediately.
+    pub fn spawn(options: CommandOptions, interval: Duration) -> Self {
+        let shared = Arc::new(RunnerShared {
+            latest: Mutex::new(None),
+            child: Mutex::new(None),
+            interval_millis: AtomicU64::new(interval.as_millis() as u64),
+            running: AtomicBool::new(true),
+        });
+
+        let thread_shared = shared.clone();
+        let thread_options = options.clone();
+        let handle = std::thread::Builder::new()
+            .name(String::from("Command runner"))
+            .spawn(move || {
+                while thread_shared.is_running() {
+                    let output = run(&thread_options, &thread_shared);
+
+                    if !thread_shared.is_running() {
+                        break;
+                    }
+
+                    thread_shared.publish(output);
+
+                    let interval = thread_shared.interval_millis.load(Ordering::Acquire);
+                    std::thread::park_timeout(Duration::from_millis(interval));
+                }
+            })
+            .expect("Failed to spawn command runner thread");
+
+        Self {
+            options,
+            shared,
+            thread: handle.thread().clone(),
+        }
+    }
+
+    /// The options this runner has been started with.
+    pub fn options(&self) -> &CommandOptions {
+        &self.options
+    }
+
+    /// Changes the time between a command exiting and it being started again.
+    pub fn set_interval(&self, interval: Duration) {
+        let interval = interval.as_millis() as u64;
+
+        if self.shared.interval_millis.swap(interval, Ordering::AcqRel) > interval {
+            self.thread.unpark();
+        }
+    }
+
+    /// Takes the output produced since the last call, if any.
+    pub fn take(&self) -> Option<CommandOutput> {
+        self.shared.latest.lock().unwrap().take()
+    }
+}
+
+impl Drop for CommandRunner {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Release);
+        self.shared.kill();
+        self.thread.unpark();
+    }
+}
+
+/// Creates a command running a command line in the system shell.
+fn shell_command(command_line: &str) -> Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        let mut command = Command::new("cmd");
+        command
+            .arg("/C")
+            .arg(command_line)
+            .creation_flags(CREATE_NO_WINDOW);
+        command
+    }
+
+    #[cfg(not(windows))]
+    {
+        use std::os::unix::process::CommandExt;
+
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+
+        unsafe {
+            // Allows killing pipelines and other children of the shell along with it
+            command.pre_exec(|| {
+                libc::setpgid(0, 0);
+                Ok(())
+            });
+        }
+
+        command
+    }
+}
+
+/// Collects the lines read from a pipe, keeping only the last ones.
+fn spawn_reader(
+    pipe: impl Read + Send + 'static,
+    lines: Arc<Mutex<VecDeque<String>>>,
+    options: &CommandOptions,
+    shared: &Arc<RunnerShared>,
+) -> JoinHandle<()> {
+    let continuous = options.continuous;
+    let max_lines = options.max_lines.max(1);
+    let shared = shared.clone();
+
+    std::thread::Builder::new()
+        .name(String::from("Command output reader"))
+        .spawn(move || {
+            let mut reader = BufReader::new(pipe);
+            let mut buffer = Vec::new();
+
+            loop {
+                buffer.clear();
+                match reader.read_until(b'\n', &mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+
+                let line = String::from_utf8_lossy(&buffer);
+                let line = line.trim_end_matches(&['\r', '\n'][..]);
+
+                let mut lines = lines.lock().unwrap();
+                lines.push_back(line.to_string());
+                while lines.len() > max_lines {
+                    lines.pop_front();
+                }
+
+                if continuous {
+                    shared.publish(CommandOutput {
+                        lines: lines.iter().cloned().collect(),
+                        error: None,
+                    });
+                }
+            }
+        })
+        .expect("Failed to spawn command output reader thread")
+}
+
+/// Runs the command once and collects its output.
+fn run(options: &CommandOptions, shared: &Arc<RunnerShared>) -> CommandOutput {
+    if options.command_line.trim().is_empty() {
+        return CommandOutput {
+            lines: Vec::new(),
+            error: Some(String::from("No command configured")),
+        };
+    }
+
+    let mut command = shell_command(&options.command_line);
+    command.stdin(Stdio::null()).stdout(Stdio::piped());
+
+    if options.capture_stderr {
+        command.stderr(Stdio::piped());
+    } else {
+        command.stderr(Stdio::null());
+    }
+
+    let mut child = match command.spawn() {
+        Ok(v) => v,
+        Err(err) => {
+            return CommandOutput {
+                lines: Vec::new(),
+                error: Some(format!("Failed to start command: {}", err)),
+            }
+        }
+    };
+
+    let lines = Arc::new(Mutex::new(VecDeque::new()));
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|pipe| Box::new(pipe) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|pipe| Box::new(pipe) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|pipe| spawn_reader(pipe, lines.clone(), options, shared))
+    .collect::<Vec<_>>();
+
+    *shared.child.lock().unwrap() = Some(child);
+
+    let result = wait(options, shared);
+
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    let child = shared.child.lock().unwrap().take();
+    if let Some(mut child) = child {
+        // Reaps the process if it has been killed
+        let _ = child.wait();
+    }
+
+    let error = match result {
+        Ok(status) if status.success() => None,
+        Ok(status) => Some(format!("Command failed with {}", status)),
+        Err(err) => Some(err),
+    };
+
+    let lines = lines.lock().unwrap().drain(..).collect();
+    CommandOutput { lines, error }
+}
+
+/// Waits for the current command to exit, killing it once the timeout elapses.
+fn wait(options: &CommandOptions, shared: &RunnerShared) -> Result<ExitStatus, String> {
+    let deadline = options
+        .timeout
+        .filter(|_| !options.continuous)
+        .map(|timeout| (Instant::now() + timeout, timeout));
+
+    loop {
+        let status = match shared.child.lock().unwrap().as_mut() {
+            None => return Err(String::from("Command has been stopped")),
+            Some(child) => child.try_wait(),
+        };
+
+        match status {
+            Ok(Some(status)) => return Ok(status),
+            Ok(None) => {}
+            Err(err) => return Err(format!("Failed to wait for command: {}", err)),
+        }
+
+        if !shared.is_running() {
+            shared.kill();
+            return Err(String::from("Command has been stopped"));
+        }
+
+        if let Some((deadline, timeout)) = deadline {
+            if Instant::now() >= deadline {
+                shared.kill();
+                return Err(format!(
+                    "Command timed out after {:.1} seconds",
+                    timeout.as_secs_f32()
+                ));
+            }
+        }
+
+        std::thread::park_timeout(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs::{File, Metadata};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use crate::util::FileWatcher;
+
+/// Follows a file as it grows, like `tail -F`.
+///
+/// The file is only read again when the [`FileWatcher`] reports a change. Truncated files
+/// are read again from the start, and when the file is replaced, for example by log rotation,
+/// the rest of the old file is read before following the new one.
+pub struct FileTail {
+    watcher: FileWatcher,
+    file: Option<TailedFile>,
+
+    /// Maximum amount of bytes read from the end of the file when starting to follow it.
+    initial_bytes: u64,
+
+    /// The last line read, which hasn't been terminated yet.
+    partial: Vec<u8>,
+    started: bool,
+
+    /// Why the last read failed, reported again until a read succeeds.
+    error: Option<(ErrorKind, String)>,
+}
+
+struct TailedFile {
+    file: File,
+    offset: u64,
+    identity: Option<u64>,
+
+    /// Whether everything up to the first line break is skipped, because reading started
+    /// in the middle of a line.
+    skip_partial: bool,
+}
+
+impl FileTail {
+    /// Starts following the given file, it doesn't need to exist yet.
+    pub fn new(path: impl Into<PathBuf>, initial_bytes: u64) -> Result<Self, notify::Error> {
+        Ok(Self {
+            watcher: FileWatcher::new(path)?,
+            file: None,
+            initial_bytes,
+            partial: Vec::new(),
+            started: false,
+            error: None,
+        })
+    }
+
+    /// Reads the lines which have been completed since the last call.
+    ///
+    /// Errors are returned until reading succeeds again, even if the file didn't change in
+    /// the meantime.
+    pub fn read_lines(&mut self, out: &mut Vec<String>) -> std::io::Result<()> {
+        if self.started && !self.watcher.has_changed() {
+            return match &self.error {
+                None => Ok(()),
+                Some((kind, message)) => Err(std::io::Error::new(*kind, message.clone())),
+            };
+        }
+
+        let first = !self.started;
+        self.started = true;
+
+        let result = self.read_changes(first, out);
+        self.error = result
+            .as_ref()
+            .err()
+            .map(|err| (err.kind(), err.to_string()));
+
+        result
+    }
+
+    fn read_changes(&mut self, first: bool, out: &mut Vec<String>) -> std::io::Result<()> {
+        if let Some(file) = &mut self.file {
+            // Rotated files may have been written to until they were replaced
+            file.read_available(&mut self.partial, out)?;
+        }
+
+        let metadata = std::fs::metadata(self.watcher.path())?;
+        let replaced = self
+            .file
+            .as_ref()
+            .map_or(true, |file| file.identity != identity(&metadata));
+
+        if replaced {
+            if !self.partial.is_empty() {
+                push_line(&self.partial, out);
+                self.partial.clear();
+            }
+
+            let mut file = File::open(self.watcher.path())?;
+            let offset = if first {
+                metadata.len().saturating_sub(self.initial_bytes)
+            } else {
+                0
+            };
+
+            file.seek(SeekFrom::Start(offset))?;
+
+            let tailed = self.file.insert(TailedFile {
+                file,
+                offset,
+                identity: identity(&metadata),
+                skip_partial: offset > 0,
+            });
+
+            tailed.read_available(&mut self.partial, out)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TailedFile {
+    /// Reads everything appended since the last read.
+    fn read_available(
+        &mut self,
+        partial: &mut Vec<u8>,
+        out: &mut Vec<String>,
+    ) -> std::io::Result<()> {
+        let length = self.file.metadata()?.len();
+        if length < self.offset {
+            // The file has been truncated
+            self.file.seek(SeekFrom::Start(0))?;
+            self.offset = 0;
+            partial.clear();
+        }
+
+        let mut buffer = Vec::new();
+        self.file.read_to_end(&mut buffer)?;
+        self.offset += buffer.len() as u64;
+
+        let mut rest = buffer.as_slice();
+        if self.skip_partial {
+            match rest.iter().position(|b| *b == b'\n') {
+                None => return Ok(()),
+                Some(end) => {
+                    rest = &rest[end + 1..];
+                    self.skip_partial = false;
+                }
+            }
+        }
+
+        while let Some(end) = rest.iter().position(|b| *b == b'\n') {
+            partial.extend_from_slice(&rest[..end]);
+            push_line(partial, out);
+            partial.clear();
+
+            rest = &rest[end + 1..];
+        }
+
+        partial.extend_from_slice(rest);
+        Ok(())
+    }
+}
+
+fn push_line(line: &[u8], out: &mut Vec<String>) {
+    let line = String::from_utf8_lossy(line);
+    out.push(line.trim_end_matches('\r').to_string());
+}
+
+/// Identifies a file independent of its path, so replaced files can be detected.
+#[cfg(unix)]
+fn identity(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.ino())
+}
+
+/// Identifies a file independent of its path, so replaced files can be detected.
+///
+/// There is no stable way to do so on other platforms, there only truncation is detected.
+#[cfg(not(unix))]
+fn identity(_metadata: &Metadata) -> Option<u64> {
+    None
+}
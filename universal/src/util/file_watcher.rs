@@ -0,0 +1,104 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+
+use notify::{raw_watcher, RawEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Watches a single file for changes using the native file system notifications.
+///
+/// The parent directory is watched instead of the file itself, this way the watcher keeps
+/// working when the file is replaced, rotated or created after the watcher.
+pub struct FileWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<RawEvent>,
+    path: PathBuf,
+    file_name: Option<OsString>,
+}
+
+impl FileWatcher {
+    /// Starts watching the given file.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, notify::Error> {
+        let path = path.into();
+        let file_name = path.file_name().map(ToOwned::to_owned);
+
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (sender, receiver) = channel();
+        let mut watcher = raw_watcher(sender)?;
+        watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+            path,
+            file_name,
+        })
+    }
+
+    /// Retrieves the path of the watched file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Drains all pending notifications and determines whether any of them concerned
+    /// the watched file.
+    pub fn has_changed(&self) -> bool {
+        let mut changed = false;
+
+        while let Ok(event) = self.receiver.try_recv() {
+            changed |= match (&event.path, &self.file_name) {
+                (Some(path), Some(file_name)) => path.file_name() == Some(file_name.as_os_str()),
+                _ => true,
+            };
+        }
+
+        changed
+    }
+}
+
+/// A file which is read again whenever it is modified or another file is selected.
+///
+/// The watcher is kept as long as the path stays the same, creating a new one after every
+/// change could lose the events which arrive in the meantime.
+#[derive(Default)]
+pub struct WatchedFile {
+    path: Option<String>,
+    watcher: Option<FileWatcher>,
+}
+
+impl WatchedFile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Switches to the given file and determines whether it needs to be read again.
+    ///
+    /// This is the case on the first call, after the path changed and when the file has
+    /// been modified since the last call. An empty path means that no file is selected.
+    pub fn update(&mut self, path: &str) -> bool {
+        if self.path.as_deref() == Some(path) {
+            return self
+                .watcher
+                .as_ref()
+                .map_or(false, FileWatcher::has_changed);
+        }
+
+        self.watcher = if path.is_empty() {
+            None
+        } else {
+            match FileWatcher::new(path) {
+                Ok(watcher) => Some(watcher),
+                Err(err) => {
+                    log::warn!("Failed to watch {}: {}", path, err);
+                    None
+                }
+            }
+        };
+
+        self.path = Some(path.to_string());
+        true
+    }
+}
@@ -0,0 +1,813 @@
+use std::path::Path;
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+
+use crate::util::WatchedFile;
+
+/// Upper bound of iterations when expanding a recurrence rule.
+const MAX_EXPANSION_STEPS: usize = 100_000;
+
+/// How far into the future recurring events are expanded.
+const EXPANSION_HORIZON_DAYS: i64 = 5 * 365;
+
+/// The zone an event time is specified in.
+#[derive(Debug, Clone, PartialEq)]
+enum EventZone {
+    Utc,
+    Named(Tz),
+
+    /// Floating times are interpreted in the local time zone.
+    Floating,
+}
+
+impl EventZone {
+    fn to_utc(&self, time: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        let resolve = |time: &NaiveDateTime| match self {
+            EventZone::Utc => Some(Utc.from_utc_datetime(time)),
+            EventZone::Named(zone) => zone
+                .from_local_datetime(time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            EventZone::Floating => Local
+                .from_local_datetime(time)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        };
+
+        resolve(time).or_else(|| resolve(&(*time + Duration::hours(1))))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A parsed `RRULE`, only the commonly used parts are supported.
+#[derive(Debug, Clone)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<NaiveDateTime>,
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+impl RecurrenceRule {
+    fn parse(value: &str) -> Option<Self> {
+        let mut frequency = None;
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+
+        for part in value.split(';') {
+            let (key, value) = match part.split_once('=') {
+                None => continue,
+                Some(v) => v,
+            };
+
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        "MONTHLY" => Some(Frequency::Monthly),
+                        "YEARLY" => Some(Frequency::Yearly),
+                        _ => None,
+                    }
+                }
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|&v| v > 0)?,
+                "COUNT" => rule.count = value.parse().ok(),
+                "UNTIL" => rule.until = parse_date_time(value).map(|(time, _, _)| time),
+                "BYDAY" => {
+                    rule.by_day = value.split(',').filter_map(parse_week_day).collect();
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .filter_map(|v| v.parse::<i32>().ok())
+                        .filter(|day| (1..=31).contains(&day.unsigned_abs()))
+                        .collect();
+                }
+                "BYMONTH" => {
+                    rule.by_month = value
+                        .split(',')
+                        .filter_map(|v| v.parse().ok())
+                        .filter(|month| (1..=12).contains(month))
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        rule.frequency = frequency?;
+        Some(rule)
+    }
+
+    /// Generates the sorted candidate dates for the n-th period after the start, fails once
+    /// the period lies outside of the supported dates.
+    fn candidates(&self, start: NaiveDate, period: i64, out: &mut Vec<NaiveDate>) -> Option<()> {
+        out.clear();
+        let step = period.checked_mul(self.interval as i64)?;
+
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(days(step)?)?;
+
+                if self.matches_week_day(date) && self.matches_month(date) {
+                    out.push(date);
+                }
+            }
+            Frequency::Weekly => {
+                let offset = start.weekday().num_days_from_monday() as i64;
+                let week_start = start
+                    .checked_add_signed(Duration::days(-offset))?
+                    .checked_add_signed(days(step.checked_mul(7)?)?)?;
+
+                if self.by_day.is_empty() {
+                    out.push(week_start.checked_add_signed(Duration::days(offset))?);
+                } else {
+                    for offset in 0..7 {
+                        let date = week_start.checked_add_signed(Duration::days(offset))?;
+
+                        if self.matches_week_day(date) {
+                            out.push(date);
+                        }
+                    }
+                }
+            }
+            Frequency::Monthly => {
+                let (year, month) = add_months(start.year(), start.month(), step)?;
+
+                if self.by_month.is_empty() || self.by_month.contains(&month) {
+                    self.month_candidates(start, year, month, out)?;
+                }
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(step).ok()?)?;
+
+                if self.by_month.is_empty() {
+                    self.month_candidates(start, year, start.month(), out)?;
+                } else {
+                    let mut months = self.by_month.clone();
+                    months.sort_unstable();
+
+                    for month in months {
+                        self.month_candidates(start, year, month, out)?;
+                    }
+                }
+            }
+        }
+
+        Some(())
+    }
+
+    /// Generates the candidate dates within a single month.
+    fn month_candidates(
+        &self,
+        start: NaiveDate,
+        year: i32,
+        month: u32,
+        out: &mut Vec<NaiveDate>,
+    ) -> Option<()> {
+        let length = days_in_month(year, month)?;
+        let first = out.len();
+
+        if !self.by_month_day.is_empty() {
+            for &day in &self.by_month_day {
+                let day = if day < 0 {
+                    length as i32 + day + 1
+                } else {
+                    day
+                };
+
+                if day >= 1 && day <= length as i32 {
+                    out.extend(NaiveDate::from_ymd_opt(year, month, day as u32));
+                }
+            }
+        } else if !self.by_day.is_empty() {
+            for &(ordinal, weekday) in &self.by_day {
+                let matching = (1..=length)
+                    .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+                    .filter(|date| date.weekday() == weekday)
+                    .collect::<Vec<_>>();
+
+                match ordinal {
+                    None => out.extend(matching),
+                    Some(n) if n > 0 => out.extend(matching.get(n as usize - 1)),
+                    Some(n) => out.extend(
+                        matching
+                            .len()
+                            .checked_sub(n.unsigned_abs() as usize)
+                            .and_then(|i| matching.get(i)),
+                    ),
+                }
+            }
+        } else if let Some(date) = NaiveDate::from_ymd_opt(year, month, start.day()) {
+            out.push(date);
+        }
+
+        out[first..].sort_unstable();
+        Some(())
+    }
+
+    fn matches_week_day(&self, date: NaiveDate) -> bool {
+        self.by_day.is_empty() || self.by_day.iter().any(|(_, day)| *day == date.weekday())
+    }
+
+    fn matches_month(&self, date: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&date.month())
+    }
+}
+
+/// A single event of a calendar.
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    summary: String,
+    start: NaiveDateTime,
+    zone: EventZone,
+    duration: Duration,
+    rule: Option<RecurrenceRule>,
+    exceptions: Vec<NaiveDateTime>,
+}
+
+impl CalendarEvent {
+    /// Expands the event into its occurrences which end after `from`, stopping once
+    /// `until` has been passed.
+    fn occurrences(&self, from: &DateTime<Utc>, until: &DateTime<Utc>, out: &mut Vec<Occurrence>) {
+        let mut push = |start: &NaiveDateTime| -> bool {
+            if self.exceptions.contains(start) {
+                return true;
+            }
+
+            let start = match self.zone.to_utc(start) {
+                None => return true,
+                Some(v) => v,
+            };
+
+            if start > *until {
+                return false;
+            }
+
+            let end = start + self.duration;
+            if end > *from || (self.duration.is_zero() && start >= *from) {
+                out.push(Occurrence {
+                    summary: self.summary.clone(),
+                    start,
+                    end,
+                });
+            }
+
+            true
+        };
+
+        let rule = match &self.rule {
+            None => {
+                push(&self.start);
+                return;
+            }
+            Some(v) => v,
+        };
+
+        let (date, time) = (self.start.date(), self.start.time());
+        let mut produced = 0;
+        let mut candidates = Vec::new();
+
+        for period in 0..MAX_EXPANSION_STEPS as i64 {
+            if rule.candidates(date, period, &mut candidates).is_none() {
+                return;
+            }
+
+            for date in &candidates {
+                let start = date.and_time(time);
+
+                if start < self.start {
+                    continue;
+                }
+
+                if rule.until.map_or(false, |until| start > until) {
+                    return;
+                }
+
+                if rule.count.map_or(false, |count| produced >= count) {
+                    return;
+                }
+
+                produced += 1;
+
+                if !push(&start) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// A concrete occurrence of an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Occurrence {
+    /// Determines whether the occurrence is currently taking place.
+    pub fn is_ongoing(&self, now: &DateTime<Utc>) -> bool {
+        self.start <= *now && *now < self.end
+    }
+}
+
+/// A parsed iCalendar (RFC 5545) document.
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    events: Vec<CalendarEvent>,
+}
+
+impl Calendar {
+    /// Parses the events of an iCalendar document, malformed events are skipped.
+    pub fn parse(source: &str) -> Self {
+        let mut events = Vec::new();
+        let mut current: Option<EventBuilder> = None;
+
+        for line in unfold(source) {
+            let (name, parameters, value) = match split_content_line(&line) {
+                None => continue,
+                Some(v) => v,
+            };
+
+            match (name.as_str(), &mut current) {
+                ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                    current = Some(EventBuilder::default())
+                }
+                ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                    if let Some(event) = current.take().and_then(EventBuilder::build) {
+                        events.push(event);
+                    }
+                }
+                (_, Some(builder)) => builder.property(&name, parameters, value),
+                _ => {}
+            }
+        }
+
+        Self { events }
+    }
+
+    /// Retrieves the amount of events in the calendar.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Determines whether the calendar has no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Retrieves up to `count` occurrences which are ongoing or start after `now`, sorted
+    /// by their start.
+    pub fn upcoming(&self, now: &DateTime<Utc>, count: usize) -> Vec<Occurrence> {
+        let until = *now + Duration::days(EXPANSION_HORIZON_DAYS);
+        let mut out = Vec::new();
+
+        for event in &self.events {
+            event.occurrences(now, &until, &mut out);
+        }
+
+        out.sort_by(|a, b| a.start.cmp(&b.start));
+        out.truncate(count);
+        out
+    }
+}
+
+#[derive(Default)]
+struct EventBuilder {
+    summary: Option<String>,
+    start: Option<(NaiveDateTime, EventZone, bool)>,
+    end: Option<(NaiveDateTime, EventZone, bool)>,
+    duration: Option<Duration>,
+    rule: Option<RecurrenceRule>,
+    exceptions: Vec<NaiveDateTime>,
+}
+
+impl EventBuilder {
+    fn property(&mut self, name: &str, parameters: Vec<(String, String)>, value: String) {
+        let tz_id = parameters
+            .iter()
+            .find(|(key, _)| key == "TZID")
+            .map(|(_, value)| value.as_str());
+
+        match name {
+            "SUMMARY" => self.summary = Some(unescape(&value)),
+            "DTSTART" => self.start = parse_zoned(&value, tz_id),
+            "DTEND" => self.end = parse_zoned(&value, tz_id),
+            "DURATION" => self.duration = parse_duration(&value),
+            "RRULE" => self.rule = RecurrenceRule::parse(&value),
+            "EXDATE" => self.exceptions.extend(
+                value
+                    .split(',')
+                    .filter_map(|v| parse_zoned(v, tz_id))
+                    .map(|(time, _, _)| time),
+            ),
+            _ => {}
+        }
+    }
+
+    fn build(self) -> Option<CalendarEvent> {
+        let (start, zone, all_day) = self.start?;
+
+        let duration = match (self.end, self.duration) {
+            (Some((end, end_zone, _)), _) => match (zone.to_utc(&start), end_zone.to_utc(&end)) {
+                (Some(start), Some(end)) => end - start,
+                _ => Duration::zero(),
+            },
+            (None, Some(duration)) => duration,
+            (None, None) if all_day => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+
+        Some(CalendarEvent {
+            summary: self.summary.unwrap_or_default(),
+            start,
+            zone,
+            duration: duration.max(Duration::zero()),
+            rule: self.rule,
+            exceptions: self.exceptions,
+        })
+    }
+}
+
+/// A calendar loaded from disk which is reloaded as soon as the file changes.
+pub struct CalendarFile {
+    file: WatchedFile,
+    calendar: Calendar,
+    cache: Vec<Occurrence>,
+    cache_count: usize,
+    cache_valid_until: Option<DateTime<Utc>>,
+}
+
+impl CalendarFile {
+    pub fn new() -> Self {
+        Self {
+            file: WatchedFile::new(),
+            calendar: Calendar::default(),
+            cache: Vec::new(),
+            cache_count: 0,
+            cache_valid_until: None,
+        }
+    }
+
+    /// Loads the calendar at the given path if it changed since the last call.
+    pub fn update(&mut self, path: &str) {
+        if !self.file.update(path) {
+            return;
+        }
+
+        self.calendar = Calendar::default();
+        self.cache_valid_until = None;
+
+        if path.is_empty() {
+            return;
+        }
+
+        match std::fs::read_to_string(Path::new(path)) {
+            Ok(source) => {
+                self.calendar = Calendar::parse(&source);
+                log::debug!(
+                    "Loaded {} events from calendar \"{}\"",
+                    self.calendar.len(),
+                    path
+                );
+            }
+            Err(err) => log::error!("Failed to read calendar from \"{}\": {}", path, err),
+        }
+    }
+
+    /// Retrieves up to `count` ongoing or upcoming occurrences.
+    ///
+    /// The result is cached until the first of the occurrences starts or ends.
+    pub fn upcoming(&mut self, now: &DateTime<Utc>, count: usize) -> &[Occurrence] {
+        let valid =
+            self.cache_count == count && self.cache_valid_until.map_or(false, |until| *now < until);
+
+        if !valid {
+            self.cache = self.calendar.upcoming(now, count);
+            self.cache_count = count;
+            self.cache_valid_until = Some(
+                self.cache
+                    .iter()
+                    .flat_map(|o| [o.start, o.end])
+                    .filter(|time| time > now)
+                    .min()
+                    .unwrap_or_else(|| *now + Duration::hours(1)),
+            );
+        }
+
+        &self.cache
+    }
+}
+
+impl Default for CalendarFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Joins folded lines, continuation lines start with a space or a tab.
+fn unfold(source: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in source.lines() {
+        match (
+            line.strip_prefix(|c| c == ' ' || c == '\t'),
+            lines.last_mut(),
+        ) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+/// Splits a content line into its upper case name, parameters and value.
+fn split_content_line(line: &str) -> Option<(String, Vec<(String, String)>, String)> {
+    let mut in_quotes = false;
+    let separator = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+
+    let (head, value) = (&line[..separator], &line[separator + 1..]);
+    let mut head = head.split(';');
+    let name = head.next()?.trim().to_ascii_uppercase();
+
+    let parameters = head
+        .filter_map(|p| p.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+
+    Some((name, parameters, value.trim_end().to_string()))
+}
+
+/// Resolves escaped characters in text values.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+/// Parses a date or date time value, returns whether it was in UTC and whether it is a date.
+fn parse_date_time(value: &str) -> Option<(NaiveDateTime, bool, bool)> {
+    let value = value.trim();
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|time| (time, true, false));
+    }
+
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .map(|time| (time, false, false))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .map(|date| (date.and_time(NaiveTime::from_hms(0, 0, 0)), false, true))
+        })
+        .ok()
+}
+
+/// Parses a date time with its zone, returns the time, zone and whether it is an all day value.
+fn parse_zoned(value: &str, tz_id: Option<&str>) -> Option<(NaiveDateTime, EventZone, bool)> {
+    let (time, utc, all_day) = parse_date_time(value)?;
+
+    let zone = match (utc, tz_id.and_then(|id| id.parse::<Tz>().ok())) {
+        (true, _) => EventZone::Utc,
+        (false, Some(zone)) => EventZone::Named(zone),
+        (false, None) => EventZone::Floating,
+    };
+
+    Some((time, zone, all_day))
+}
+
+/// Parses a `DURATION` value such as `P1DT2H30M` or `P2W`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let value = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let amount = number.parse::<i64>().ok()?;
+                number.clear();
+
+                let seconds = match unit {
+                    'W' => 7 * 24 * 60 * 60,
+                    'D' => 24 * 60 * 60,
+                    'H' => 60 * 60,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+
+                total = total
+                    .checked_add(&Duration::milliseconds(amount.checked_mul(seconds * 1000)?))?;
+            }
+        }
+    }
+
+    Some(if negative { -total } else { total })
+}
+
+/// Parses a `BYDAY` entry such as `MO`, `2TU` or `-1FR`.
+fn parse_week_day(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+
+    // Values ending in multi-byte characters can't be split there, but are invalid anyway
+    let (ordinal, day) = (value.get(..split)?, value.get(split..)?);
+
+    let day = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        Some(ordinal.trim_start_matches('+').parse().ok()?)
+    };
+
+    Some((ordinal, day))
+}
+
+/// Adds a (possibly negative) amount of months to a year and month.
+fn add_months(year: i32, month: u32, months: i64) -> Option<(i32, u32)> {
+    let total = (year as i64 * 12 + (month as i64 - 1)).checked_add(months)?;
+
+    Some((
+        i32::try_from(total.div_euclid(12)).ok()?,
+        total.rem_euclid(12) as u32 + 1,
+    ))
+}
+
+/// Creates a duration of whole days, fails instead of panicking for huge amounts.
+fn days(amount: i64) -> Option<Duration> {
+    amount
+        .checked_mul(24 * 60 * 60 * 1000)
+        .map(Duration::milliseconds)
+}
+
+/// Determines the length of a month, fails for months outside of the supported dates.
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = add_months(year, month, 1)?;
+
+    Some(
+        NaiveDate::from_ymd_opt(next_year, next_month, 1)?
+            .pred_opt()?
+            .day(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expands a single event starting at `start` with the given rule.
+    fn expand(start: &str, rule: &str) -> Vec<NaiveDate> {
+        let source = format!(
+            "BEGIN:VCALENDAR\n\
+             BEGIN:VEVENT\n\
+             SUMMARY:Test\n\
+             DTSTART:{}\n\
+             RRULE:{}\n\
+             END:VEVENT\n\
+             END:VCALENDAR\n",
+            start, rule
+        );
+
+        Calendar::parse(&source)
+            .upcoming(&Utc.ymd(2020, 1, 1).and_hms(0, 0, 0), 100)
+            .iter()
+            .map(|occurrence| occurrence.start.naive_utc().date())
+            .collect()
+    }
+
+    fn dates(dates: &[(i32, u32, u32)]) -> Vec<NaiveDate> {
+        dates
+            .iter()
+            .map(|&(year, month, day)| NaiveDate::from_ymd(year, month, day))
+            .collect()
+    }
+
+    #[test]
+    fn expands_daily_with_count() {
+        assert_eq!(
+            expand("20210104T090000Z", "FREQ=DAILY;INTERVAL=2;COUNT=3"),
+            dates(&[(2021, 1, 4), (2021, 1, 6), (2021, 1, 8)])
+        );
+    }
+
+    #[test]
+    fn expands_weekly_by_day() {
+        assert_eq!(
+            expand("20210104T090000Z", "FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=5"),
+            dates(&[
+                (2021, 1, 4),
+                (2021, 1, 6),
+                (2021, 1, 8),
+                (2021, 1, 11),
+                (2021, 1, 13)
+            ])
+        );
+    }
+
+    #[test]
+    fn expands_monthly_by_ordinal_day() {
+        assert_eq!(
+            expand("20210112T090000Z", "FREQ=MONTHLY;BYDAY=2TU;COUNT=3"),
+            dates(&[(2021, 1, 12), (2021, 2, 9), (2021, 3, 9)])
+        );
+        assert_eq!(
+            expand("20210129T090000Z", "FREQ=MONTHLY;BYDAY=-1FR;COUNT=3"),
+            dates(&[(2021, 1, 29), (2021, 2, 26), (2021, 3, 26)])
+        );
+    }
+
+    #[test]
+    fn expands_monthly_by_negative_month_day() {
+        assert_eq!(
+            expand("20200131T090000Z", "FREQ=MONTHLY;BYMONTHDAY=-1;COUNT=3"),
+            dates(&[(2020, 1, 31), (2020, 2, 29), (2020, 3, 31)])
+        );
+    }
+
+    #[test]
+    fn stops_at_until() {
+        assert_eq!(
+            expand("20210104T090000Z", "FREQ=WEEKLY;UNTIL=20210118T090000Z"),
+            dates(&[(2021, 1, 4), (2021, 1, 11), (2021, 1, 18)])
+        );
+    }
+
+    #[test]
+    fn ignores_out_of_range_values() {
+        assert_eq!(
+            expand("20210104T090000Z", "FREQ=YEARLY;BYMONTH=0,13;COUNT=2"),
+            dates(&[(2021, 1, 4), (2022, 1, 4)])
+        );
+        assert_eq!(
+            expand("20210104T090000Z", "FREQ=MONTHLY;INTERVAL=4294967295"),
+            dates(&[(2021, 1, 4)])
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+use crate::util::expand_placeholders;
+
+/// Placeholders understood by [`expand_json_template`], meant to be shown to the user.
+pub const JSON_TEMPLATE_HELP: &str = "\
+Placeholders are JSONPath expressions:
+{$.name} - a member of the root object
+{$.list[0]} {$.list[-1]} - the first and the last element of an array
+{$['some key']} - a member with special characters in its name
+{$.list[*].name} - the member of all elements, separated by commas
+{$..name} - all members with this name, at any depth
+{$.value|.1} - a number rounded to one decimal
+Write {{ for a literal {";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Member(String),
+    Index(i64),
+    Wildcard,
+
+    /// Selects the value itself and everything nested in it, the next segment is applied to
+    /// all of them.
+    Descendants,
+}
+
+/// A parsed JSONPath expression, see <https://goessner.net/articles/JsonPath/>.
+///
+/// Only the selectors needed to pick values out of a document are supported, filters,
+/// slices and unions are not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let mut rest = path
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| format!("{} does not start with $", path))?;
+
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                segments.push(Segment::Descendants);
+
+                rest = if after.starts_with('[') {
+                    after
+                } else {
+                    parse_member(after, &mut segments)
+                        .ok_or_else(|| format!("Missing name after .. in {}", path))?
+                };
+            } else if let Some(after) = rest.strip_prefix('.') {
+                rest = parse_member(after, &mut segments)
+                    .ok_or_else(|| format!("Missing name after . in {}", path))?;
+            } else if let Some(after) = rest.strip_prefix('[') {
+                rest = parse_bracket(after, &mut segments)
+                    .ok_or_else(|| format!("Invalid brackets in {}", path))?;
+            } else {
+                return Err(format!("Unexpected {} in {}", rest, path));
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Selects all values matching the expression, in document order.
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![root];
+
+        for segment in &self.segments {
+            let mut next = Vec::new();
+
+            for value in current {
+                match segment {
+                    Segment::Member(name) => next.extend(value.get(name.as_str())),
+                    Segment::Index(index) => {
+                        if let Value::Array(items) = value {
+                            let index = if *index < 0 {
+                                items.len() as i64 + index
+                            } else {
+                                *index
+                            };
+
+                            if index >= 0 {
+                                next.extend(items.get(index as usize));
+                            }
+                        }
+                    }
+                    Segment::Wildcard => match value {
+                        Value::Array(items) => next.extend(items),
+                        Value::Object(members) => next.extend(members.values()),
+                        _ => {}
+                    },
+                    Segment::Descendants => collect_descendants(value, &mut next),
+                }
+            }
+
+            current = next;
+        }
+
+        current
+    }
+}
+
+/// Parses a member name or `*` following a dot, returns the rest of the expression.
+fn parse_member<'a>(text: &'a str, segments: &mut Vec<Segment>) -> Option<&'a str> {
+    let end = text.find(&['.', '['][..]).unwrap_or(text.len());
+    let name = &text[..end];
+
+    match name {
+        "" => return None,
+        "*" => segments.push(Segment::Wildcard),
+        name => segments.push(Segment::Member(name.to_string())),
+    }
+
+    Some(&text[end..])
+}
+
+/// Parses the contents of brackets following the opening one, returns the rest of the
+/// expression.
+fn parse_bracket<'a>(text: &'a str, segments: &mut Vec<Segment>) -> Option<&'a str> {
+    let text = text.trim_start();
+
+    if let Some(quote) = text.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        let quoted = &text[1..];
+        let end = quoted.find(quote)?;
+        let rest = quoted[end + 1..].trim_start().strip_prefix(']')?;
+
+        segments.push(Segment::Member(quoted[..end].to_string()));
+        return Some(rest);
+    }
+
+    let end = text.find(']')?;
+    match text[..end].trim() {
+        "*" => segments.push(Segment::Wildcard),
+        index => segments.push(Segment::Index(index.parse().ok()?)),
+    }
+
+    Some(&text[end + 1..])
+}
+
+fn collect_descendants<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(value);
+
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_descendants(item, out)),
+        Value::Object(members) => members
+            .values()
+            .for_each(|member| collect_descendants(member, out)),
+        _ => {}
+    }
+}
+
+/// Interprets a value as a number, strings containing a number included.
+pub fn json_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Formats a value for display, strings are written without quotes.
+pub fn write_json_value(value: &Value, decimals: Option<usize>, out: &mut String) {
+    if let Some(number) = decimals.and_then(|_| json_number(value)) {
+        out.push_str(&format!("{:.*}", decimals.unwrap(), number));
+        return;
+    }
+
+    match value {
+        Value::String(text) => out.push_str(text),
+        value => out.push_str(&value.to_string()),
+    }
+}
+
+/// Expands all placeholders in a template with the values they select from a document,
+/// see [`JSON_TEMPLATE_HELP`].
+///
+/// Placeholders which don't select anything expand to nothing, while invalid ones are kept
+/// as they are.
+pub fn expand_json_template(template: &str, document: &Value, out: &mut String) {
+    expand_placeholders(template, out, |name, argument, out| {
+        // Quoted member names may contain colons themselves
+        let placeholder = match argument {
+            None => Cow::Borrowed(name),
+            Some(argument) => Cow::Owned(format!("{}:{}", name, argument)),
+        };
+
+        let (path, decimals) = match placeholder.rsplit_once('|') {
+            Some((path, format)) => (path, format.trim().strip_prefix('.')),
+            None => (placeholder.as_ref(), None),
+        };
+
+        let decimals = decimals.and_then(|decimals| decimals.parse::<usize>().ok());
+
+        let path = match JsonPath::parse(path) {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+
+        for (i, value) in path.select(document).into_iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+
+            write_json_value(value, decimals, out);
+        }
+
+        true
+    });
+}
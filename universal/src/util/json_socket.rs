@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::Thread;
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// Reads JSON documents from a Unix socket on a background thread.
+///
+/// The socket is read from until the other side closes the connection, each document sent
+/// in the meantime replaces the previous one. Documents may be separated by whitespace such
+/// as line breaks. Afterwards the socket is connected to again once the delay has elapsed,
+/// so programs can either keep the connection open and send updates, or send a single
+/// document to every client.
+#[derive(Debug)]
+pub struct JsonSocketReader {
+    path: PathBuf,
+    shared: Arc<ReaderShared>,
+    thread: Thread,
+}
+
+#[derive(Debug)]
+struct ReaderShared {
+    latest: Mutex<Option<Result<Value, String>>>,
+    running: AtomicBool,
+
+    /// The current connection, shut down to interrupt a blocking read when stopping.
+    #[cfg(unix)]
+    stream: Mutex<Option<std::os::unix::net::UnixStream>>,
+}
+
+impl ReaderShared {
+    fn publish(&self, document: Result<Value, String>) {
+        *self.latest.lock().unwrap() = Some(document);
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+}
+
+impl JsonSocketReader {
+    /// Starts reading from the socket on a new thread, connecting to it immediately.
+    pub fn spawn(path: impl Into<PathBuf>, reconnect_delay: Duration) -> Self {
+        let path = path.into();
+
+        let shared = Arc::new(ReaderShared {
+            latest: Mutex::new(None),
+            running: AtomicBool::new(true),
+            #[cfg(unix)]
+            stream: Mutex::new(None),
+        });
+
+        let thread_shared = shared.clone();
+        let thread_path = path.clone();
+        let handle = std::thread::Builder::new()
+            .name(String::from("JSON socket reader"))
+            .spawn(move || {
+                while thread_shared.is_running() {
+                    if let Err(err) = read_socket(&thread_path, &thread_shared) {
+                        if thread_shared.is_running() {
+                            thread_shared.publish(Err(err));
+                        }
+                    }
+
+                    std::thread::park_timeout(reconnect_delay);
+                }
+            })
+            .expect("Failed to spawn JSON socket reader thread");
+
+        Self {
+            path,
+            shared,
+            thread: handle.thread().clone(),
+        }
+    }
+
+    /// The path of the socket this reader connects to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Takes the latest document received since the last call, or why reading failed.
+    pub fn take(&self) -> Option<Result<Value, String>> {
+        self.shared.latest.lock().unwrap().take()
+    }
+}
+
+impl Drop for JsonSocketReader {
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Release);
+
+        #[cfg(unix)]
+        if let Some(stream) = self.shared.stream.lock().unwrap().as_ref() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+
+        self.thread.unpark();
+    }
+}
+
+/// Connects to the socket and publishes all documents until the connection is closed.
+#[cfg(unix)]
+fn read_socket(path: &Path, shared: &ReaderShared) -> Result<(), String> {
+    use std::io::BufReader;
+    use std::os::unix::net::UnixStream;
+
+    let stream = UnixStream::connect(path)
+        .map_err(|err| format!("Failed to connect to {}: {}", path.display(), err))?;
+
+    *shared.stream.lock().unwrap() = stream.try_clone().ok();
+
+    // Stopping might have missed the connection while it was being stored
+    if !shared.is_running() {
+        return Ok(());
+    }
+
+    let documents =
+        serde_json::Deserializer::from_reader(BufReader::new(stream)).into_iter::<Value>();
+
+    let mut result = Ok(());
+    for document in documents {
+        match document {
+            Ok(document) => shared.publish(Ok(document)),
+            Err(err) => {
+                result = Err(format!("Invalid JSON from {}: {}", path.display(), err));
+                break;
+            }
+        }
+    }
+
+    *shared.stream.lock().unwrap() = None;
+    result
+}
+
+#[cfg(not(unix))]
+fn read_socket(_path: &Path, _shared: &ReaderShared) -> Result<(), String> {
+    Err(String::from(
+        "Unix sockets are not supported on this platform",
+    ))
+}
@@ -1,7 +1,35 @@
+pub use ansi::*;
+pub use astronomy::*;
+pub use command::*;
 pub use delayed::*;
+pub use file_tail::*;
+pub use file_watcher::*;
+pub use ical::*;
+pub use json_path::*;
+pub use json_socket::*;
 pub use notifier::*;
 pub use owned_codec::*;
+pub use power_supply::*;
+pub use sampler::*;
+pub use send_cell::*;
+pub use system_info::*;
+pub use template::*;
+pub use todo::*;
 
+mod ansi;
+mod astronomy;
+mod command;
 mod delayed;
+mod file_tail;
+mod file_watcher;
+mod ical;
+mod json_path;
+mod json_socket;
 mod notifier;
 mod owned_codec;
+mod power_supply;
+mod sampler;
+mod send_cell;
+mod system_info;
+mod template;
+mod todo;
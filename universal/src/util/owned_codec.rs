@@ -1,5 +1,7 @@
-use skia_safe::{Codec, Data};
+use skia_safe::{codec, Codec, Data, Image};
 use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use thiserror::Error;
 
 #[derive(Debug)]
 pub struct OwnedCodec {
@@ -37,3 +39,24 @@ impl DerefMut for OwnedCodec {
         self.get_codec_mut()
     }
 }
+
+#[derive(Debug, Error)]
+pub enum ImageLoadError {
+    #[error("an I/O error occurred: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("no decoder is available")]
+    NoDecoder,
+
+    #[error("failed to decode image: {0:?}")]
+    Decode(codec::Result),
+}
+
+/// Reads and decodes an image file.
+pub fn load_image(path: impl AsRef<Path>) -> Result<Image, ImageLoadError> {
+    let mut codec = std::fs::read(path)
+        .map(OwnedCodec::new)?
+        .ok_or(ImageLoadError::NoDecoder)?;
+
+    codec.get_image(None, None).map_err(ImageLoadError::Decode)
+}
@@ -0,0 +1,168 @@
+use std::path::Path;
+use std::time::Duration;
+
+/// Default location of the power supply class in sysfs.
+pub const DEFAULT_POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ChargeState {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl ChargeState {
+    fn parse(value: &str) -> Self {
+        match value {
+            "Charging" => Self::Charging,
+            "Discharging" => Self::Discharging,
+            "Full" => Self::Full,
+            "Not charging" => Self::NotCharging,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChargeState::Charging => "Charging",
+            ChargeState::Discharging => "Discharging",
+            ChargeState::Full => "Full",
+            ChargeState::NotCharging => "Not charging",
+            ChargeState::Unknown => "Unknown",
+        }
+    }
+}
+
+/// The state of a single battery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryStatus {
+    /// Name of the battery, such as `BAT0`.
+    pub name: String,
+
+    /// Charge level in the range 0 to 1.
+    pub capacity: f32,
+
+    pub state: ChargeState,
+
+    /// Estimated time until the battery is empty or, while charging, full.
+    pub time_remaining: Option<Duration>,
+}
+
+/// Reads the state of all batteries found in the given power supply directory.
+///
+/// The root is usually [`DEFAULT_POWER_SUPPLY_ROOT`], each battery is a subdirectory with
+/// a `type` file containing `Battery`. Batteries are sorted by their name.
+pub fn read_batteries(root: &Path) -> std::io::Result<Vec<BatteryStatus>> {
+    let mut batteries = Vec::new();
+
+    for entry in std::fs::read_dir(root)? {
+        let path = entry?.path();
+
+        if read_value(&path, "type").as_deref() != Some("Battery") {
+            continue;
+        }
+
+        if let Some(status) = read_battery(&path) {
+            batteries.push(status);
+        }
+    }
+
+    batteries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(batteries)
+}
+
+fn read_battery(path: &Path) -> Option<BatteryStatus> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let number = |file: &str| read_value(path, file).and_then(|v| v.parse::<f64>().ok());
+
+    // Batteries either report energy (µWh, µW) or charge (µAh, µA)
+    let (now, full, rate) = match (number("energy_now"), number("energy_full")) {
+        (Some(now), Some(full)) => (Some(now), Some(full), number("power_now")),
+        _ => (
+            number("charge_now"),
+            number("charge_full"),
+            number("current_now"),
+        ),
+    };
+
+    let capacity = match (number("capacity"), now, full) {
+        (Some(percent), _, _) => percent / 100.0,
+        (None, Some(now), Some(full)) if full > 0.0 => now / full,
+        _ => return None,
+    };
+
+    let state = read_value(path, "status")
+        .map(|v| ChargeState::parse(&v))
+        .unwrap_or(ChargeState::Unknown);
+
+    let hours = match (state, now, full, rate.map(f64::abs)) {
+        (ChargeState::Discharging, Some(now), _, Some(rate)) if rate > 0.0 => Some(now / rate),
+        (ChargeState::Charging, Some(now), Some(full), Some(rate)) if rate > 0.0 => {
+            Some((full - now).max(0.0) / rate)
+        }
+        _ => None,
+    };
+
+    Some(BatteryStatus {
+        name,
+        capacity: capacity.clamp(0.0, 1.0) as f32,
+        state,
+        time_remaining: hours.map(|hours| Duration::from_secs_f64(hours * 60.0 * 60.0)),
+    })
+}
+
+fn read_value(path: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(path.join(file))
+        .ok()
+        .map(|v| v.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_supply(root: &Path, name: &str, values: &[(&str, &str)]) {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for (file, value) in values {
+            std::fs::write(dir.join(file), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn reads_batteries_from_fake_sysfs_tree() {
+        let root =
+            std::env::temp_dir().join(format!("snowland-power-supply-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+
+        write_supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("capacity", "50"),
+                ("status", "Discharging"),
+                ("energy_now", "25000000"),
+                ("energy_full", "50000000"),
+                ("power_now", "12500000"),
+            ],
+        );
+        write_supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+
+        let batteries = read_batteries(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            batteries.unwrap(),
+            vec![BatteryStatus {
+                name: String::from("BAT0"),
+                capacity: 0.5,
+                state: ChargeState::Discharging,
+                time_remaining: Some(Duration::from_secs(2 * 60 * 60)),
+            }]
+        );
+    }
+}
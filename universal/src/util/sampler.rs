@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::Thread;
+use std::time::Duration;
+
+/// Periodically produces values on a background thread.
+///
+/// This keeps slow work, such as reading files or running processes, off the render thread.
+/// The renderer only ever picks up the most recent value. The thread is stopped as soon as
+/// the sampler is dropped.
+#[derive(Debug)]
+pub struct Sampler<T>
+where
+    T: Send + 'static,
+{
+    shared: Arc<SamplerShared<T>>,
+    thread: Thread,
+}
+
+#[derive(Debug)]
+struct SamplerShared<T> {
+    latest: Mutex<Option<T>>,
+    interval_millis: AtomicU64,
+    running: AtomicBool,
+}
+
+impl<T> Sampler<T>
+where
+    T: Send + 'static,
+{
+    /// Starts sampling on a new thread, the first sample is taken immediately.
+    pub fn spawn<F>(name: &str, interval: Duration, mut sample: F) -> Self
+    where
+        F: FnMut() -> T + Send + 'static,
+    {
+        let shared = Arc::new(SamplerShared {
+            latest: Mutex::new(None),
+            interval_millis: AtomicU64::new(interval.as_millis() as u64),
+            running: AtomicBool::new(true),
+        });
+
+        let thread_shared = shared.clone();
+        let handle = std::thread::Builder::new()
+            .name(format!("{} sampler", name))
+            .spawn(move || {
+                while thread_shared.running.load(Ordering::Acquire) {
+                    let value = sample();
+                    *thread_shared.latest.lock().unwrap() = Some(value);
+
+                    let interval = thread_shared.interval_millis.load(Ordering::Acquire);
+                    std::thread::park_timeout(Duration::from_millis(interval));
+                }
+            })
+            .expect("Failed to spawn sampler thread");
+
+        Self {
+            shared,
+            thread: handle.thread().clone(),
+        }
+    }
+
+    /// Changes the interval between two samples.
+    pub fn set_interval(&self, interval: Duration) {
+        let interval = interval.as_millis() as u64;
+
+        if self.shared.interval_millis.swap(interval, Ordering::AcqRel) > interval {
+            // Wake up the thread so a shorter interval takes effect immediately
+            self.thread.unpark();
+        }
+    }
+
+    /// Requests a new sample as soon as possible.
+    pub fn trigger(&self) {
+        self.thread.unpark();
+    }
+
+    /// Takes the value produced since the last call, if any.
+    pub fn take(&self) -> Option<T> {
+        self.shared.latest.lock().unwrap().take()
+    }
+}
+
+impl<T> Drop for Sampler<T>
+where
+    T: Send + 'static,
+{
+    fn drop(&mut self) {
+        self.shared.running.store(false, Ordering::Release);
+        self.thread.unpark();
+    }
+}
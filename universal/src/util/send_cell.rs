@@ -0,0 +1,39 @@
+use std::ops::{Deref, DerefMut};
+
+/// Allows keeping values which are not marked as sendable in the state of a renderer.
+///
+/// Renderers are created on the user interface thread and then moved to the render thread,
+/// so their state has to be [`Send`]. skia-safe doesn't mark most of its objects as such,
+/// even those which own all of their data or are reference counted atomically.
+pub struct SendCell<T>(T);
+
+// Only values which can be moved between threads are accepted by the constructor
+unsafe impl<T> Send for SendCell<T> {}
+
+impl<T> SendCell<T> {
+    /// Wraps a value to make it sendable.
+    ///
+    /// # Safety
+    ///
+    /// The value must not share any state with other values which may be used by another
+    /// thread at the same time, unless the state is synchronized. This is the case for
+    /// objects which own all of their data, such as paragraphs, or immutable objects which
+    /// are reference counted atomically, such as runtime effects.
+    pub unsafe fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for SendCell<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SendCell<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
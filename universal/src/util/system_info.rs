@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+/// Used and total amount of a resource, in bytes.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Usage {
+    pub used: u64,
+    pub total: u64,
+}
+
+impl Usage {
+    /// Retrieves the used fraction in the range 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used as f32 / self.total as f32
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskUsage {
+    pub mount_point: String,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkThroughput {
+    pub interface: String,
+
+    /// Received bytes per second.
+    pub receive: f64,
+
+    /// Transmitted bytes per second.
+    pub transmit: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Temperature {
+    pub sensor: String,
+    pub celsius: f32,
+}
+
+/// The state of the system at a point in time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemSnapshot {
+    /// Usage of all processors combined in the range 0 to 1.
+    pub cpu_total: f32,
+
+    /// Usage per processor core in the range 0 to 1.
+    pub cpu_cores: Vec<f32>,
+
+    pub memory: Usage,
+    pub swap: Usage,
+    pub load_average: [f32; 3],
+    pub disks: Vec<DiskUsage>,
+    pub networks: Vec<NetworkThroughput>,
+    pub temperatures: Vec<Temperature>,
+}
+
+/// Accumulated processor times as found in `/proc/stat`.
+#[derive(Debug, Copy, Clone, Default)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuTimes {
+    /// Computes the usage between an earlier and this sample.
+    fn usage_since(&self, earlier: &CpuTimes) -> f32 {
+        let total = self.total.saturating_sub(earlier.total);
+        let idle = self.idle.saturating_sub(earlier.idle);
+
+        if total == 0 {
+            0.0
+        } else {
+            1.0 - (idle as f32 / total as f32)
+        }
+    }
+}
+
+/// Reads system information from `/proc` and `/sys`.
+///
+/// Rates, such as processor usage and network throughput, are computed from the difference
+/// between two calls to [`SystemProbe::sample`], so the first sample reports them as zero.
+/// Everything which can't be read is left empty, which is always the case on systems
+/// without procfs.
+#[derive(Debug)]
+pub struct SystemProbe {
+    previous_cpu: Vec<CpuTimes>,
+    previous_network: HashMap<String, (u64, u64)>,
+    previous_instant: Option<Instant>,
+}
+
+impl SystemProbe {
+    pub fn new() -> Self {
+        Self {
+            previous_cpu: Vec::new(),
+            previous_network: HashMap::new(),
+            previous_instant: None,
+        }
+    }
+
+    /// Takes a snapshot of the current system state.
+    pub fn sample(&mut self) -> SystemSnapshot {
+        let mut snapshot = SystemSnapshot::default();
+
+        let now = Instant::now();
+        let elapsed = self
+            .previous_instant
+            .map(|previous| now.duration_since(previous).as_secs_f64());
+        self.previous_instant = Some(now);
+
+        self.sample_cpu(&mut snapshot);
+        self.sample_network(&mut snapshot, elapsed);
+        Self::sample_memory(&mut snapshot);
+        Self::sample_load_average(&mut snapshot);
+        Self::sample_disks(&mut snapshot);
+        Self::sample_temperatures(&mut snapshot);
+
+        snapshot
+    }
+
+    fn sample_cpu(&mut self, snapshot: &mut SystemSnapshot) {
+        let stat = match std::fs::read_to_string("/proc/stat") {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        // The first line is the sum of all cores, followed by one line per core
+        let times = stat
+            .lines()
+            .filter(|line| line.starts_with("cpu"))
+            .map(|line| {
+                let values = line
+                    .split_whitespace()
+                    .skip(1)
+                    .take(8)
+                    .filter_map(|v| v.parse::<u64>().ok())
+                    .collect::<Vec<_>>();
+
+                CpuTimes {
+                    idle: values.get(3).copied().unwrap_or(0) + values.get(4).copied().unwrap_or(0),
+                    total: values.iter().sum(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if times.len() == self.previous_cpu.len() {
+            let mut usages = times
+                .iter()
+                .zip(self.previous_cpu.iter())
+                .map(|(now, earlier)| now.usage_since(earlier));
+
+            snapshot.cpu_total = usages.next().unwrap_or(0.0);
+            snapshot.cpu_cores = usages.collect();
+        } else {
+            snapshot.cpu_cores = vec![0.0; times.len().saturating_sub(1)];
+        }
+
+        self.previous_cpu = times;
+    }
+
+    fn sample_memory(snapshot: &mut SystemSnapshot) {
+        let info = match std::fs::read_to_string("/proc/meminfo") {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        let mut values = HashMap::new();
+        for line in info.lines() {
+            if let Some((key, value)) = line.split_once(':') {
+                let kilobytes = value
+                    .split_whitespace()
+                    .next()
+                    .and_then(|v| v.parse::<u64>().ok());
+
+                if let Some(kilobytes) = kilobytes {
+                    values.insert(key, kilobytes * 1024);
+                }
+            }
+        }
+
+        let value = |key| values.get(key).copied().unwrap_or(0);
+
+        snapshot.memory = Usage {
+            used: value("MemTotal").saturating_sub(value("MemAvailable")),
+            total: value("MemTotal"),
+        };
+
+        snapshot.swap = Usage {
+            used: value("SwapTotal").saturating_sub(value("SwapFree")),
+            total: value("SwapTotal"),
+        };
+    }
+
+    fn sample_load_average(snapshot: &mut SystemSnapshot) {
+        if let Ok(load) = std::fs::read_to_string("/proc/loadavg") {
+            for (out, value) in snapshot
+                .load_average
+                .iter_mut()
+                .zip(load.split_whitespace())
+            {
+                *out = value.parse().unwrap_or(0.0);
+            }
+        }
+    }
+
+    fn sample_disks(snapshot: &mut SystemSnapshot) {
+        let mounts = match std::fs::read_to_string("/proc/mounts") {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        for line in mounts.lines() {
+            let mut parts = line.split_whitespace();
+            let (device, mount_point) = match (parts.next(), parts.next()) {
+                (Some(device), Some(mount_point)) => (device, unescape_mount_point(mount_point)),
+                _ => continue,
+            };
+
+            // Only real block devices are of interest, this skips tmpfs, proc and friends
+            if !device.starts_with("/dev/")
+                || snapshot.disks.iter().any(|d| d.mount_point == mount_point)
+            {
+                continue;
+            }
+
+            if let Some(usage) = file_system_usage(&mount_point) {
+                snapshot.disks.push(DiskUsage { mount_point, usage });
+            }
+        }
+    }
+
+    fn sample_network(&mut self, snapshot: &mut SystemSnapshot, elapsed: Option<f64>) {
+        let dev = match std::fs::read_to_string("/proc/net/dev") {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        let mut current = HashMap::new();
+
+        // The first two lines are headers
+        for line in dev.lines().skip(2) {
+            let (interface, values) = match line.split_once(':') {
+                Some(v) => v,
+                None => continue,
+            };
+
+            let values = values
+                .split_whitespace()
+                .filter_map(|v| v.parse::<u64>().ok())
+                .collect::<Vec<_>>();
+
+            let (received, transmitted) = match (values.get(0), values.get(8)) {
+                (Some(received), Some(transmitted)) => (*received, *transmitted),
+                _ => continue,
+            };
+
+            let interface = interface.trim().to_string();
+            let rate = |now: u64, earlier: u64| match elapsed {
+                Some(elapsed) if elapsed > 0.0 => now.saturating_sub(earlier) as f64 / elapsed,
+                _ => 0.0,
+            };
+
+            let (receive, transmit) = match self.previous_network.get(&interface) {
+                Some((earlier_received, earlier_transmitted)) => (
+                    rate(received, *earlier_received),
+                    rate(transmitted, *earlier_transmitted),
+                ),
+                None => (0.0, 0.0),
+            };
+
+            snapshot.networks.push(NetworkThroughput {
+                interface: interface.clone(),
+                receive,
+                transmit,
+            });
+
+            current.insert(interface, (received, transmitted));
+        }
+
+        self.previous_network = current;
+    }
+
+    fn sample_temperatures(snapshot: &mut SystemSnapshot) {
+        let devices = match std::fs::read_dir("/sys/class/hwmon") {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        let mut devices = devices
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        devices.sort();
+
+        for device in devices {
+            let name = read_trimmed(&device.join("name")).unwrap_or_else(|| {
+                device
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+
+            let mut inputs = match std::fs::read_dir(&device) {
+                Ok(entries) => entries
+                    .filter_map(Result::ok)
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .filter(|file| file.starts_with("temp") && file.ends_with("_input"))
+                    .collect::<Vec<_>>(),
+                Err(_) => continue,
+            };
+            inputs.sort();
+
+            for input in inputs {
+                let millidegrees =
+                    match read_trimmed(&device.join(&input)).and_then(|v| v.parse::<i64>().ok()) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+
+                let prefix = input.trim_end_matches("_input");
+                let label = read_trimmed(&device.join(format!("{}_label", prefix)))
+                    .unwrap_or_else(|| prefix.to_string());
+
+                snapshot.temperatures.push(Temperature {
+                    sensor: format!("{} {}", name, label),
+                    celsius: millidegrees as f32 / 1000.0,
+                });
+            }
+        }
+    }
+}
+
+impl Default for SystemProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|v| v.trim().to_string())
+}
+
+/// Resolves the octal escapes (such as `\040` for spaces) used in `/proc/mounts`.
+fn unescape_mount_point(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+
+        let escaped = rest.get(i + 1..i + 4);
+        match escaped.and_then(|v| u8::from_str_radix(v, 8).ok()) {
+            Some(c) => {
+                out.push(c as char);
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(unix)]
+fn file_system_usage(mount_point: &str) -> Option<Usage> {
+    use std::ffi::CString;
+
+    let path = CString::new(mount_point).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+
+    // The path is a valid C string and the result is only read if the call succeeded
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+
+        stat.assume_init()
+    };
+
+    let block_size = stat.f_frsize as u64;
+    let total = stat.f_blocks as u64 * block_size;
+    let free = stat.f_bfree as u64 * block_size;
+
+    Some(Usage {
+        used: total.saturating_sub(free),
+        total,
+    })
+}
+
+#[cfg(not(unix))]
+fn file_system_usage(_mount_point: &str) -> Option<Usage> {
+    None
+}
@@ -0,0 +1,165 @@
+use std::fmt::Write;
+use std::io::Read;
+
+use chrono::Local;
+
+/// Maximum amount of bytes a `{file:...}` placeholder reads.
+const MAX_FILE_SIZE: u64 = 64 * 1024;
+
+/// Placeholders understood by [`expand_template`], meant to be shown to the user.
+pub const TEMPLATE_HELP: &str = "\
+Placeholders:
+{time} or {time:%H:%M} - the current local time, with an optional format
+{date} or {date:%d.%m.%Y} - the current local date, with an optional format
+{hostname} {user} {uptime}
+{env:VARIABLE} - the value of an environment variable
+{file:/path/to/file} - the contents of a file, without trailing whitespace
+{display.name} {display.width} {display.height} - the display the module is on
+Write {{ for a literal {";
+
+/// Values of placeholders only the renderer knows about.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateContext {
+    pub display_name: String,
+    pub display_width: i32,
+    pub display_height: i32,
+}
+
+/// Determines whether a template contains placeholders, escaped and unclosed braces don't
+/// count.
+pub fn has_placeholders(template: &str) -> bool {
+    let mut found = false;
+
+    expand_placeholders(template, &mut String::new(), |_, _, _| {
+        found = true;
+        false
+    });
+
+    found
+}
+
+/// Expands all placeholders in a template, see [`TEMPLATE_HELP`].
+///
+/// Unknown placeholders are kept as they are and placeholders which can't be evaluated,
+/// such as missing files, expand to nothing. This may block on the file system, so it
+/// should not be called on the render thread.
+pub fn expand_template(template: &str, context: &TemplateContext, out: &mut String) {
+    expand_placeholders(template, out, |name, argument, out| {
+        let _ = match (name, argument) {
+            ("time", format) => {
+                write!(out, "{}", Local::now().format(format.unwrap_or("%H:%M:%S")))
+            }
+            ("date", format) => {
+                write!(out, "{}", Local::now().format(format.unwrap_or("%Y-%m-%d")))
+            }
+            ("hostname", None) => write!(out, "{}", hostname().unwrap_or_default()),
+            ("user", None) => write!(out, "{}", user().unwrap_or_default()),
+            ("uptime", None) => write_uptime(out),
+            ("env", Some(variable)) => {
+                write!(out, "{}", std::env::var(variable).unwrap_or_default())
+            }
+            ("file", Some(path)) => write!(out, "{}", read_file(path).unwrap_or_default()),
+            ("display.name", None) => write!(out, "{}", context.display_name),
+            ("display.width", None) => write!(out, "{}", context.display_width),
+            ("display.height", None) => write!(out, "{}", context.display_height),
+            _ => return false,
+        };
+
+        true
+    });
+}
+
+/// Replaces the `{name}` and `{name:argument}` placeholders of a template, this is what all
+/// templates shown to the user are built on.
+///
+/// `f` writes the value of a placeholder and returns `false` without writing anything if
+/// the placeholder is unknown, unknown placeholders are kept as they are. `{{` is written
+/// as a literal `{`, and a `{` which is never closed is kept along with the rest of the
+/// template.
+pub fn expand_placeholders(
+    template: &str,
+    out: &mut String,
+    mut f: impl FnMut(&str, Option<&str>, &mut String) -> bool,
+) {
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+
+        if let Some(escaped) = rest[start..].strip_prefix("{{") {
+            out.push('{');
+            rest = escaped;
+            continue;
+        }
+
+        let end = match rest[start..].find('}') {
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+            Some(v) => start + v,
+        };
+
+        let placeholder = &rest[start + 1..end];
+        let (name, argument) = match placeholder.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (placeholder, None),
+        };
+
+        if !f(name, argument, out) {
+            out.push_str(&rest[start..=end]);
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    out.push_str(rest);
+}
+
+fn hostname() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .map(|name| name.trim().to_string())
+        .ok()
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+}
+
+fn user() -> Option<String> {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+}
+
+/// Writes the system uptime as days, hours and minutes, nothing if it is unknown.
+fn write_uptime(out: &mut String) -> std::fmt::Result {
+    let seconds = match std::fs::read_to_string("/proc/uptime")
+        .ok()
+        .and_then(|uptime| uptime.split_whitespace().next()?.parse::<f64>().ok())
+    {
+        None => return Ok(()),
+        Some(v) => v as u64,
+    };
+
+    let minutes = seconds / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+
+    if days > 0 {
+        write!(out, "{}d {}:{:02}", days, hours, minutes)
+    } else {
+        write!(out, "{}:{:02}", hours, minutes)
+    }
+}
+
+fn read_file(path: &str) -> Option<String> {
+    let mut content = String::new();
+
+    std::fs::File::open(path)
+        .ok()?
+        .take(MAX_FILE_SIZE)
+        .read_to_string(&mut content)
+        .ok()?;
+
+    content.truncate(content.trim_end().len());
+    Some(content)
+}
@@ -0,0 +1,209 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A single task of a todo.txt file, see <https://github.com/todotxt/todo.txt>.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    /// Index of the line in the file the task has been read from.
+    pub line: usize,
+
+    /// The line as it is in the file.
+    pub raw: String,
+
+    pub done: bool,
+
+    /// Priority letter from A to Z, completed tasks keep theirs in a `pri:` tag.
+    pub priority: Option<char>,
+    pub completion_date: Option<NaiveDate>,
+    pub creation_date: Option<NaiveDate>,
+
+    /// The text after the dates and priority, without the `due:` tag.
+    pub description: String,
+    pub projects: Vec<String>,
+    pub contexts: Vec<String>,
+    pub due: Option<NaiveDate>,
+}
+
+impl Task {
+    /// Parses a line of a todo.txt file, empty lines yield [`None`].
+    pub fn parse(line: usize, raw: &str) -> Option<Self> {
+        let mut rest = raw.trim();
+        if rest.is_empty() {
+            return None;
+        }
+
+        let done = match rest.strip_prefix("x ") {
+            Some(v) => {
+                rest = v;
+                true
+            }
+            None => false,
+        };
+
+        let completion_date = if done { take_date(&mut rest) } else { None };
+
+        let mut priority = None;
+        if !done {
+            let bytes = rest.as_bytes();
+            if bytes.len() >= 4
+                && bytes[0] == b'('
+                && bytes[1].is_ascii_uppercase()
+                && bytes[2] == b')'
+                && bytes[3] == b' '
+            {
+                priority = Some(bytes[1] as char);
+                rest = &rest[4..];
+            }
+        }
+
+        let creation_date = take_date(&mut rest);
+
+        let mut task = Self {
+            line,
+            raw: raw.to_string(),
+            done,
+            priority,
+            completion_date,
+            creation_date,
+            description: String::new(),
+            projects: Vec::new(),
+            contexts: Vec::new(),
+            due: None,
+        };
+
+        for word in rest.split_whitespace() {
+            if let Some(project) = word.strip_prefix('+').filter(|v| !v.is_empty()) {
+                task.projects.push(project.to_string());
+            } else if let Some(context) = word.strip_prefix('@').filter(|v| !v.is_empty()) {
+                task.contexts.push(context.to_string());
+            } else if let Some(due) = word
+                .strip_prefix("due:")
+                .and_then(|due| NaiveDate::parse_from_str(due, DATE_FORMAT).ok())
+            {
+                task.due = Some(due);
+                continue;
+            } else if let Some(letter) = word.strip_prefix("pri:").filter(|_| done) {
+                task.priority = letter.chars().next().filter(char::is_ascii_uppercase);
+                continue;
+            }
+
+            if !task.description.is_empty() {
+                task.description.push(' ');
+            }
+
+            task.description.push_str(word);
+        }
+
+        Some(task)
+    }
+
+    /// Determines whether the task is still open while its due date has passed.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.done && self.due.map_or(false, |due| due < today)
+    }
+
+    /// Creates the line of this task once it has been completed.
+    ///
+    /// The priority is kept as a `pri:` tag, as is convention for todo.txt.
+    fn completed_line(&self, today: NaiveDate) -> String {
+        let mut rest = self.raw.trim();
+
+        if let Some(priority) = self.priority {
+            rest = rest
+                .strip_prefix(&format!("({}) ", priority))
+                .unwrap_or(rest);
+        }
+
+        let mut line = format!("x {} {}", today.format(DATE_FORMAT), rest);
+        if let Some(priority) = self.priority {
+            line.push_str(&format!(" pri:{}", priority));
+        }
+
+        line
+    }
+}
+
+/// Removes a leading date followed by a space or the end of the text.
+fn take_date(text: &mut &str) -> Option<NaiveDate> {
+    let candidate = text.get(..10)?;
+    let after = &text[10..];
+
+    if !(after.is_empty() || after.starts_with(' ')) {
+        return None;
+    }
+
+    let date = NaiveDate::parse_from_str(candidate, DATE_FORMAT).ok()?;
+    *text = after.trim_start();
+
+    Some(date)
+}
+
+/// Reads all tasks of a todo.txt file.
+pub fn read_tasks(path: &Path) -> std::io::Result<Vec<Task>> {
+    let content = std::fs::read_to_string(path)?;
+
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter_map(|(line, raw)| Task::parse(line, raw))
+        .collect())
+}
+
+/// Marks a task as done in the file it has been read from.
+///
+/// This fails if the line of the task has been changed since the task was read, so edits
+/// made in the meantime are never overwritten.
+pub fn complete_task(path: &Path, task: &Task, today: NaiveDate) -> std::io::Result<()> {
+    // Replace the file a link points to instead of the link itself
+    let path = std::fs::canonicalize(path)?;
+    let content = std::fs::read_to_string(&path)?;
+    let mut lines = content.split_inclusive('\n').collect::<Vec<_>>();
+
+    let current = lines
+        .get(task.line)
+        .map(|line| line.trim_end_matches(&['\r', '\n'][..]));
+    if current != Some(task.raw.as_str()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "the task has been changed in the meantime",
+        ));
+    }
+
+    let original = lines[task.line];
+    let ending = &original[original.trim_end_matches(&['\r', '\n'][..]).len()..];
+
+    let replacement = format!("{}{}", task.completed_line(today), ending);
+    lines[task.line] = &replacement;
+
+    replace_file(&path, lines.concat().as_bytes())
+}
+
+/// Writes the content to a temporary file next to the original and renames it over the
+/// original, so the file is never left partially written.
+fn replace_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut temp_name = OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+
+        std::fs::set_permissions(&temp_path, std::fs::metadata(path)?.permissions())?;
+        std::fs::rename(&temp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    result
+}
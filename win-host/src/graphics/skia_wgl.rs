@@ -101,6 +101,15 @@ impl SnowlandRenderer for SkiaWGLSnowlandRender {
     fn get_size(&self) -> Result<(u64, u64), Self::Error> {
         Ok(self.worker.get_size()?)
     }
+
+    fn get_pointer_position(&self) -> Result<Option<IPoint>, Self::Error> {
+        // Querying the cursor fails while the desktop is locked, which is not an error
+        Ok(self
+            .worker
+            .get_cursor_position()
+            .ok()
+            .map(|(x, y)| IPoint::new(x, y)))
+    }
 }
 
 #[derive(Debug, Error)]
@@ -1,5 +1,6 @@
-use windows::Win32::Foundation::HWND;
-use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
+use windows::Win32::Foundation::{HWND, POINT};
+use windows::Win32::Graphics::Gdi::ScreenToClient;
+use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, GetWindowRect};
 
 use crate::WinApiError;
 
@@ -38,6 +39,19 @@ impl Worker {
         }
     }
 
+    /// Retrieves the position of the cursor relative to the window.
+    pub fn get_cursor_position(&self) -> Result<(i32, i32), WinApiError> {
+        let mut point = POINT::default();
+
+        if !unsafe { GetCursorPos(&mut point) }.as_bool()
+            || !unsafe { ScreenToClient(self.window, &mut point) }.as_bool()
+        {
+            Err(WinApiError::from_win32())
+        } else {
+            Ok((point.x, point.y))
+        }
+    }
+
     // /// Sets another window as a child of the worker window.
     // pub fn reparent_other_as_child(&self, other: HWND) {
     //     unsafe { SetParent(other, self.window) };
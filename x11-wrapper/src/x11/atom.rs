@@ -88,4 +88,13 @@ impl XAtom<'static> {
 
     /// The X11 `XA_PIXMAP` atom.
     pub const PIXMAP: Self = Self::standard(xlib_sys::XA_PIXMAP);
+
+    /// The X11 `XA_WINDOW` atom.
+    pub const WINDOW: Self = Self::standard(xlib_sys::XA_WINDOW);
+
+    /// The X11 `XA_ATOM` atom.
+    pub const ATOM: Self = Self::standard(xlib_sys::XA_ATOM);
+
+    /// The X11 `XA_CARDINAL` atom.
+    pub const CARDINAL: Self = Self::standard(xlib_sys::XA_CARDINAL);
 }
@@ -2,6 +2,13 @@ use crate::xlib_sys;
 use crate::{XAtom, XLibError, XScreen};
 use std::ffi::CString;
 
+unsafe extern "C" fn ignore_error_handler(
+    _display: *mut xlib_sys::Display,
+    _event: *mut xlib_sys::XErrorEvent,
+) -> i32 {
+    0
+}
+
 /// The heart of an X11 connection.
 ///
 /// In the context of XLib this represents a connection to the X11 server.
@@ -118,6 +125,31 @@ impl XDisplay {
         debug_assert!(atom != 0);
         unsafe { XAtom::new(atom, self) }
     }
+
+    /// Runs a function while ignoring errors reported by the X server.
+    ///
+    /// By default XLib terminates the process on errors. Requests racing with other clients,
+    /// such as querying a window which is being destroyed, are expected to fail sometimes and
+    /// should be wrapped in this function. The function needs to detect failed requests by
+    /// their return values.
+    ///
+    /// Please note that the XLib error handler is global, errors of other displays are ignored
+    /// as well while the function runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function issuing the requests
+    pub fn ignore_errors<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.sync(false);
+        let previous = unsafe { xlib_sys::XSetErrorHandler(Some(ignore_error_handler)) };
+        let result = f();
+
+        // Errors are reported asynchronously, make sure all of them arrived
+        self.sync(false);
+        unsafe { xlib_sys::XSetErrorHandler(previous) };
+
+        result
+    }
 }
 
 impl Drop for XDisplay {
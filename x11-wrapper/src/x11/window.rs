@@ -3,6 +3,7 @@ use crate::{XAtom, XDisplay, XDrawable, XScreen, XVisual};
 
 use std::fmt::Debug;
 use std::mem::MaybeUninit;
+use std::os::raw::c_ulong;
 use x11::xlib::Drawable;
 
 /// Describes the possible format of a X11 window property.
@@ -219,19 +220,35 @@ impl<'a> XWindow<'a> {
     }
 
     /// Retrieves the attributes of the window.
+    ///
+    /// # Panics
+    ///
+    /// If the window does not exist (anymore), use [`XWindow::try_get_attributes`] for
+    /// windows of other clients.
     pub fn get_attributes(&self) -> XWindowAttributes<'a> {
-        let mut raw = MaybeUninit::uninit();
-        let raw = unsafe {
-            xlib_sys::XGetWindowAttributes(self.display.handle(), self.handle, raw.as_mut_ptr());
+        self.try_get_attributes()
+            .expect("Failed to retrieve the window attributes")
+    }
 
-            raw.assume_init()
+    /// Retrieves the attributes of the window, if it still exists.
+    ///
+    /// Failures are reported to the error handler as well, see [`XDisplay::ignore_errors`].
+    pub fn try_get_attributes(&self) -> Option<XWindowAttributes<'a>> {
+        let mut raw = MaybeUninit::uninit();
+        let status = unsafe {
+            xlib_sys::XGetWindowAttributes(self.display.handle(), self.handle, raw.as_mut_ptr())
         };
 
+        if status == 0 {
+            return None;
+        }
+
         unsafe {
+            let raw = raw.assume_init();
             let screen = XScreen::new(raw.screen, self.display);
             let visual = XVisual::new(raw.visual);
 
-            XWindowAttributes::new(raw, screen, visual)
+            Some(XWindowAttributes::new(raw, screen, visual))
         }
     }
 
@@ -432,6 +449,127 @@ impl<'a> XWindow<'a> {
     pub fn delete_property(&self, property: XAtom) {
         unsafe { xlib_sys::XDeleteProperty(self.display.handle(), self.handle, property.handle()) };
     }
+
+    /// Reads a property holding a list of windows, such as `_NET_CLIENT_LIST_STACKING`.
+    ///
+    /// The list is empty if the property does not exist or is not of type `WINDOW`.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The X atom identifying the property
+    pub fn get_window_list_property(&self, property: XAtom) -> Vec<XWindow<'a>> {
+        self.get_long_list_property(property, XAtom::WINDOW)
+            .into_iter()
+            .map(|handle| unsafe { XWindow::new(handle, self.display) })
+            .collect()
+    }
+
+    /// Reads a property holding a list of atoms, such as `_NET_WM_WINDOW_TYPE`.
+    ///
+    /// The list is empty if the property does not exist or is not of type `ATOM`.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The X atom identifying the property
+    pub fn get_atom_list_property(&self, property: XAtom) -> Vec<XAtom<'a>> {
+        self.get_long_list_property(property, XAtom::ATOM)
+            .into_iter()
+            .map(|handle| unsafe { XAtom::new(handle, self.display) })
+            .collect()
+    }
+
+    /// Reads a property holding a list of numbers, such as `_NET_FRAME_EXTENTS`.
+    ///
+    /// The list is empty if the property does not exist or is not of type `CARDINAL`.
+    ///
+    /// # Arguments
+    ///
+    /// * `property` - The X atom identifying the property
+    pub fn get_cardinal_list_property(&self, property: XAtom) -> Vec<u32> {
+        self.get_long_list_property(property, XAtom::CARDINAL)
+            .into_iter()
+            .map(|value| value as u32)
+            .collect()
+    }
+
+    /// Reads a 32 bit list property of the given type.
+    fn get_long_list_property(&self, property: XAtom, ty: XAtom) -> Vec<c_ulong> {
+        // The properties read this way are small, 4096 elements are more than enough
+        match self.get_property(property, 0, 4096, false, ty) {
+            Some((data, _))
+                if data.format() == WindowPropertyDataFormat::Bit32 && data.ty() == ty =>
+            {
+                // XLib hands out 32 bit properties as an array of longs, even if a long is
+                // 64 bits wide
+                let items = unsafe {
+                    std::slice::from_raw_parts(data.data as *const c_ulong, data.length())
+                };
+
+                items.to_vec()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Translates coordinates relative to this window into coordinates relative to another
+    /// window.
+    ///
+    /// Returns `None` if the windows are not on the same screen.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x coordinate relative to this window
+    /// * `y` - The y coordinate relative to this window
+    /// * `target` - The window the coordinates should be relative to
+    pub fn translate_coordinates(&self, x: i32, y: i32, target: &XWindow) -> Option<(i32, i32)> {
+        let mut target_x = 0;
+        let mut target_y = 0;
+        let mut child = 0;
+
+        let same_screen = unsafe {
+            xlib_sys::XTranslateCoordinates(
+                self.display.handle(),
+                self.handle,
+                target.handle,
+                x,
+                y,
+                &mut target_x,
+                &mut target_y,
+                &mut child,
+            )
+        };
+
+        (same_screen != 0).then(|| (target_x, target_y))
+    }
+
+    /// Queries the position of the pointer relative to this window.
+    ///
+    /// Returns `None` if the pointer is not on the same screen as this window.
+    pub fn query_pointer(&self) -> Option<(i32, i32)> {
+        let mut root = 0;
+        let mut child = 0;
+        let mut root_x = 0;
+        let mut root_y = 0;
+        let mut x = 0;
+        let mut y = 0;
+        let mut mask = 0;
+
+        let same_screen = unsafe {
+            xlib_sys::XQueryPointer(
+                self.display.handle(),
+                self.handle,
+                &mut root,
+                &mut child,
+                &mut root_x,
+                &mut root_y,
+                &mut x,
+                &mut y,
+                &mut mask,
+            )
+        };
+
+        (same_screen != 0).then(|| (x, y))
+    }
 }
 
 impl<'a> XDrawable<'a> for XWindow<'a> {
@@ -447,7 +585,6 @@ impl<'a> XDrawable<'a> for XWindow<'a> {
 /// Properties of an X11 window.
 #[derive(Debug)]
 pub struct XWindowAttributes<'a> {
-    #[allow(dead_code)]
     inner: xlib_sys::XWindowAttributes,
     screen: XScreen<'a>,
     visual: XVisual<'a>,
@@ -486,4 +623,19 @@ impl<'a> XWindowAttributes<'a> {
     pub fn visual(&self) -> &XVisual<'a> {
         &self.visual
     }
+
+    /// Determines whether the window and all of its ancestors are mapped.
+    pub fn is_viewable(&self) -> bool {
+        self.inner.map_state == xlib_sys::IsViewable
+    }
+
+    /// Retrieves the width of the window, excluding its border.
+    pub fn width(&self) -> u32 {
+        self.inner.width as _
+    }
+
+    /// Retrieves the height of the window, excluding its border.
+    pub fn height(&self) -> u32 {
+        self.inner.height as _
+    }
 }